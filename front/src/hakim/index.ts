@@ -131,7 +131,10 @@ export const getActionHint = (tactic: string) => {
 
 export const sendTactic = (tactic: string) => {
     console.log(`tactic: `, tactic);
-    return checkErrorAndUpdate(() => instance.run_tactic(tactic));
+    return checkErrorAndUpdate(async () => {
+        const report = await instance.run_tactic(tactic);
+        return report && `error[${report.code}]: ${report.text}`;
+    });
 };
 
 export const notationList = (): string[] => {
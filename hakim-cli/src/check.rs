@@ -0,0 +1,110 @@
+use std::{path::PathBuf, process::exit};
+
+use hakim_engine::{
+    engine::{Engine, Error, LibraryProofFailure, TheoremCheck},
+    FsResolver,
+};
+
+fn usage() -> ! {
+    eprintln!("Usage: hakim check [-L [/namespace=]<dir>]... <file.v>");
+    exit(2);
+}
+
+fn print_indented(text: impl ToString) {
+    for line in text.to_string().lines() {
+        println!("    {line}");
+    }
+}
+
+fn print_report(engine: &Engine, report: &TheoremCheck) {
+    let e = match &report.error {
+        None => {
+            println!("ok      {}", report.theorem);
+            return;
+        }
+        Some(e) => e,
+    };
+    println!("FAILED  {}", report.theorem);
+    if let Some(tactic) = &e.tactic {
+        println!("    tactic: {tactic}");
+    }
+    let monitor = match &e.failure {
+        LibraryProofFailure::TacticFailed { error, monitor } => {
+            print_indented(engine.report(error));
+            Some(monitor)
+        }
+        LibraryProofFailure::Incomplete { monitor } => {
+            println!("    error: proof is incomplete");
+            Some(monitor)
+        }
+        LibraryProofFailure::InvalidProofTerm(error) => {
+            println!("    invalid proof term:");
+            print_indented(engine.report(error));
+            None
+        }
+    };
+    if let Some(monitor) = monitor {
+        for line in monitor.lines() {
+            println!("    | {line}");
+        }
+    }
+}
+
+/// Replays proofs of all theorems of a `.v` file, and exits with non zero code if
+/// any of them fails. Imports are searched in the `-L` directories and the directory
+/// of the file, after the bundled libraries. `-L /ns=dir` serves `dir/a.v` as `/ns/a`.
+pub fn check(mut args: impl Iterator<Item = String>) {
+    let mut engine = Engine::default();
+    let mut file = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-L" => {
+                let path = args.next().unwrap_or_else(|| usage());
+                match path.split_once('=') {
+                    Some((namespace, dir)) if namespace.starts_with('/') => {
+                        engine.add_library_resolver(FsResolver::with_namespace(dir, namespace))
+                    }
+                    _ => engine.add_library_path(path),
+                }
+            }
+            _ if file.is_none() => file = Some(PathBuf::from(arg)),
+            _ => usage(),
+        }
+    }
+    let file = file.unwrap_or_else(|| usage());
+    let text = std::fs::read_to_string(&file).unwrap_or_else(|e| {
+        eprintln!("Can not read {}: {e}", file.display());
+        exit(2);
+    });
+    if let Some(dir) = file.parent() {
+        engine.add_library_path(dir);
+    }
+    let name = match file.file_stem().and_then(|x| x.to_str()) {
+        Some(stem) => format!("/{stem}"),
+        None => usage(),
+    };
+    let reports = match engine.check_library_text(&name, &text) {
+        Ok(r) => r,
+        Err(Error::LibrarySyntax(lib, e)) if lib == name => {
+            println!("{}:{e}", file.display());
+            exit(1);
+        }
+        Err(e) => {
+            println!("Error in loading {}: {}", file.display(), engine.report(&e));
+            exit(1);
+        }
+    };
+    for report in &reports {
+        print_report(&engine, report);
+    }
+    let failed = reports.iter().filter(|x| x.error.is_some()).count();
+    println!(
+        "{} theorems checked, {} passed, {} failed",
+        reports.len(),
+        reports.len() - failed,
+        failed
+    );
+    if failed > 0 {
+        exit(1);
+    }
+}
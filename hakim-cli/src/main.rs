@@ -1,6 +1,8 @@
 use hakim_engine::engine::Engine;
 
-fn main() {
+mod check;
+
+fn repl() {
     let eng = Engine::default();
     let mut session = eng.interactive_session("forall a b: U, forall f: forall x: a, b, forall x y: a, forall p: eq a x y, eq b (f x) (f y)").unwrap();
     let mut rl = rustyline::Editor::<()>::new();
@@ -12,10 +14,22 @@ fn main() {
         };
         match session.run_tactic(&line) {
             Ok(_) => (),
-            Err(e) => println!("Tactic Error: {:#?}", e),
+            Err(e) => println!("{}", eng.report(&e)),
         }
         if session.is_finished() {
             break;
         }
     }
 }
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("check") => check::check(args),
+        Some(_) => {
+            eprintln!("Usage: hakim [check [-L <dir>]... <file.v>]");
+            std::process::exit(2);
+        }
+        None => repl(),
+    }
+}
@@ -0,0 +1,16 @@
+//! Checks the proofs of the bundled library, which builds and compares more terms
+//! than anything else. Run it with `cargo bench -p hakim-engine`.
+
+use std::time::Instant;
+
+use hakim_engine::engine::Engine;
+
+fn main() {
+    const RUNS: u32 = 3;
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        let mut eng = Engine::default();
+        eng.load_library_verified("/").unwrap();
+    }
+    println!("checking the library: {:?} per run", start.elapsed() / RUNS);
+}
@@ -1,2 +1,6 @@
 pub mod arith;
+pub mod farkas;
 pub mod logic;
+pub mod proof;
+pub mod refute;
+pub mod ring;
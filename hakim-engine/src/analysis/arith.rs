@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 
 use crate::brain::{self, definitely_inequal, increase_foreign_vars, remove_unused_var, type_of};
-use crate::library::prelude::{cnt, len1, mult, plus, pow, sigma, z};
+use crate::library::prelude::{cnt, len1, mult, plus, pow, sigma, z, z_of_n};
 use crate::{app_ref, brain::Term, term_ref, TermRef};
 use num_bigint::{BigInt, Sign};
 use typed_arena::Arena;
@@ -50,6 +50,26 @@ impl std::ops::Add for Poly {
     }
 }
 
+impl std::ops::Mul for Poly {
+    type Output = Poly;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let Poly(c1, v1) = self;
+        let Poly(c2, v2) = rhs;
+        let mut v = vec![];
+        for (a, x) in &v1 {
+            v.push((a * &c2, x.clone()));
+            for (b, y) in &v2 {
+                v.push((a * b, [x.clone(), y.clone()].concat()));
+            }
+        }
+        for (b, y) in v2 {
+            v.push((b * &c1, y));
+        }
+        sorter(Poly(c1 * c2, v))
+    }
+}
+
 type ArithArena<'a> = &'a Arena<ArithTree<'a>>;
 
 #[derive(Debug, Default)]
@@ -57,6 +77,11 @@ pub struct LinearPolyBuilder(HashMap<Vec<TermRef>, usize>);
 pub struct LinearPoly(BigInt, Vec<(BigInt, usize)>);
 
 impl LinearPoly {
+    #[cfg(test)]
+    pub fn new(constant: BigInt, variables: Vec<(BigInt, usize)>) -> Self {
+        LinearPoly(constant, variables)
+    }
+
     pub fn from_slice(s: &[Poly]) -> (usize, Vec<LinearPoly>) {
         let mut builder = LinearPolyBuilder::default();
         let r = s.iter().cloned().map(|x| builder.convert_poly(x)).collect();
@@ -149,10 +174,7 @@ fn pow_to_arith(op1: TermRef, op2: TermRef, arena: ArithArena<'_>) -> ArithTree<
         }
     }
     if let Term::Number { value } = op2.as_ref() {
-        if value.sign() == Sign::Minus {
-            return Const(0i32.into());
-        }
-        if *value < 5i32.into() {
+        if value.sign() != Sign::Minus && *value < 5i32.into() {
             let v = value.try_into().unwrap();
             let mut r = Const(1i32.into());
             let op1a = term_ref_to_arith(op1, arena);
@@ -301,12 +323,25 @@ fn sigma_to_arith(l: TermRef, r: TermRef, f: TermRef, arena: ArithArena<'_>) ->
     t
 }
 
+/// `↑n` of a natural number is `0` for `zero`, and `↑m + 1` for `succ m`
+fn z_of_n_to_arith(t: TermRef, n: TermRef, arena: ArithArena<'_>) -> &ArithTree<'_> {
+    match n.as_ref() {
+        Term::Axiom { unique_name, .. } if unique_name == "zero" => arena.alloc(Const(0.into())),
+        Term::App { func, op } if matches!(func.as_ref(), Term::Axiom { unique_name, .. } if unique_name == "succ") =>
+        {
+            let m = term_ref_to_arith(app_ref!(z_of_n(), op), arena);
+            arena.alloc(Plus(m, arena.alloc(Const(1.into()))))
+        }
+        _ => arena.alloc(atom_normalizer(t)),
+    }
+}
+
 fn atom_normalizer(t: TermRef) -> ArithTree<'static> {
     fn f(t: TermRef) -> TermRef {
         match t.as_ref() {
             Term::App { func, op } => {
                 let op = if type_of(op.clone()) == Ok(z()) {
-                    Poly::from(op.clone()).into_term()
+                    Poly::from(op.clone()).into_canonical_term()
                 } else {
                     f(op.clone())
                 };
@@ -355,6 +390,9 @@ fn term_ref_to_arith(t: TermRef, arena: ArithArena<'_>) -> &ArithTree<'_> {
                 },
                 _ => atom_normalizer(t),
             },
+            Term::Axiom { unique_name, .. } if unique_name == "z_of_n" => {
+                return z_of_n_to_arith(t.clone(), op2.clone(), arena);
+            }
             _ => atom_normalizer(t),
         },
         Term::Number { value } => Const(value.clone()),
@@ -417,6 +455,12 @@ impl From<TermRef> for Poly {
 }
 
 impl Poly {
+    /// The poly `constant + Σ k * x1 * x2 * ...` of monomials which are already sorted
+    /// and merged, like the monomials of other polies.
+    pub(crate) fn new(constant: BigInt, monomials: Vec<(BigInt, Vec<TermRef>)>) -> Self {
+        Poly(constant, monomials)
+    }
+
     fn into_term(self) -> TermRef {
         let mut t = term_ref!(n self.0);
         for (c, zz) in self.1 {
@@ -429,6 +473,38 @@ impl Poly {
         t
     }
 
+    /// Builds a readable term of the poly, like `2 * a * b + c + 3`, which is the same for
+    /// equal polies.
+    pub fn into_canonical_term(self) -> TermRef {
+        let mut r: Option<TermRef> = None;
+        for (c, zz) in self.1 {
+            let mut zz = zz.into_iter();
+            let mut tx = if c == 1.into() {
+                zz.next().unwrap_or_else(|| term_ref!(n 1))
+            } else {
+                term_ref!(n c)
+            };
+            for z in zz {
+                tx = app_ref!(mult(), tx, z);
+            }
+            r = Some(match r {
+                Some(r) => app_ref!(plus(), r, tx),
+                None => tx,
+            });
+        }
+        match r {
+            Some(r) if self.0 == 0.into() => r,
+            Some(r) => app_ref!(plus(), r, term_ref!(n self.0)),
+            None => term_ref!(n self.0),
+        }
+    }
+
+    /// The poly `1 * x1 * x2 * ...` for the monomial `[x1, x2, ...]`
+    pub fn monomial(mut terms: Vec<TermRef>) -> Self {
+        terms.sort();
+        Poly(0.into(), vec![(1.into(), terms)])
+    }
+
     pub fn from_subtract(t1: TermRef, t2: TermRef) -> Self {
         let arena = &Arena::with_capacity(32);
         let a1 = term_ref_to_arith(t1, arena);
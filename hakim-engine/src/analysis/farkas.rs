@@ -0,0 +1,459 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::ControlFlow,
+};
+
+use num_bigint::{BigInt, Sign};
+
+use super::arith::LinearPoly;
+
+/// Upper bound on the number of constraints during the elimination, to fail instead
+/// of running out of memory on the inputs which explode.
+const CONSTRAINT_LIMIT: usize = 20_000;
+
+pub(crate) fn gcd(a: &BigInt, b: &BigInt) -> BigInt {
+    let (mut a, mut b) = (a.magnitude().clone(), b.magnitude().clone());
+    while b.bits() != 0 {
+        let r = &a % &b;
+        a = b;
+        b = r;
+    }
+    BigInt::from(a)
+}
+
+pub(crate) fn div_floor(a: &BigInt, b: &BigInt) -> BigInt {
+    let q = a / b;
+    if &q * b != *a && (a.sign() == Sign::Minus) != (b.sign() == Sign::Minus) {
+        q - 1
+    } else {
+        q
+    }
+}
+
+/// The numbers which the variables range over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Domain {
+    Integer,
+    Rational,
+}
+
+/// `Σ coefficient * variable + constant ≥ 0`, or `> 0` if it is strict
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Constraint {
+    coefficients: BTreeMap<usize, BigInt>,
+    constant: BigInt,
+    strict: bool,
+}
+
+impl Constraint {
+    /// Linear polies of the `lia` mean `0 < p`, which over integers is `0 ≤ p - 1`
+    fn from_linear_poly(poly: &LinearPoly) -> Self {
+        let mut c = Self::from_rational(poly, false);
+        c.constant -= 1;
+        c
+    }
+
+    /// `0 < p` if it is strict, otherwise `0 ≤ p`, over rationals
+    fn from_rational(poly: &LinearPoly, strict: bool) -> Self {
+        let mut coefficients = BTreeMap::<usize, BigInt>::new();
+        for (c, v) in poly.variables() {
+            *coefficients.entry(*v).or_default() += c;
+        }
+        coefficients.retain(|_, c| c.sign() != Sign::NoSign);
+        Constraint {
+            coefficients,
+            constant: poly.constant().clone(),
+            strict,
+        }
+    }
+
+    fn coefficient(&self, var: usize) -> BigInt {
+        self.coefficients.get(&var).cloned().unwrap_or_default()
+    }
+
+    fn is_contradiction(&self) -> bool {
+        self.coefficients.is_empty()
+            && match self.constant.sign() {
+                Sign::Minus => true,
+                Sign::NoSign => self.strict,
+                Sign::Plus => false,
+            }
+    }
+
+    fn combine(a: &BigInt, first: &Self, b: &BigInt, second: &Self) -> Self {
+        let mut coefficients = first.coefficients.clone();
+        for c in coefficients.values_mut() {
+            *c *= a;
+        }
+        for (v, c) in &second.coefficients {
+            *coefficients.entry(*v).or_default() += b * c;
+        }
+        coefficients.retain(|_, c| c.sign() != Sign::NoSign);
+        Constraint {
+            coefficients,
+            constant: a * &first.constant + b * &second.constant,
+            strict: first.strict || second.strict,
+        }
+    }
+
+    /// Divides the constraint by the gcd of its coefficients. Since the variables are
+    /// integers, the constant can be rounded down, which is what makes it stronger than
+    /// the rational relaxation.
+    fn tighten(&self) -> Option<Self> {
+        if self.strict {
+            return None;
+        }
+        let g = self
+            .coefficients
+            .values()
+            .fold(BigInt::from(0), |g, c| gcd(&g, c));
+        if g <= BigInt::from(1) {
+            return None;
+        }
+        Some(Constraint {
+            coefficients: self
+                .coefficients
+                .iter()
+                .map(|(v, c)| (*v, c / &g))
+                .collect(),
+            constant: div_floor(&self.constant, &g),
+            strict: false,
+        })
+    }
+}
+
+/// One step of a derivation over `0 < p` hypotheses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// The i-th hypothesis, as `0 ≤ p - 1` over integers
+    Hyp(usize),
+    /// `a * first + b * second` of two previous steps, with positive `a` and `b`
+    Combine {
+        a: BigInt,
+        first: usize,
+        b: BigInt,
+        second: usize,
+    },
+    /// A previous step divided by the gcd of its coefficients, with constant rounded down,
+    /// which is only valid over integers
+    Tighten(usize),
+}
+
+/// A Farkas-style proof that a set of linear constraints has no solution. Each step
+/// derives a new valid constraint from previous ones, and the last one is `0 ≤ c` for
+/// some negative constant `c`, or `0 < 0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate(Vec<Step>);
+
+impl Certificate {
+    pub fn steps(&self) -> &[Step] {
+        &self.0
+    }
+
+    /// Replays the certificate with exact arithmetic, and returns true if it is a
+    /// valid derivation of a contradiction from `hyps`.
+    pub fn check(&self, hyps: &[LinearPoly]) -> bool {
+        let hyps: Vec<_> = hyps.iter().map(Constraint::from_linear_poly).collect();
+        self.replay(&hyps, Domain::Integer)
+    }
+
+    /// Like `check`, for the rational hypotheses of `find_rational_certificate`
+    pub fn check_rational(&self, hyps: &[(LinearPoly, bool)]) -> bool {
+        let hyps: Vec<_> = hyps
+            .iter()
+            .map(|(p, strict)| Constraint::from_rational(p, *strict))
+            .collect();
+        self.replay(&hyps, Domain::Rational)
+    }
+
+    fn replay(&self, hyps: &[Constraint], domain: Domain) -> bool {
+        let mut derived: Vec<Constraint> = vec![];
+        for step in &self.0 {
+            let c = match step {
+                Step::Hyp(i) => match hyps.get(*i) {
+                    Some(c) => c.clone(),
+                    None => return false,
+                },
+                Step::Combine {
+                    a,
+                    first,
+                    b,
+                    second,
+                } => {
+                    if a.sign() != Sign::Plus || b.sign() != Sign::Plus {
+                        return false;
+                    }
+                    match (derived.get(*first), derived.get(*second)) {
+                        (Some(x), Some(y)) => Constraint::combine(a, x, b, y),
+                        _ => return false,
+                    }
+                }
+                Step::Tighten(_) if domain == Domain::Rational => return false,
+                Step::Tighten(i) => match derived.get(*i).and_then(|x| x.tighten()) {
+                    Some(c) => c,
+                    None => return false,
+                },
+            };
+            derived.push(c);
+        }
+        derived.last().is_some_and(Constraint::is_contradiction)
+    }
+
+    /// Keeps only the steps which the last step depends on.
+    fn prune(self) -> Self {
+        let steps = self.0;
+        let mut needed = BTreeSet::new();
+        let mut stack = vec![steps.len() - 1];
+        while let Some(i) = stack.pop() {
+            if !needed.insert(i) {
+                continue;
+            }
+            match &steps[i] {
+                Step::Hyp(_) => (),
+                Step::Combine { first, second, .. } => stack.extend([*first, *second]),
+                Step::Tighten(j) => stack.push(*j),
+            }
+        }
+        let new_index: BTreeMap<usize, usize> =
+            needed.iter().enumerate().map(|(i, x)| (*x, i)).collect();
+        let r = needed
+            .into_iter()
+            .map(|i| match &steps[i] {
+                Step::Hyp(h) => Step::Hyp(*h),
+                Step::Combine {
+                    a,
+                    first,
+                    b,
+                    second,
+                } => Step::Combine {
+                    a: a.clone(),
+                    first: new_index[first],
+                    b: b.clone(),
+                    second: new_index[second],
+                },
+                Step::Tighten(j) => Step::Tighten(new_index[j]),
+            })
+            .collect();
+        Certificate(r)
+    }
+}
+
+struct Derivation {
+    domain: Domain,
+    steps: Vec<Step>,
+    /// the current constraints, by their coefficients, with the constant, strictness and
+    /// step of the tightest one
+    constraints: BTreeMap<Vec<(usize, BigInt)>, (BigInt, bool, usize)>,
+}
+
+impl Derivation {
+    /// Adds a constraint, and returns the step which derives a contradiction, if any.
+    fn add(&mut self, c: Constraint, step: Step) -> Option<usize> {
+        self.steps.push(step);
+        let mut index = self.steps.len() - 1;
+        let tightened = match self.domain {
+            Domain::Integer => c.tighten(),
+            Domain::Rational => None,
+        };
+        let c = match tightened {
+            Some(t) => {
+                self.steps.push(Step::Tighten(index));
+                index += 1;
+                t
+            }
+            None => c,
+        };
+        if c.coefficients.is_empty() {
+            return c.is_contradiction().then_some(index);
+        }
+        let key = c.coefficients.into_iter().collect::<Vec<_>>();
+        match self.constraints.get(&key) {
+            Some((constant, strict, _))
+                if *constant < c.constant || (*constant == c.constant && *strict >= c.strict) => {}
+            _ => {
+                self.constraints.insert(key, (c.constant, c.strict, index));
+            }
+        }
+        None
+    }
+
+    /// Picks the variable which its elimination produces the least constraints.
+    fn best_variable(&self) -> Option<usize> {
+        let mut counts = BTreeMap::<usize, (usize, usize)>::new();
+        for key in self.constraints.keys() {
+            for (v, c) in key {
+                let e = counts.entry(*v).or_default();
+                if c.sign() == Sign::Plus {
+                    e.0 += 1;
+                } else {
+                    e.1 += 1;
+                }
+            }
+        }
+        counts
+            .into_iter()
+            .min_by_key(|(_, (p, n))| (p * n) as isize - (p + n) as isize)
+            .map(|(v, _)| v)
+    }
+
+    fn search(&mut self, hyps: Vec<Constraint>) -> Option<usize> {
+        for (i, c) in hyps.into_iter().enumerate() {
+            if let Some(r) = self.add(c, Step::Hyp(i)) {
+                return Some(r);
+            }
+        }
+        while let Some(var) = self.best_variable() {
+            if let ControlFlow::Break(r) = self.eliminate(var) {
+                return r;
+            }
+        }
+        None
+    }
+
+    /// Breaks with the step which derives a contradiction, or with `None` if the
+    /// constraints exceed the limit.
+    fn eliminate(&mut self, var: usize) -> ControlFlow<Option<usize>> {
+        let old = std::mem::take(&mut self.constraints);
+        let (mut lower, mut upper) = (vec![], vec![]);
+        for (key, (constant, strict, step)) in old {
+            let c = Constraint {
+                coefficients: key.into_iter().collect(),
+                constant,
+                strict,
+            };
+            match c.coefficient(var).sign() {
+                Sign::Plus => lower.push((c, step)),
+                Sign::Minus => upper.push((c, step)),
+                Sign::NoSign => {
+                    let key = c.coefficients.into_iter().collect();
+                    self.constraints.insert(key, (c.constant, c.strict, step));
+                }
+            }
+        }
+        for (l, ls) in &lower {
+            for (u, us) in &upper {
+                let lc = l.coefficient(var);
+                let uc = -u.coefficient(var);
+                let g = gcd(&lc, &uc);
+                let a = uc / &g;
+                let b = lc / &g;
+                let c = Constraint::combine(&a, l, &b, u);
+                let step = Step::Combine {
+                    a,
+                    first: *ls,
+                    b,
+                    second: *us,
+                };
+                if let Some(r) = self.add(c, step) {
+                    return ControlFlow::Break(Some(r));
+                }
+                if self.constraints.len() > CONSTRAINT_LIMIT {
+                    return ControlFlow::Break(None);
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Searches for a certificate of infeasibility of `0 < p` for all `p` in `hyps` over
+/// integers, using Fourier-Motzkin elimination with tightening of the constraints. It
+/// is sound but not complete, since it only sees the real shadow of each elimination.
+pub fn find_certificate(hyps: &[LinearPoly]) -> Option<Certificate> {
+    let hyps = hyps.iter().map(Constraint::from_linear_poly).collect();
+    search(hyps, Domain::Integer)
+}
+
+/// Searches for a certificate of infeasibility of the hypotheses over rationals, where
+/// `(p, true)` means `0 < p` and `(p, false)` means `0 ≤ p`. Fourier-Motzkin elimination
+/// is complete over rationals, so it finds one if the hypotheses are infeasible, unless
+/// the constraints explode.
+pub fn find_rational_certificate(hyps: &[(LinearPoly, bool)]) -> Option<Certificate> {
+    let hyps = hyps
+        .iter()
+        .map(|(p, strict)| Constraint::from_rational(p, *strict))
+        .collect();
+    search(hyps, Domain::Rational)
+}
+
+fn search(hyps: Vec<Constraint>, domain: Domain) -> Option<Certificate> {
+    let mut d = Derivation {
+        domain,
+        steps: vec![],
+        constraints: BTreeMap::new(),
+    };
+    let found = d.search(hyps)?;
+    d.steps.truncate(found + 1);
+    Some(Certificate(d.steps).prune())
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+
+    use super::{find_certificate, find_rational_certificate, Certificate, Step};
+    use crate::analysis::arith::LinearPoly;
+
+    fn lp(c: i64, vars: &[(i64, usize)]) -> LinearPoly {
+        LinearPoly::new(
+            c.into(),
+            vars.iter().map(|(c, v)| (BigInt::from(*c), *v)).collect(),
+        )
+    }
+
+    #[test]
+    fn certificate_is_checked() {
+        // 0 < 2x - 4 and 0 < 6 - 2x, so 2 < x < 3
+        let hyps = [lp(-4, &[(2, 0)]), lp(6, &[(-2, 0)])];
+        let cert = find_certificate(&hyps).unwrap();
+        assert!(cert.check(&hyps));
+        // it needs rounding, the rational relaxation is feasible
+        assert!(cert.0.iter().any(|x| matches!(x, Step::Tighten(_))));
+        let without_rounding = Certificate(vec![
+            Step::Hyp(0),
+            Step::Hyp(1),
+            Step::Combine {
+                a: 1.into(),
+                first: 0,
+                b: 1.into(),
+                second: 1,
+            },
+        ]);
+        assert!(!without_rounding.check(&hyps));
+        assert!(!cert.check(&[lp(-4, &[(2, 0)]), lp(7, &[(-2, 0)])]));
+    }
+
+    #[test]
+    fn feasible() {
+        let hyps = [
+            lp(-4, &[(2, 0), (1, 1)]),
+            lp(7, &[(-2, 0)]),
+            lp(0, &[(1, 1)]),
+        ];
+        assert_eq!(find_certificate(&hyps), None);
+    }
+
+    #[test]
+    fn big_coefficients() {
+        let big = BigInt::from(10).pow(40);
+        let hyps = [
+            LinearPoly::new(-big.clone(), vec![(1.into(), 0)]),
+            LinearPoly::new(big, vec![((-1).into(), 0)]),
+        ];
+        assert!(find_certificate(&hyps).unwrap().check(&hyps));
+    }
+
+    #[test]
+    fn rational() {
+        // 0 < 2x - 4 and 0 < 6 - 2x has the rational solution x = 5/2
+        let hyps = [(lp(-4, &[(2, 0)]), true), (lp(6, &[(-2, 0)]), true)];
+        assert_eq!(find_rational_certificate(&hyps), None);
+        // 0 < x and 0 ≤ -x
+        let hyps = [(lp(0, &[(1, 0)]), true), (lp(0, &[(-1, 0)]), false)];
+        let cert = find_rational_certificate(&hyps).unwrap();
+        assert!(cert.check_rational(&hyps));
+        let non_strict = [(lp(0, &[(1, 0)]), false), (lp(0, &[(-1, 0)]), false)];
+        assert!(!cert.check_rational(&non_strict));
+        assert_eq!(find_rational_certificate(&non_strict), None);
+    }
+}
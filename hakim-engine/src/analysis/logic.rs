@@ -5,7 +5,7 @@ use typed_arena::Arena;
 
 use crate::{
     brain::{normalize, remove_unused_var, Abstraction, Term, TermRef},
-    interactive::{self, Frame},
+    interactive::{self, Frame, ProofTerm},
 };
 
 #[derive(Debug, Clone)]
@@ -176,18 +176,35 @@ impl<T: Clone + Debug> LogicBuilder<'_, T> {
         convert: for<'a> fn(t: TermRef, arena: LogicArena<'a, T>) -> LogicValue<'a, T>,
         check_contradiction: fn(&[T]) -> bool,
         negator: fn(T) -> T,
-    ) -> interactive::tactic::Result<Vec<Frame>> {
+    ) -> interactive::tactic::Result<(Vec<Frame>, ProofTerm)> {
         use interactive::tactic::Error::*;
+        let proof = ProofTerm::oracle(name, &frame);
+        match Self::find_evidence(
+            frame,
+            convert,
+            |x| check_contradiction(x).then_some(()),
+            negator,
+        ) {
+            Some(_) => Ok((vec![], proof)),
+            None => Err(CanNotSolve(name)),
+        }
+    }
+
+    /// Splits the negation of the goal and the hyps of the frame into cases, and returns
+    /// the evidence of `checker` for the contradiction of each case, or `None` if it
+    /// fails on one of them.
+    pub fn find_evidence<E>(
+        frame: Frame,
+        convert: for<'a> fn(t: TermRef, arena: LogicArena<'a, T>) -> LogicValue<'a, T>,
+        checker: impl Fn(&[T]) -> Option<E> + Copy,
+        negator: fn(T) -> T,
+    ) -> Option<Vec<E>> {
         let logic_builder = LogicBuilder::new(convert);
         logic_builder.and_not_term(normalize(frame.goal));
         for hyp in frame.hyps {
             logic_builder.and_term(normalize(hyp.ty));
         }
-        if logic_builder.check_contradiction(check_contradiction, negator) {
-            Ok(vec![])
-        } else {
-            Err(CanNotSolve(name))
-        }
+        logic_builder.check_contradiction(checker, negator)
     }
 }
 
@@ -257,23 +274,16 @@ impl<'a, T: Clone + Debug> LogicBuilder<'a, T> {
         }
         (self.f)(term, &self.arena)
     }
-    fn dfs(&'a self, checker: fn(&[T]) -> bool, negator: fn(T) -> T) -> bool {
-        /*        println!("bhyps");
-                let tmp = self.hyps.bhyps.0.take();
-                for a in &tmp {
-                    print!("{:?} ", a);
-                }
-                println!();
-                self.hyps.bhyps.0.set(tmp);
-        */
-        let mut ans = false;
+    fn dfs<E>(
+        &'a self,
+        checker: impl Fn(&[T]) -> Option<E> + Copy,
+        negator: fn(T) -> T,
+    ) -> Option<Vec<E>> {
+        let mut ans = None;
         let mut found = false;
 
         let step1 = |h1, h2| {
-            //            dbg!("step1");
-            //            dbg!("h1");
             self.hyps.add_hyp(h1, false, negator);
-            //            dbg!("h2");
             self.hyps.add_hyp(h2, false, negator);
             let c = self.dfs(checker, negator);
             self.hyps.add_hyp(h2, true, negator);
@@ -300,19 +310,15 @@ impl<'a, T: Clone + Debug> LogicBuilder<'a, T> {
         }
 
         let step2 = |h1, h2| {
-            //            dbg!("step2");
-            //            dbg!("h1");
             self.hyps.add_hyp(h1, false, negator);
-            let mut ans = self.dfs(checker, negator);
+            let first = self.dfs(checker, negator);
             self.hyps.add_hyp(h1, true, negator);
-
-            if ans {
-                //                dbg!("h2");
-                self.hyps.add_hyp(h2, false, negator);
-                ans = self.dfs(checker, negator);
-                self.hyps.add_hyp(h2, true, negator);
-            }
-            ans
+            let mut ans = first?;
+            self.hyps.add_hyp(h2, false, negator);
+            let second = self.dfs(checker, negator);
+            self.hyps.add_hyp(h2, true, negator);
+            ans.extend(second?);
+            Some(ans)
         };
         if let Some(h) = self.hyps.bhyps.pop() {
             if let Or(x, y) = h {
@@ -329,16 +335,23 @@ impl<'a, T: Clone + Debug> LogicBuilder<'a, T> {
             return ans;
         }
         let sh = self.hyps.simple_hyps.0.take();
-        ans = checker(&sh);
+        ans = checker(&sh).map(|x| vec![x]);
         self.hyps.simple_hyps.0.set(sh);
         ans
     }
-    pub fn check_contradiction(&'a self, checker: fn(&[T]) -> bool, negator: fn(T) -> T) -> bool {
+
+    /// Returns the evidence of `checker` for each case of the contradiction, or `None`
+    /// if there is a case which it can not refute.
+    pub fn check_contradiction<E>(
+        &'a self,
+        checker: impl Fn(&[T]) -> Option<E> + Copy,
+        negator: fn(T) -> T,
+    ) -> Option<Vec<E>> {
         let root = self.root.take();
         let root = match root {
             LogicValue::Exp(e) => self.arena.alloc(e),
-            LogicValue::True => return false,
-            LogicValue::False => return true,
+            LogicValue::True => return None,
+            LogicValue::False => return Some(vec![]),
         };
         self.hyps.add_hyp(root, false, negator);
         self.dfs(checker, negator)
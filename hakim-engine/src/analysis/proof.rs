@@ -0,0 +1,395 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use num_bigint::BigInt;
+
+use crate::{
+    app_ref,
+    brain::{fill_axiom, Abstraction, Term, TermRef},
+    engine::Engine,
+    library::prelude::{eq, false_ty, lt, or, z},
+    term_ref,
+};
+
+/// `a = b` over integers
+pub fn eq_z(a: TermRef, b: TermRef) -> TermRef {
+    app_ref!(eq(), z(), a, b)
+}
+
+pub fn lt_z(a: TermRef, b: TermRef) -> TermRef {
+    app_ref!(lt(), a, b)
+}
+
+/// `a ≤ b`, which is `a < b ∨ a = b`
+pub fn le_z(a: TermRef, b: TermRef) -> TermRef {
+    app_ref!(or(), lt_z(a.clone(), b.clone()), eq_z(a, b))
+}
+
+/// `p → q` of two closed terms
+pub fn implies(p: TermRef, q: TermRef) -> TermRef {
+    TermRef::new(Term::Forall(Abstraction {
+        var_ty: p,
+        hint_name: None,
+        body: q,
+    }))
+}
+
+/// `p → False`
+pub fn not(p: TermRef) -> TermRef {
+    implies(p, false_ty())
+}
+
+/// A proof of `lhs = rhs` in the type `ty`. The proof is `None` when the sides are
+/// equal by computation, like `2 + 3` and `5`.
+#[derive(Debug, Clone)]
+pub struct Eqn {
+    pub ty: TermRef,
+    pub lhs: TermRef,
+    pub rhs: TermRef,
+    proof: Option<TermRef>,
+}
+
+impl Eqn {
+    pub fn new(ty: TermRef, lhs: TermRef, rhs: TermRef, proof: TermRef) -> Self {
+        Eqn {
+            ty,
+            lhs,
+            rhs,
+            proof: Some(proof),
+        }
+    }
+
+    pub fn by_computation(ty: TermRef, lhs: TermRef, rhs: TermRef) -> Self {
+        Eqn {
+            ty,
+            lhs,
+            rhs,
+            proof: None,
+        }
+    }
+
+    /// Whether the proof is only the computation of the kernel
+    pub fn is_trivial(&self) -> bool {
+        self.proof.is_none()
+    }
+}
+
+fn local_ty(local: &TermRef) -> &TermRef {
+    match local.as_ref() {
+        Term::Axiom { ty, .. } => ty,
+        _ => panic!("locals are axioms"),
+    }
+}
+
+/// Which of the locals are used in `t`
+fn used_locals(locals: &[TermRef], t: &TermRef) -> Vec<bool> {
+    let index = local_index(locals);
+    let used = RefCell::new(vec![false; locals.len()]);
+    fill_axiom(t.clone(), |name, ty, _| {
+        if let Some(j) = index.get(name) {
+            used.borrow_mut()[*j] = true;
+        }
+        term_ref!(axiom name, ty)
+    });
+    used.into_inner()
+}
+
+fn local_index(locals: &[TermRef]) -> HashMap<&str, usize> {
+    locals
+        .iter()
+        .enumerate()
+        .map(|(j, x)| match x.as_ref() {
+            Term::Axiom { unique_name, .. } => (unique_name.as_str(), j),
+            _ => panic!("locals are axioms"),
+        })
+        .collect()
+}
+
+/// Replaces the locals with the variables of the binders of `λ x1 x2 ..., t`
+fn abstract_locals(locals: &[TermRef], t: TermRef) -> TermRef {
+    if locals.is_empty() {
+        return t;
+    }
+    let index = local_index(locals);
+    let n = locals.len();
+    fill_axiom(t, |name, ty, depth| match index.get(name) {
+        Some(j) => term_ref!(v depth + n - 1 - j),
+        None => term_ref!(axiom name, ty),
+    })
+}
+
+/// Builds proof terms out of the axioms of the prelude, which are added by
+/// `init_arith`. Proofs refer to hypotheses by their axiom, like the proofs of
+/// tactics do.
+pub struct Prover<'a> {
+    engine: &'a Engine,
+    next_local: Cell<usize>,
+}
+
+impl<'a> Prover<'a> {
+    pub fn new(engine: &'a Engine) -> Self {
+        Prover {
+            engine,
+            next_local: Cell::new(0),
+        }
+    }
+
+    pub fn engine(&self) -> &'a Engine {
+        self.engine
+    }
+
+    /// The axiom of the prelude, applied to the arguments
+    pub fn apply(&self, name: &str, args: impl IntoIterator<Item = TermRef>) -> TermRef {
+        let axiom = self
+            .engine
+            .name_term(name)
+            .unwrap_or_else(|| panic!("{name} is an axiom of the prelude"));
+        args.into_iter().fold(axiom, |f, x| app_ref!(f, x))
+    }
+
+    /// A new axiom of type `ty`, which stands for a bound variable until `lambda`
+    /// abstracts it. Its name is not an identifier, so it never clashes with the names
+    /// of the frame.
+    pub fn local(&self, ty: TermRef) -> TermRef {
+        let i = self.next_local.get();
+        self.next_local.set(i + 1);
+        term_ref!(axiom format!("#{i}"), ty)
+    }
+
+    /// `λ x: ty, body` for the local `x` of type `ty`
+    pub fn lambda(local: &TermRef, body: TermRef) -> TermRef {
+        Self::lambdas(std::slice::from_ref(local), body)
+    }
+
+    /// `λ x1 x2 ..., body` for the locals `x1 x2 ...`, in one pass over `body`
+    pub fn lambdas(locals: &[TermRef], body: TermRef) -> TermRef {
+        let body = abstract_locals(locals, body);
+        locals.iter().enumerate().rev().fold(body, |body, (j, x)| {
+            TermRef::new(Term::Fun(Abstraction {
+                var_ty: abstract_locals(&locals[..j], local_ty(x).clone()),
+                hint_name: None,
+                body,
+            }))
+        })
+    }
+
+    /// `let x := v in body` for the locals `x` and values `v`, as the redexes
+    /// `(λ x, body) v`, so the kernel checks each value once however many times the
+    /// body uses it. Each value may use the locals before it, and the lets which are
+    /// not used are dropped.
+    pub fn let_in(lets: Vec<(TermRef, TermRef)>, body: TermRef) -> TermRef {
+        let locals: Vec<TermRef> = lets.iter().map(|(x, _)| x.clone()).collect();
+        let mut live = used_locals(&locals, &body);
+        for (j, (_, v)) in lets.iter().enumerate().rev() {
+            if live[j] {
+                for (m, used) in used_locals(&locals[..j], v).into_iter().enumerate() {
+                    live[m] |= used;
+                }
+            }
+        }
+        let lets: Vec<_> = lets
+            .into_iter()
+            .zip(live)
+            .filter(|x| x.1)
+            .map(|x| x.0)
+            .collect();
+        let locals: Vec<TermRef> = lets.iter().map(|(x, _)| x.clone()).collect();
+        let body = abstract_locals(&locals, body);
+        lets.into_iter()
+            .enumerate()
+            .rev()
+            .fold(body, |body, (j, (x, v))| {
+                let before = &locals[..j];
+                let lambda = TermRef::new(Term::Fun(Abstraction {
+                    var_ty: abstract_locals(before, local_ty(&x).clone()),
+                    hint_name: None,
+                    body,
+                }));
+                app_ref!(lambda, abstract_locals(before, v))
+            })
+    }
+
+    /// `λ x: ty, f x` for a new local `x`
+    pub fn abstract_over(&self, ty: TermRef, f: impl FnOnce(TermRef) -> TermRef) -> TermRef {
+        let x = self.local(ty);
+        Self::lambda(&x, f(x.clone()))
+    }
+
+    pub fn refl(&self, ty: TermRef, t: TermRef) -> TermRef {
+        self.apply("refl", [ty, t])
+    }
+
+    pub fn proof(&self, e: &Eqn) -> TermRef {
+        match &e.proof {
+            Some(p) => p.clone(),
+            None => self.refl(e.ty.clone(), e.lhs.clone()),
+        }
+    }
+
+    /// The proof of `motive rhs` from the proof of `motive lhs`
+    pub fn rewrite(
+        &self,
+        e: &Eqn,
+        proof: TermRef,
+        motive: impl FnOnce(TermRef) -> TermRef,
+    ) -> TermRef {
+        match &e.proof {
+            None => proof,
+            Some(p) => {
+                let motive = self.abstract_over(e.ty.clone(), motive);
+                self.apply(
+                    "eq_ind",
+                    [
+                        e.ty.clone(),
+                        e.lhs.clone(),
+                        motive,
+                        proof,
+                        e.rhs.clone(),
+                        p.clone(),
+                    ],
+                )
+            }
+        }
+    }
+
+    pub fn sym(&self, e: Eqn) -> Eqn {
+        let proof = match &e.proof {
+            None => None,
+            Some(_) => {
+                let refl = self.refl(e.ty.clone(), e.lhs.clone());
+                let (ty, lhs) = (e.ty.clone(), e.lhs.clone());
+                Some(self.rewrite(&e, refl, |x| app_ref!(eq(), ty, x, lhs)))
+            }
+        };
+        Eqn {
+            ty: e.ty,
+            lhs: e.rhs,
+            rhs: e.lhs,
+            proof,
+        }
+    }
+
+    /// `a = c` from `a = b` and `b = c`
+    pub fn trans(&self, first: Eqn, second: Eqn) -> Eqn {
+        let proof = match (&first.proof, &second.proof) {
+            (None, p) | (p, None) => p.clone(),
+            (Some(p), Some(_)) => {
+                let (ty, lhs) = (first.ty.clone(), first.lhs.clone());
+                Some(self.rewrite(&second, p.clone(), |x| app_ref!(eq(), ty, lhs, x)))
+            }
+        };
+        Eqn {
+            ty: first.ty,
+            lhs: first.lhs,
+            rhs: second.rhs,
+            proof,
+        }
+    }
+
+    /// Chains the equations of the consecutive steps
+    pub fn chain(&self, steps: impl IntoIterator<Item = Eqn>) -> Eqn {
+        let mut steps = steps.into_iter();
+        let first = steps.next().expect("a chain has a step");
+        steps.fold(first, |r, x| self.trans(r, x))
+    }
+
+    /// `ctx lhs = ctx rhs` in the type `ty` of the context
+    pub fn cong(&self, e: Eqn, ty: TermRef, ctx: impl Fn(TermRef) -> TermRef) -> Eqn {
+        let lhs = ctx(e.lhs.clone());
+        let rhs = ctx(e.rhs.clone());
+        let proof = e.proof.as_ref().map(|_| {
+            let refl = self.refl(ty.clone(), lhs.clone());
+            let (ty, lhs) = (ty.clone(), lhs.clone());
+            self.rewrite(&e, refl, |x| app_ref!(eq(), ty, lhs, ctx(x)))
+        });
+        Eqn {
+            ty,
+            lhs,
+            rhs,
+            proof,
+        }
+    }
+
+    /// The proof of `0 < n` for a positive literal, which its size is logarithmic in `n`
+    pub fn pos(&self, n: &BigInt) -> TermRef {
+        let one = BigInt::from(1);
+        if *n == one {
+            return self.apply("ℤ_zero_lt_one", []);
+        }
+        if *n == BigInt::from(2) || n % 2 == one {
+            let m: BigInt = n - 1;
+            let one_pos = self.pos(&one);
+            return self.apply(
+                "ℤ_lt_plus",
+                [
+                    term_ref!(n m.clone()),
+                    term_ref!(n 1),
+                    self.pos(&m),
+                    one_pos,
+                ],
+            );
+        }
+        let m: BigInt = n / 2;
+        self.apply(
+            "ℤ_lt_mult",
+            [
+                term_ref!(n 2),
+                term_ref!(n m.clone()),
+                self.pos(&2.into()),
+                self.pos(&m),
+            ],
+        )
+    }
+
+    /// The proof of `0 ≤ n` for a non negative literal
+    pub fn nonneg(&self, n: &BigInt) -> TermRef {
+        let zero = term_ref!(n 0);
+        let n_term = term_ref!(n n.clone());
+        let lt = lt_z(zero.clone(), n_term.clone());
+        let eq = eq_z(zero.clone(), n_term);
+        if *n == BigInt::from(0) {
+            self.apply("inr", [lt, eq, self.refl(z(), zero)])
+        } else {
+            self.apply("inl", [lt, eq, self.pos(n)])
+        }
+    }
+
+    /// The proof of `False` from the proof of `0 ≤ c` for a negative literal
+    pub fn negative_nonneg(&self, c: &BigInt, proof: TermRef) -> TermRef {
+        let d: BigInt = -c - 1;
+        let m1 = -1;
+        let minus_one = term_ref!(n m1);
+        let sum = self.apply(
+            "ℤ_le_plus",
+            [
+                term_ref!(n c.clone()),
+                term_ref!(n d.clone()),
+                proof,
+                self.nonneg(&d),
+            ],
+        );
+        let zero_lt_zero = self.apply("ℤ_le_lt", [minus_one, sum]);
+        self.apply("ℤ_lt_irrefl", [term_ref!(n 0), zero_lt_zero])
+    }
+}
+
+/// The name of the axiom at the head of the application, and its arguments
+pub fn spine(t: &TermRef) -> Option<(&str, Vec<TermRef>)> {
+    let mut args = vec![];
+    let mut t = t;
+    loop {
+        match t.as_ref() {
+            Term::App { func, op } => {
+                args.push(op.clone());
+                t = func;
+            }
+            Term::Axiom { unique_name, .. } => {
+                args.reverse();
+                return Some((unique_name, args));
+            }
+            _ => return None,
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use super::proof::{not, spine, Prover};
+use crate::{
+    app_ref,
+    brain::{Abstraction, Term, TermRef},
+    library::prelude::false_ty,
+};
+
+/// What a theory learns from the proof of a proposition
+pub enum Assumption<F> {
+    /// A literal of the theory, with the proposition which it stands for and its proof
+    Fact(F, TermRef, TermRef),
+    /// Another proposition with its proof, which is split further by the refuter
+    Prop(TermRef, TermRef),
+}
+
+/// A decision procedure for the literals of some theory, like the linear inequalities
+/// of `lia`, which builds the proofs of its contradictions.
+pub trait Theory {
+    type Fact: Clone;
+
+    /// Whether the proposition is a literal of the theory, or its negation
+    fn understands(&self, prop: &TermRef) -> bool;
+
+    /// The facts which the proof of the proposition gives, or `None` if it is not a
+    /// literal of the theory
+    fn assume(&self, prop: &TermRef, proof: TermRef) -> Option<Vec<Assumption<Self::Fact>>>;
+
+    /// The proof of `False` from the facts and the proofs of their propositions, if
+    /// they are contradictory
+    fn refute(&self, facts: &[(Self::Fact, TermRef)]) -> Option<TermRef>;
+}
+
+/// The propositions which need a case split, with their proof
+#[derive(Clone)]
+enum Branch {
+    /// `a ∨ b`
+    Or(TermRef, TermRef, TermRef),
+    /// `a → b`
+    Implies(TermRef, TermRef, TermRef),
+    /// `¬(a ∧ b)`
+    NotAnd(TermRef, TermRef, TermRef),
+}
+
+#[derive(Clone)]
+struct State<F> {
+    queue: Vec<(TermRef, TermRef)>,
+    facts: Vec<(F, TermRef)>,
+    branches: Vec<Branch>,
+}
+
+/// `a → b` of a forall which its body doesn't depend on its variable
+pub fn as_implies(t: &TermRef) -> Option<(TermRef, TermRef)> {
+    match t.as_ref() {
+        Term::Forall(Abstraction { var_ty, body, .. }) if body.loose_bound() == 0 => {
+            Some((var_ty.clone(), body.clone()))
+        }
+        _ => None,
+    }
+}
+
+pub fn as_not(t: &TermRef) -> Option<TermRef> {
+    as_implies(t).and_then(|(a, b)| (b == false_ty()).then_some(a))
+}
+
+/// The operands of `and` or `or`
+fn as_binary(t: &TermRef, name: &str) -> Option<(TermRef, TermRef)> {
+    match spine(t)? {
+        (head, args) if head == name && args.len() == 2 => Some((args[0].clone(), args[1].clone())),
+        _ => None,
+    }
+}
+
+/// Proves the goal by contradiction from the hyps, by splitting the logical connectives
+/// into cases and refuting each case with the theory, like `LogicBuilder` does.
+pub struct Refuter<'a, T> {
+    prover: &'a Prover<'a>,
+    theory: &'a T,
+}
+
+impl<'a, T: Theory> Refuter<'a, T> {
+    pub fn new(prover: &'a Prover<'a>, theory: &'a T) -> Self {
+        Refuter { prover, theory }
+    }
+
+    /// The proof of the goal from the hyps, which are pairs of propositions and
+    /// their proofs
+    pub fn prove(&self, goal: TermRef, hyps: Vec<(TermRef, TermRef)>) -> Option<TermRef> {
+        let p = self.prover;
+        let not_goal = p.local(not(goal.clone()));
+        let mut queue = hyps;
+        queue.push((not(goal.clone()), not_goal.clone()));
+        queue.reverse();
+        let state = State {
+            queue,
+            facts: vec![],
+            branches: vec![],
+        };
+        let refuted = self.refute(state)?;
+        Some(p.apply("by_contra", [goal, Prover::lambda(&not_goal, refuted)]))
+    }
+
+    /// Whether the proposition can help in the refutation, so it is worth splitting
+    fn relevant(&self, prop: &TermRef) -> bool {
+        if *prop == false_ty() || self.theory.understands(prop) {
+            return true;
+        }
+        if let Some(a) = as_not(prop) {
+            return self.relevant(&a);
+        }
+        if let Some((a, b)) = as_implies(prop)
+            .or_else(|| as_binary(prop, "and"))
+            .or_else(|| as_binary(prop, "or"))
+        {
+            return self.relevant(&a) || self.relevant(&b);
+        }
+        false
+    }
+
+    /// The proof of `False` in the state. The proofs of the facts are bound once
+    /// before the case splits, since every case uses them.
+    fn refute(&self, state: State<T::Fact>) -> Option<TermRef> {
+        let mut lets = vec![];
+        let proof = self.refute_with(state, &mut lets)?;
+        Some(Prover::let_in(lets, proof))
+    }
+
+    fn refute_with(
+        &self,
+        mut state: State<T::Fact>,
+        lets: &mut Vec<(TermRef, TermRef)>,
+    ) -> Option<TermRef> {
+        let p = self.prover;
+        while let Some((prop, proof)) = state.queue.pop() {
+            if prop == false_ty() {
+                return Some(proof);
+            }
+            if let Some(assumptions) = self.theory.assume(&prop, proof.clone()) {
+                for x in assumptions {
+                    match x {
+                        Assumption::Fact(f, prop, proof) => {
+                            let x = p.local(prop);
+                            lets.push((x.clone(), proof));
+                            state.facts.push((f, x));
+                        }
+                        Assumption::Prop(prop, proof) => state.queue.push((prop, proof)),
+                    }
+                }
+                continue;
+            }
+            if let Some((a, b)) = as_binary(&prop, "and") {
+                state.queue.push((
+                    a.clone(),
+                    p.apply("proj1", [a.clone(), b.clone(), proof.clone()]),
+                ));
+                state
+                    .queue
+                    .push((b.clone(), p.apply("proj2", [a, b, proof])));
+                continue;
+            }
+            if let Some((a, b)) = as_binary(&prop, "or") {
+                if self.relevant(&prop) {
+                    state.branches.push(Branch::Or(a, b, proof));
+                }
+                continue;
+            }
+            if let Some(a) = as_not(&prop) {
+                self.split_not(&mut state, a, proof);
+                continue;
+            }
+            if let Some((a, b)) = as_implies(&prop) {
+                if self.relevant(&prop) {
+                    state.branches.push(Branch::Implies(a, b, proof));
+                }
+            }
+        }
+        if let Some(proof) = self.theory.refute(&state.facts) {
+            return Some(proof);
+        }
+        let branch = state.branches.pop()?;
+        // refutes the state with one more hypothesis, and abstracts over it
+        let case = |prop: TermRef| {
+            let x = p.local(prop.clone());
+            let mut state = state.clone();
+            state.queue.push((prop, x.clone()));
+            Some(Prover::lambda(&x, self.refute(state)?))
+        };
+        Some(match branch {
+            Branch::Or(a, b, proof) => {
+                let first = case(a.clone())?;
+                let second = case(b.clone())?;
+                p.apply("or_elim", [a, b, proof, false_ty(), first, second])
+            }
+            Branch::Implies(a, b, proof) => {
+                let first = case(not(a.clone()))?;
+                let second = case(b)?;
+                let a_proof = p.apply("by_contra", [a, first]);
+                app_ref!(second, app_ref!(proof, a_proof))
+            }
+            Branch::NotAnd(a, b, proof) => {
+                let first = case(not(a.clone()))?;
+                let second = case(not(b.clone()))?;
+                let a_proof = p.apply("by_contra", [a.clone(), first]);
+                let both = p.abstract_over(b.clone(), |y| {
+                    app_ref!(proof, p.apply("pair", [a, b, a_proof, y]))
+                });
+                app_ref!(second, both)
+            }
+        })
+    }
+
+    /// Splits `¬a` with its proof
+    fn split_not(&self, state: &mut State<T::Fact>, a: TermRef, proof: TermRef) {
+        let p = self.prover;
+        if let Some((x, y)) = as_binary(&a, "or") {
+            let not_x = p.abstract_over(x.clone(), |h| {
+                app_ref!(proof, p.apply("inl", [x.clone(), y.clone(), h]))
+            });
+            let not_y = p.abstract_over(y.clone(), |h| {
+                app_ref!(proof, p.apply("inr", [x.clone(), y.clone(), h]))
+            });
+            state.queue.push((not(x), not_x));
+            state.queue.push((not(y), not_y));
+        } else if let Some((x, y)) = as_binary(&a, "and") {
+            if self.relevant(&a) {
+                state.branches.push(Branch::NotAnd(x, y, proof));
+            }
+        } else if let Some(x) = as_not(&a) {
+            state
+                .queue
+                .push((x.clone(), p.apply("by_contra", [x, proof])));
+        } else if let Some((x, y)) = as_implies(&a) {
+            // ¬(x → y) gives x, since ¬x proves x → y, and ¬y, since y proves x → y
+            let x_proof = p.abstract_over(not(x.clone()), |nx| {
+                let imp = p.abstract_over(x.clone(), |h| {
+                    let absurd = p.abstract_over(not(y.clone()), |_| app_ref!(nx, h));
+                    p.apply("by_contra", [y.clone(), absurd])
+                });
+                app_ref!(proof, imp)
+            });
+            let not_y = p.abstract_over(y.clone(), |h| {
+                app_ref!(proof, p.abstract_over(x.clone(), |_| h))
+            });
+            state
+                .queue
+                .push((x.clone(), p.apply("by_contra", [x, x_proof])));
+            state.queue.push((not(y), not_y));
+        }
+    }
+}
@@ -0,0 +1,1309 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use num_bigint::BigInt;
+
+use super::arith::Poly;
+use super::farkas::{div_floor, gcd};
+use super::proof::{eq_z, lt_z, not, spine, Eqn, Prover};
+use crate::{
+    app_ref,
+    brain::{self, definitely_inequal, type_of, Term, TermRef},
+    library::prelude::{
+        cnt, cons, eq, len1, minus, mult, nil, plus, pow, q, q_of_z, qminus, qmult, qplus, sigma,
+        z, z_of_n,
+    },
+    term_ref,
+};
+
+type Monomial = (BigInt, Vec<TermRef>);
+
+/// The numbers which a `Ring` normalizes. The literals of the rationals are the
+/// integer literals under `q_of_z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Carrier {
+    Integer,
+    Rational,
+}
+
+impl Carrier {
+    pub fn ty(self) -> TermRef {
+        match self {
+            Carrier::Integer => z(),
+            Carrier::Rational => q(),
+        }
+    }
+
+    /// The names of `+`, `*` and `-`
+    fn names(self) -> [&'static str; 3] {
+        match self {
+            Carrier::Integer => ["plus", "mult", "minus"],
+            Carrier::Rational => ["qplus", "qmult", "qminus"],
+        }
+    }
+
+    /// The axiom `name` of the carrier, like `ℤ_plus_comm`
+    fn law(self, name: &str) -> String {
+        match self {
+            Carrier::Integer => format!("ℤ_{name}"),
+            Carrier::Rational => format!("ℚ_{name}"),
+        }
+    }
+
+    pub fn add(self, a: TermRef, b: TermRef) -> TermRef {
+        let op = match self {
+            Carrier::Integer => plus(),
+            Carrier::Rational => qplus(),
+        };
+        app_ref!(op, a, b)
+    }
+
+    pub fn mul(self, a: TermRef, b: TermRef) -> TermRef {
+        let op = match self {
+            Carrier::Integer => mult(),
+            Carrier::Rational => qmult(),
+        };
+        app_ref!(op, a, b)
+    }
+
+    pub fn sub(self, a: TermRef, b: TermRef) -> TermRef {
+        let op = match self {
+            Carrier::Integer => minus(),
+            Carrier::Rational => qminus(),
+        };
+        app_ref!(op, a, b)
+    }
+
+    pub fn num(self, n: &BigInt) -> TermRef {
+        let n = term_ref!(n n.clone());
+        match self {
+            Carrier::Integer => n,
+            Carrier::Rational => app_ref!(q_of_z(), n),
+        }
+    }
+
+    /// The value of the literal
+    fn literal(self, t: &TermRef) -> Option<&BigInt> {
+        let n = match self {
+            Carrier::Integer => t,
+            Carrier::Rational => match t.as_ref() {
+                Term::App { func, op } if *func == q_of_z() => op,
+                _ => return None,
+            },
+        };
+        match n.as_ref() {
+            Term::Number { value } => Some(value),
+            _ => None,
+        }
+    }
+
+    fn monomial(self, atoms: &[TermRef]) -> TermRef {
+        let (last, init) = atoms.split_last().expect("monomials have atoms");
+        init.iter()
+            .rev()
+            .fold(last.clone(), |r, x| self.mul(x.clone(), r))
+    }
+
+    fn scaled(self, (k, atoms): &Monomial) -> TermRef {
+        self.mul(self.num(k), self.monomial(atoms))
+    }
+}
+
+/// A polynomial in the form `k1 * m1 + (k2 * m2 + (... + c))`, which its monomials
+/// `m = x1 * (x2 * ...)` are sorted and merged like the monomials of `Poly`, and their
+/// coefficients are not zero. Equal polynomials have the same normal form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Normal {
+    carrier: Carrier,
+    monomials: Vec<Monomial>,
+    constant: BigInt,
+}
+
+fn sigma0(r: TermRef, f: TermRef) -> TermRef {
+    app_ref!(sigma(), term_ref!(n 0), r, f)
+}
+
+fn is_number(t: &TermRef, n: i32) -> bool {
+    matches!(t.as_ref(), Term::Number { value } if *value == n.into())
+}
+
+/// Whether the exponent is a literal which powers are expanded for
+fn is_small_literal(n: &TermRef) -> bool {
+    matches!(n.as_ref(), Term::Number { value } if *value >= 0.into() && *value < 5.into())
+}
+
+/// Replaces the atoms of the sorted `atoms` with `locals`
+fn replace(t: &TermRef, atoms: &[TermRef], locals: &[TermRef]) -> TermRef {
+    if let Ok(i) = atoms.binary_search(t) {
+        return locals[i].clone();
+    }
+    match t.as_ref() {
+        Term::App { func, op } => {
+            app_ref!(replace(func, atoms, locals), replace(op, atoms, locals))
+        }
+        _ => t.clone(),
+    }
+}
+
+impl Normal {
+    fn constant(carrier: Carrier, constant: BigInt) -> Self {
+        Normal {
+            carrier,
+            monomials: vec![],
+            constant,
+        }
+    }
+
+    pub fn term(&self) -> TermRef {
+        let c = self.carrier;
+        self.monomials
+            .iter()
+            .rev()
+            .fold(c.num(&self.constant), |r, m| c.add(c.scaled(m), r))
+    }
+
+    pub fn poly(&self) -> Poly {
+        Poly::new(self.constant.clone(), self.monomials.clone())
+    }
+
+    /// The value of the polynomial, if it has no variable
+    pub fn as_constant(&self) -> Option<&BigInt> {
+        self.monomials.is_empty().then_some(&self.constant)
+    }
+
+    /// The first monomial and the polynomial of the rest
+    fn split(&self) -> Option<(Monomial, Normal)> {
+        let (first, rest) = self.monomials.split_first()?;
+        let rest = Normal {
+            carrier: self.carrier,
+            monomials: rest.to_vec(),
+            constant: self.constant.clone(),
+        };
+        Some((first.clone(), rest))
+    }
+
+    fn push_front(mut self, m: Monomial) -> Self {
+        self.monomials.insert(0, m);
+        self
+    }
+
+    /// The gcd `g` of the coefficients and the polynomial divided by it, which its
+    /// constant is rounded down, if `g` is more than one. Over integers `0 ≤ p` implies
+    /// that the divided polynomial is not negative as well.
+    pub fn tightened(&self) -> Option<(BigInt, Normal)> {
+        let g = self
+            .monomials
+            .iter()
+            .fold(BigInt::from(0), |g, (k, _)| gcd(&g, k));
+        if g <= BigInt::from(1) {
+            return None;
+        }
+        let monomials = self
+            .monomials
+            .iter()
+            .map(|(k, x)| (k / &g, x.clone()))
+            .collect();
+        let constant = div_floor(&self.constant, &g);
+        Some((
+            g,
+            Normal {
+                carrier: self.carrier,
+                monomials,
+                constant,
+            },
+        ))
+    }
+
+    fn map_atoms(self, f: impl Fn(&TermRef) -> TermRef) -> Self {
+        let monomials = self
+            .monomials
+            .into_iter()
+            .map(|(k, x)| (k, x.iter().map(&f).collect()))
+            .collect();
+        Normal { monomials, ..self }
+    }
+
+    fn with_constant(&self, constant: BigInt) -> Self {
+        Normal {
+            carrier: self.carrier,
+            monomials: self.monomials.clone(),
+            constant,
+        }
+    }
+}
+
+/// Proves the equalities of integer expressions, by rewriting them into `Normal` with
+/// the axioms of rings. It understands the same functions as `Poly`, like literal
+/// powers, `sigma` and the length and count of lists, and other terms are atoms, which
+/// their integer arguments are normalized as well. Over the rationals it understands
+/// `+`, `*`, `-` and `q_of_z` of integer expressions.
+pub struct Ring<'a> {
+    prover: &'a Prover<'a>,
+    carrier: Carrier,
+    /// the ring which normalizes the integer arguments, if it is not this one
+    integers: Option<&'a Ring<'a>>,
+    /// the sums which `sigma` is split into, which are atoms and are not split again
+    opaque: RefCell<Vec<TermRef>>,
+    /// the normal forms which are found, since the atoms share their arguments and the
+    /// hypotheses share their terms
+    normal: RefCell<HashMap<TermRef, (Normal, Eqn)>>,
+    canonical: RefCell<HashMap<TermRef, (Normal, Eqn)>>,
+}
+
+impl<'a> Ring<'a> {
+    pub fn new(prover: &'a Prover<'a>) -> Self {
+        Ring {
+            prover,
+            carrier: Carrier::Integer,
+            integers: None,
+            opaque: RefCell::new(vec![]),
+            normal: RefCell::new(HashMap::new()),
+            canonical: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// The ring of the rationals, which normalizes the integers with `integers`
+    pub fn rational(prover: &'a Prover<'a>, integers: &'a Ring<'a>) -> Self {
+        Ring {
+            carrier: Carrier::Rational,
+            integers: Some(integers),
+            ..Ring::new(prover)
+        }
+    }
+
+    fn integers(&self) -> &Ring<'a> {
+        self.integers.unwrap_or(self)
+    }
+
+    fn ty(&self) -> TermRef {
+        self.carrier.ty()
+    }
+
+    fn add(&self, a: TermRef, b: TermRef) -> TermRef {
+        self.carrier.add(a, b)
+    }
+
+    fn mul(&self, a: TermRef, b: TermRef) -> TermRef {
+        self.carrier.mul(a, b)
+    }
+
+    fn num(&self, n: &BigInt) -> TermRef {
+        self.carrier.num(n)
+    }
+
+    fn constant(&self, c: BigInt) -> Normal {
+        Normal::constant(self.carrier, c)
+    }
+
+    /// `t = t`
+    fn trivial(&self, t: &TermRef) -> Eqn {
+        Eqn::by_computation(self.ty(), t.clone(), t.clone())
+    }
+
+    /// The axiom which proves `lhs = rhs` from the arguments
+    fn ax<const N: usize>(
+        &self,
+        name: &str,
+        args: [TermRef; N],
+        lhs: TermRef,
+        rhs: TermRef,
+    ) -> Eqn {
+        Eqn::new(self.ty(), lhs, rhs, self.prover.apply(name, args))
+    }
+
+    /// Like `ax`, for the axiom `name` of the carrier
+    fn law<const N: usize>(
+        &self,
+        name: &str,
+        args: [TermRef; N],
+        lhs: TermRef,
+        rhs: TermRef,
+    ) -> Eqn {
+        self.ax(&self.carrier.law(name), args, lhs, rhs)
+    }
+
+    /// `a ∘ b = c` of the literals `a`, `b` and the result `c` of the integer operation
+    /// `name`. The kernel computes it over integers, and the rationals take it from
+    /// `↑(a ∘ b) = ↑a ∘ ↑b`.
+    fn literal_op(&self, name: &str, a: &BigInt, b: &BigInt, c: &BigInt) -> Eqn {
+        let (at, bt) = (self.num(a), self.num(b));
+        let lhs = match name {
+            "plus" => self.add(at, bt),
+            _ => self.mul(at, bt),
+        };
+        let rhs = self.num(c);
+        match self.carrier {
+            Carrier::Integer => Eqn::by_computation(z(), lhs, rhs),
+            Carrier::Rational => {
+                let args = [term_ref!(n a.clone()), term_ref!(n b.clone())];
+                self.sym(self.ax(&format!("ℚ_of_z_{name}"), args, rhs, lhs))
+            }
+        }
+    }
+
+    /// `a + b = c` of literals
+    fn add_literals(&self, a: &BigInt, b: &BigInt) -> Eqn {
+        self.literal_op("plus", a, b, &(a + b))
+    }
+
+    /// `a * b = c` of literals
+    fn mul_literals(&self, a: &BigInt, b: &BigInt) -> Eqn {
+        self.literal_op("mult", a, b, &(a * b))
+    }
+
+    fn sym(&self, e: Eqn) -> Eqn {
+        self.prover.sym(e)
+    }
+
+    fn chain(&self, steps: impl IntoIterator<Item = Eqn>) -> Eqn {
+        self.prover.chain(steps)
+    }
+
+    fn cong(&self, e: Eqn, ctx: impl Fn(TermRef) -> TermRef) -> Eqn {
+        self.prover.cong(e, self.ty(), ctx)
+    }
+
+    /// `a + lhs = a + rhs`
+    pub fn add_left(&self, e: Eqn, a: &TermRef) -> Eqn {
+        self.cong(e, |x| self.add(a.clone(), x))
+    }
+
+    /// `lhs + b = rhs + b`
+    pub fn add_right(&self, e: Eqn, b: &TermRef) -> Eqn {
+        self.cong(e, |x| self.add(x, b.clone()))
+    }
+
+    fn mul_left(&self, e: Eqn, a: &TermRef) -> Eqn {
+        self.cong(e, |x| self.mul(a.clone(), x))
+    }
+
+    /// `lhs * b = rhs * b`
+    fn mul_right(&self, e: Eqn, b: &TermRef) -> Eqn {
+        self.cong(e, |x| self.mul(x, b.clone()))
+    }
+
+    fn plus_comm(&self, a: TermRef, b: TermRef) -> Eqn {
+        let (lhs, rhs) = (
+            self.add(a.clone(), b.clone()),
+            self.add(b.clone(), a.clone()),
+        );
+        self.law("plus_comm", [a, b], lhs, rhs)
+    }
+
+    /// `a + b + c = a + (b + c)`
+    fn plus_assoc(&self, a: TermRef, b: TermRef, c: TermRef) -> Eqn {
+        let lhs = self.add(self.add(a.clone(), b.clone()), c.clone());
+        let rhs = self.add(a.clone(), self.add(b.clone(), c.clone()));
+        self.law("plus_assoc", [a, b, c], lhs, rhs)
+    }
+
+    fn plus_zero(&self, a: TermRef) -> Eqn {
+        let lhs = self.add(a.clone(), self.num(&0.into()));
+        self.law("plus_zero", [a.clone()], lhs, a)
+    }
+
+    fn zero_plus(&self, a: TermRef) -> Eqn {
+        self.chain([
+            self.plus_comm(self.num(&0.into()), a.clone()),
+            self.plus_zero(a),
+        ])
+    }
+
+    /// `a + (b + c) = b + (a + c)`
+    fn plus_swap(&self, a: TermRef, b: TermRef, c: TermRef) -> Eqn {
+        self.chain([
+            self.sym(self.plus_assoc(a.clone(), b.clone(), c.clone())),
+            self.add_right(self.plus_comm(a.clone(), b.clone()), &c),
+            self.plus_assoc(b, a, c),
+        ])
+    }
+
+    fn mult_comm(&self, a: TermRef, b: TermRef) -> Eqn {
+        let (lhs, rhs) = (
+            self.mul(a.clone(), b.clone()),
+            self.mul(b.clone(), a.clone()),
+        );
+        self.law("mult_comm", [a, b], lhs, rhs)
+    }
+
+    /// `a * b * c = a * (b * c)`
+    fn mult_assoc(&self, a: TermRef, b: TermRef, c: TermRef) -> Eqn {
+        let lhs = self.mul(self.mul(a.clone(), b.clone()), c.clone());
+        let rhs = self.mul(a.clone(), self.mul(b.clone(), c.clone()));
+        self.law("mult_assoc", [a, b, c], lhs, rhs)
+    }
+
+    fn mult_one(&self, a: TermRef) -> Eqn {
+        let lhs = self.mul(self.num(&1.into()), a.clone());
+        self.law("mult_one", [a.clone()], lhs, a)
+    }
+
+    fn mult_zero(&self, a: TermRef) -> Eqn {
+        let lhs = self.mul(self.num(&0.into()), a.clone());
+        self.law("mult_zero", [a], lhs, self.num(&0.into()))
+    }
+
+    /// `a * (b * c) = b * (a * c)`
+    fn mult_swap(&self, a: TermRef, b: TermRef, c: TermRef) -> Eqn {
+        self.chain([
+            self.sym(self.mult_assoc(a.clone(), b.clone(), c.clone())),
+            self.mul_right(self.mult_comm(a.clone(), b.clone()), &c),
+            self.mult_assoc(b, a, c),
+        ])
+    }
+
+    /// `a * (b + c) = a * b + a * c`
+    fn distr(&self, a: TermRef, b: TermRef, c: TermRef) -> Eqn {
+        let lhs = self.mul(a.clone(), self.add(b.clone(), c.clone()));
+        let rhs = self.add(
+            self.mul(a.clone(), b.clone()),
+            self.mul(a.clone(), c.clone()),
+        );
+        self.law("distr", [a, b, c], lhs, rhs)
+    }
+
+    /// `(a + b) * c = a * c + b * c`
+    fn distr_r(&self, a: TermRef, b: TermRef, c: TermRef) -> Eqn {
+        let ac = self.mul(a.clone(), c.clone());
+        let cb = self.mul(c.clone(), b.clone());
+        self.chain([
+            self.mult_comm(self.add(a.clone(), b.clone()), c.clone()),
+            self.distr(c.clone(), a.clone(), b.clone()),
+            self.add_right(self.mult_comm(c.clone(), a), &cb),
+            self.add_left(self.mult_comm(c, b), &ac),
+        ])
+    }
+
+    /// `x + y = r` of two normal forms
+    pub fn add_normal(&self, x: &Normal, y: &Normal) -> (Normal, Eqn) {
+        let (xt, yt) = (x.term(), y.term());
+        let order = match (x.monomials.first(), y.monomials.first()) {
+            (None, None) => {
+                let r = self.constant(&x.constant + &y.constant);
+                return (r, self.add_literals(&x.constant, &y.constant));
+            }
+            (Some(a), Some(b)) => a.1.cmp(&b.1),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+        };
+        let c = self.carrier;
+        match order {
+            Ordering::Less => {
+                let (m, rest) = x.split().unwrap();
+                let mt = c.scaled(&m);
+                let (r, e) = self.add_normal(&rest, y);
+                let e = self.chain([
+                    self.plus_assoc(mt.clone(), rest.term(), yt),
+                    self.add_left(e, &mt),
+                ]);
+                (r.push_front(m), e)
+            }
+            Ordering::Greater => {
+                let (m, rest) = y.split().unwrap();
+                let mt = c.scaled(&m);
+                let (r, e) = self.add_normal(x, &rest);
+                let e = self.chain([
+                    self.plus_swap(xt, mt.clone(), rest.term()),
+                    self.add_left(e, &mt),
+                ]);
+                (r.push_front(m), e)
+            }
+            Ordering::Equal => {
+                let ((k1, atoms), x_rest) = x.split().unwrap();
+                let ((k2, _), y_rest) = y.split().unwrap();
+                let mono = c.monomial(&atoms);
+                let t1 = self.mul(self.num(&k1), mono.clone());
+                let t2 = self.mul(self.num(&k2), mono.clone());
+                let (xr, yr) = (x_rest.term(), y_rest.term());
+                let rest = self.add(xr.clone(), yr.clone());
+                let (r, e) = self.add_normal(&x_rest, &y_rest);
+                let k = &k1 + &k2;
+                let merged = self.mul(self.num(&k), mono.clone());
+                let mut steps = vec![
+                    self.plus_assoc(t1.clone(), xr.clone(), yt),
+                    self.add_left(self.plus_swap(xr, t2.clone(), yr), &t1),
+                    self.sym(self.plus_assoc(t1, t2, rest.clone())),
+                    self.add_right(
+                        self.sym(self.distr_r(self.num(&k1), self.num(&k2), mono.clone())),
+                        &rest,
+                    ),
+                    self.add_right(self.mul_right(self.add_literals(&k1, &k2), &mono), &rest),
+                ];
+                if k == 0.into() {
+                    steps.push(self.add_right(self.mult_zero(mono), &rest));
+                    steps.push(self.zero_plus(rest));
+                    steps.push(e);
+                    (r, self.chain(steps))
+                } else {
+                    steps.push(self.add_left(e, &merged));
+                    (r.push_front((k, atoms)), self.chain(steps))
+                }
+            }
+        }
+    }
+
+    /// `k * x = r` of a normal form
+    pub fn scale_normal(&self, k: &BigInt, x: &Normal) -> (Normal, Eqn) {
+        let xt = x.term();
+        if *k == 0.into() {
+            return (self.constant(0.into()), self.mult_zero(xt));
+        }
+        let Some(((k1, atoms), rest)) = x.split() else {
+            let r = self.constant(k * &x.constant);
+            return (r, self.mul_literals(k, &x.constant));
+        };
+        let mono = self.carrier.monomial(&atoms);
+        let kk1 = k * &k1;
+        let rest_t = rest.term();
+        let scaled_rest = self.mul(self.num(k), rest_t.clone());
+        let head = self.mul(self.num(&kk1), mono.clone());
+        let (r, e) = self.scale_normal(k, &rest);
+        let e = self.chain([
+            self.distr(self.num(k), self.mul(self.num(&k1), mono.clone()), rest_t),
+            self.add_right(
+                self.sym(self.mult_assoc(self.num(k), self.num(&k1), mono.clone())),
+                &scaled_rest,
+            ),
+            self.add_right(
+                self.mul_right(self.mul_literals(k, &k1), &mono),
+                &scaled_rest,
+            ),
+            self.add_left(e, &head),
+        ]);
+        (r.push_front((kk1, atoms)), e)
+    }
+
+    /// `x * y = r` of two normal forms
+    pub fn mul_normal(&self, x: &Normal, y: &Normal) -> (Normal, Eqn) {
+        let Some((m, rest)) = x.split() else {
+            return self.scale_normal(&x.constant, y);
+        };
+        let (mt, rest_t, yt) = (self.carrier.scaled(&m), rest.term(), y.term());
+        let (r1, e1) = self.monomial_mul(&m, y);
+        let (r2, e2) = self.mul_normal(&rest, y);
+        let (r, e3) = self.add_normal(&r1, &r2);
+        let e = self.chain([
+            self.distr_r(mt, rest_t.clone(), yt.clone()),
+            self.add_right(e1, &self.mul(rest_t, yt)),
+            self.add_left(e2, &r1.term()),
+            e3,
+        ]);
+        (r, e)
+    }
+
+    /// `(k * m) * y = r` of a monomial and a normal form
+    fn monomial_mul(&self, m: &Monomial, y: &Normal) -> (Normal, Eqn) {
+        let carrier = self.carrier;
+        let mt = carrier.scaled(m);
+        let (k, atoms) = m;
+        let Some((n, rest)) = y.split() else {
+            let c = &y.constant;
+            if *c == 0.into() {
+                let e = self.chain([self.mult_comm(mt.clone(), self.num(c)), self.mult_zero(mt)]);
+                return (self.constant(0.into()), e);
+            }
+            let product = (c * k, atoms.clone());
+            let head = carrier.scaled(&product);
+            let mono = carrier.monomial(atoms);
+            let e = self.chain([
+                self.mult_comm(mt, self.num(c)),
+                self.sym(self.mult_assoc(self.num(c), self.num(k), mono.clone())),
+                self.mul_right(self.mul_literals(c, k), &mono),
+                self.sym(self.plus_zero(head)),
+            ]);
+            return (self.constant(0.into()).push_front(product), e);
+        };
+        let (nt, rest_t) = (carrier.scaled(&n), rest.term());
+        let (product, e1) = self.monomial_product(m, &n);
+        let e1 = self.chain([e1, self.sym(self.plus_zero(carrier.scaled(&product)))]);
+        let r1 = self.constant(0.into()).push_front(product);
+        let (r2, e2) = self.monomial_mul(m, &rest);
+        let (r, e3) = self.add_normal(&r1, &r2);
+        let e = self.chain([
+            self.distr(mt.clone(), nt, rest_t.clone()),
+            self.add_right(e1, &self.mul(mt, rest_t)),
+            self.add_left(e2, &r1.term()),
+            e3,
+        ]);
+        (r, e)
+    }
+
+    /// `(k * m) * (l * n) = (k * l) * mn` of two monomials
+    fn monomial_product(&self, (k, a): &Monomial, (l, b): &Monomial) -> (Monomial, Eqn) {
+        let (m, n) = (self.carrier.monomial(a), self.carrier.monomial(b));
+        let kl = k * l;
+        let (atoms, merge) = self.merge_atoms(a, b);
+        let e = self.chain([
+            self.mult_assoc(self.num(k), m.clone(), self.mul(self.num(l), n.clone())),
+            self.mul_left(
+                self.mult_swap(m.clone(), self.num(l), n.clone()),
+                &self.num(k),
+            ),
+            self.sym(self.mult_assoc(self.num(k), self.num(l), self.mul(m, n))),
+            self.mul_right(self.mul_literals(k, l), &merge.lhs),
+            self.mul_left(merge, &self.num(&kl)),
+        ]);
+        ((kl, atoms), e)
+    }
+
+    /// `m * n = mn` of two products of sorted atoms, which the atoms of `mn` are sorted
+    fn merge_atoms(&self, a: &[TermRef], b: &[TermRef]) -> (Vec<TermRef>, Eqn) {
+        let c = self.carrier;
+        let (x, a_rest) = a.split_first().unwrap();
+        let (y, b_rest) = b.split_first().unwrap();
+        let (m, n) = (c.monomial(a), c.monomial(b));
+        if x <= y {
+            if a_rest.is_empty() {
+                let atoms = [a, b].concat();
+                let e = Eqn::by_computation(self.ty(), self.mul(m, n), c.monomial(&atoms));
+                return (atoms, e);
+            }
+            let (rest, e) = self.merge_atoms(a_rest, b);
+            let e = self.chain([
+                self.mult_assoc(x.clone(), c.monomial(a_rest), n),
+                self.mul_left(e, x),
+            ]);
+            return ([vec![x.clone()], rest].concat(), e);
+        }
+        if b_rest.is_empty() {
+            return ([b, a].concat(), self.mult_comm(m, n));
+        }
+        let (rest, e) = self.merge_atoms(a, b_rest);
+        let e = self.chain([
+            self.mult_swap(m, y.clone(), c.monomial(b_rest)),
+            self.mul_left(e, y),
+        ]);
+        ([vec![y.clone()], rest].concat(), e)
+    }
+
+    /// Normalizes the rhs of the equation
+    fn then_normalize(&self, e: Eqn) -> (Normal, Eqn) {
+        let (r, e2) = self.normalize(&e.rhs);
+        (r, self.prover.trans(e, e2))
+    }
+
+    /// The proof of `a = b`, if they have the same normal form
+    pub fn prove_eq(&self, a: &TermRef, b: &TermRef) -> Option<Eqn> {
+        let (x, ea) = self.normalize(a);
+        let (y, eb) = self.normalize(b);
+        (x == y).then(|| self.prover.trans(ea, self.sym(eb)))
+    }
+
+    /// The readable term of `Poly::into_canonical_term` which is equal to `t`
+    pub fn canonical(&self, t: &TermRef) -> (Normal, Eqn) {
+        if let Some(r) = self.canonical.borrow().get(t) {
+            return r.clone();
+        }
+        let (x, e) = self.normalize(t);
+        let c = x.poly().into_canonical_term();
+        let (y, ec) = self.normalize(&c);
+        debug_assert_eq!(x, y, "canonical terms have the same normal form");
+        let r = (x, self.prover.trans(e, self.sym(ec)));
+        self.canonical.borrow_mut().insert(t.clone(), r.clone());
+        r
+    }
+
+    /// The equation `lhs = rhs` of `build(args)`, which holds in every ring, so it is
+    /// proved for the locals and applied to `args`. This keeps `args` from being
+    /// normalized.
+    pub fn identity<const N: usize>(
+        &self,
+        args: [TermRef; N],
+        build: impl Fn(&[TermRef; N]) -> (TermRef, TermRef),
+    ) -> Eqn {
+        let locals = args.clone().map(|_| self.prover.local(self.ty()));
+        let (lhs, rhs) = build(&locals);
+        let (x, ea) = self.arith(&lhs);
+        let (y, eb) = self.arith(&rhs);
+        assert_eq!(x, y, "identities of rings are provable");
+        let e = self.prover.trans(ea, self.sym(eb));
+        let (lhs, rhs) = build(&args);
+        self.instantiate(&locals, &args, e, lhs, rhs)
+    }
+
+    /// `lhs = rhs` from the equation `e` over the locals, which `lhs` and `rhs` are
+    /// the same as its sides with `args` in place of the locals
+    fn instantiate(
+        &self,
+        locals: &[TermRef],
+        args: &[TermRef],
+        e: Eqn,
+        lhs: TermRef,
+        rhs: TermRef,
+    ) -> Eqn {
+        if e.is_trivial() {
+            return Eqn::by_computation(self.ty(), lhs, rhs);
+        }
+        let proof = Prover::lambdas(locals, self.prover.proof(&e));
+        let proof = args.iter().fold(proof, |p, x| app_ref!(p, x));
+        Eqn::new(self.ty(), lhs, rhs, proof)
+    }
+
+    /// `t = r` for the normal form `r` of the term `t`. The special functions of `t`
+    /// are rewritten first, and the arithmetic is normalized over locals in place of
+    /// the atoms, so the steps of the proof don't repeat the atoms.
+    pub fn normalize(&self, t: &TermRef) -> (Normal, Eqn) {
+        if let Some(r) = self.normal.borrow().get(t) {
+            return r.clone();
+        }
+        let r = self.normalize_new(t);
+        self.normal.borrow_mut().insert(t.clone(), r.clone());
+        r
+    }
+
+    fn normalize_new(&self, t: &TermRef) -> (Normal, Eqn) {
+        let mut atoms = vec![];
+        let (s, e) = self.skeleton(t, &mut atoms);
+        atoms.sort();
+        atoms.dedup();
+        // the locals are sorted like the atoms, so the normal forms agree
+        let mut locals: Vec<TermRef> = atoms.iter().map(|_| self.prover.local(self.ty())).collect();
+        locals.sort();
+        let small = replace(&s, &atoms, &locals);
+        let (r, e_small) = self.arith(&small);
+        let r = r.map_atoms(|x| {
+            let i = locals.binary_search(x).expect("the atoms are locals");
+            atoms[i].clone()
+        });
+        let e_s = self.instantiate(&locals, &atoms, e_small, s, r.term());
+        (r, self.prover.trans(e, e_s))
+    }
+
+    /// `t = s` for the term `s` of sums, products and literal powers of atoms, by
+    /// rewriting the other functions of `t`. The atoms of `s` are added to `atoms`.
+    fn skeleton(&self, t: &TermRef, atoms: &mut Vec<TermRef>) -> (TermRef, Eqn) {
+        if self.carrier.literal(t).is_some() {
+            return (t.clone(), self.trivial(t));
+        }
+        if self.opaque.borrow().contains(t) {
+            atoms.push(t.clone());
+            return (t.clone(), self.trivial(t));
+        }
+        let Some((head, args)) = spine(t) else {
+            return self.atom_leaf(t, atoms);
+        };
+        let [plus_name, mult_name, minus_name] = self.carrier.names();
+        let op: fn(Carrier, TermRef, TermRef) -> TermRef = match head {
+            _ if head == plus_name => Carrier::add,
+            _ if head == mult_name => Carrier::mul,
+            _ if head == minus_name => Carrier::sub,
+            "pow" if is_small_literal(&args[1]) => |_, a, n| app_ref!(pow(), a, n),
+            "q_of_z" => return self.lift(&args[0], atoms),
+            _ => match self.special(t, head, &args) {
+                Some((r, e)) => {
+                    atoms.extend(r.monomials.iter().flat_map(|(_, x)| x.iter().cloned()));
+                    return (r.term(), e);
+                }
+                None => return self.atom_leaf(t, atoms),
+            },
+        };
+        let c = self.carrier;
+        let [a, b]: [TermRef; 2] = args
+            .try_into()
+            .expect("binary operators have two arguments");
+        let (sa, ea) = self.skeleton(&a, atoms);
+        let (sb, eb) = self.skeleton(&b, atoms);
+        let e = self.chain([
+            self.cong(ea, |x| op(c, x, b.clone())),
+            self.cong(eb, |x| op(c, sa.clone(), x)),
+        ]);
+        (op(c, sa, sb), e)
+    }
+
+    /// `↑x = s` of an integer `x`, for the rational term `s` of sums and products of
+    /// literals and the atoms `↑y` of the atoms `y` of the normal form of `x`
+    fn lift(&self, x: &TermRef, atoms: &mut Vec<TermRef>) -> (TermRef, Eqn) {
+        let (n, e) = self.integers().normalize(x);
+        let e = self.cong(e, |y| app_ref!(q_of_z(), y));
+        let (s, e2) = self.lift_term(&n.term(), atoms);
+        (s, self.prover.trans(e, e2))
+    }
+
+    fn lift_term(&self, t: &TermRef, atoms: &mut Vec<TermRef>) -> (TermRef, Eqn) {
+        let lifted = app_ref!(q_of_z(), t);
+        let op: fn(Carrier, TermRef, TermRef) -> TermRef = match spine(t) {
+            Some(("plus", _)) => Carrier::add,
+            Some(("mult", _)) => Carrier::mul,
+            Some(_) | None => {
+                if let Term::Number { .. } = t.as_ref() {
+                } else {
+                    atoms.push(lifted.clone());
+                }
+                return (lifted.clone(), self.trivial(&lifted));
+            }
+        };
+        let Some((head, args)) = spine(t) else {
+            unreachable!("sums and products are applications");
+        };
+        let [a, b]: [TermRef; 2] = args
+            .try_into()
+            .expect("binary operators have two arguments");
+        let c = self.carrier;
+        let (la, lb) = (app_ref!(q_of_z(), a), app_ref!(q_of_z(), b));
+        let split = self.ax(
+            &format!("ℚ_of_z_{head}"),
+            [a.clone(), b.clone()],
+            lifted,
+            op(c, la.clone(), lb.clone()),
+        );
+        let (sa, ea) = self.lift_term(&a, atoms);
+        let (sb, eb) = self.lift_term(&b, atoms);
+        let e = self.chain([
+            split,
+            self.cong(ea, |x| op(c, x, lb.clone())),
+            self.cong(eb, |x| op(c, sa.clone(), x)),
+        ]);
+        (op(c, sa, sb), e)
+    }
+
+    /// An atom of a skeleton, which its integer arguments are canonical
+    fn atom_leaf(&self, t: &TermRef, atoms: &mut Vec<TermRef>) -> (TermRef, Eqn) {
+        let e = self.canonical_atom(t);
+        atoms.push(e.rhs.clone());
+        (e.rhs.clone(), e)
+    }
+
+    /// The normal form of the special functions, which are understood like `Poly`
+    /// does, or `None` for atoms
+    fn special(&self, t: &TermRef, head: &str, args: &[TermRef]) -> Option<(Normal, Eqn)> {
+        Some(match (head, args) {
+            ("pow", [a, n]) => return self.pow(t, a, n),
+            ("z_of_n", [n]) => self.z_of_n(t, n),
+            ("len1", [ty, l]) => self.len(t, ty, l),
+            ("cnt", [ty, x, l]) => self.count(t, ty, x, l),
+            ("sigma", [a, b, f]) if is_number(a, 0) => self.sigma(b, f),
+            ("sigma", [a, b, f]) => {
+                let rhs = app_ref!(
+                    minus(),
+                    sigma0(b.clone(), f.clone()),
+                    sigma0(a.clone(), f.clone())
+                );
+                let e = self.ax(
+                    "ℤ_sigma_split",
+                    [a.clone(), b.clone(), f.clone()],
+                    t.clone(),
+                    rhs,
+                );
+                self.then_normalize(e)
+            }
+            _ => return None,
+        })
+    }
+
+    /// The normal form of a term of sums, products and literal powers of atoms
+    fn arith(&self, t: &TermRef) -> (Normal, Eqn) {
+        if let Some(value) = self.carrier.literal(t) {
+            return (self.constant(value.clone()), self.trivial(t));
+        }
+        let Some((head, args)) = spine(t) else {
+            return self.atom(self.trivial(t));
+        };
+        let [plus_name, mult_name, minus_name] = self.carrier.names();
+        match (head, args.as_slice()) {
+            (_, [a, b]) if head == plus_name => {
+                self.binary(a, b, |x, y| self.add(x, y), |x, y| self.add_normal(x, y))
+            }
+            (_, [a, b]) if head == mult_name => {
+                self.binary(a, b, |x, y| self.mul(x, y), |x, y| self.mul_normal(x, y))
+            }
+            (_, [a, b]) if head == minus_name => {
+                let m1 = -1;
+                let rhs = self.add(a.clone(), self.mul(self.num(&m1.into()), b.clone()));
+                let e = self.law("minus", [a.clone(), b.clone()], t.clone(), rhs);
+                let (r, e2) = self.arith(&e.rhs);
+                (r, self.prover.trans(e, e2))
+            }
+            ("pow", [a, n]) if is_small_literal(n) => {
+                let e = self.pow_literal(t, a, n);
+                let (r, e2) = self.arith(&e.rhs);
+                (r, self.prover.trans(e, e2))
+            }
+            _ => self.atom(self.trivial(t)),
+        }
+    }
+
+    fn binary(
+        &self,
+        a: &TermRef,
+        b: &TermRef,
+        op: impl Fn(TermRef, TermRef) -> TermRef,
+        op_normal: impl Fn(&Normal, &Normal) -> (Normal, Eqn),
+    ) -> (Normal, Eqn) {
+        let (x, ea) = self.arith(a);
+        let (y, eb) = self.arith(b);
+        let (r, e) = op_normal(&x, &y);
+        let xt = x.term();
+        let e = self.chain([
+            self.cong(ea, |t| op(t, b.clone())),
+            self.cong(eb, |t| op(xt.clone(), t)),
+            e,
+        ]);
+        (r, e)
+    }
+
+    /// The normal form `1 * x + 0` of the atom `x` in `t = x`
+    fn atom(&self, e: Eqn) -> (Normal, Eqn) {
+        let x = e.rhs.clone();
+        let e = self.chain([
+            e,
+            self.sym(self.plus_zero(x.clone())),
+            self.add_right(self.sym(self.mult_one(x.clone())), &self.num(&0.into())),
+        ]);
+        (self.constant(0.into()).push_front((1.into(), vec![x])), e)
+    }
+
+    /// Replaces the integer arguments of the atom with their canonical terms, like
+    /// `Poly` does
+    fn canonical_atom(&self, t: &TermRef) -> Eqn {
+        self.canonical_args(t).unwrap_or_else(|| self.trivial(t))
+    }
+
+    /// `t = t'` for the term `t'` of type `ty` with the canonical integer arguments
+    pub fn canonical_term(&self, ty: &TermRef, t: &TermRef) -> Eqn {
+        self.canonical_args(t)
+            .unwrap_or_else(|| Eqn::by_computation(ty.clone(), t.clone(), t.clone()))
+    }
+
+    fn canonical_args(&self, t: &TermRef) -> Option<Eqn> {
+        let Term::App { func, op } = t.as_ref() else {
+            return None;
+        };
+        let e_op = if type_of(op.clone()) == Ok(z()) {
+            Some(self.integers().canonical(op).1).filter(|e| e.rhs != *op)
+        } else {
+            self.canonical_args(op)
+        };
+        let e_func = self.canonical_args(func);
+        if e_op.is_none() && e_func.is_none() {
+            return None;
+        }
+        let ty = type_of(t.clone()).ok()?;
+        let mut r = Eqn::by_computation(ty.clone(), t.clone(), t.clone());
+        let mut func = func.clone();
+        if let Some(e) = e_func {
+            func = e.rhs.clone();
+            let e = self.prover.cong(e, ty.clone(), |g| app_ref!(g, op));
+            r = self.prover.trans(r, e);
+        }
+        if let Some(e) = e_op {
+            let e = self.prover.cong(e, ty, |x| app_ref!(func, x));
+            r = self.prover.trans(r, e);
+        }
+        Some(r)
+    }
+
+    /// `a ^ n = r` of a literal exponent `0 ≤ n < 5`, where `r` is `1` or `a * a ^ (n - 1)`
+    fn pow_literal(&self, t: &TermRef, a: &TermRef, n: &TermRef) -> Eqn {
+        let Term::Number { value } = n.as_ref() else {
+            unreachable!("the exponent is a literal");
+        };
+        if *value == 0.into() {
+            return self.ax("ℤ_pow_zero", [a.clone()], t.clone(), term_ref!(n 1));
+        }
+        let m = value - 1;
+        let rhs = self.mul(a.clone(), app_ref!(pow(), a, self.num(&m)));
+        let nonneg = self.prover.nonneg(&m);
+        self.ax(
+            "ℤ_pow_succ",
+            [a.clone(), self.num(&m), nonneg],
+            t.clone(),
+            rhs,
+        )
+    }
+
+    /// The powers which are not literal powers, which are atoms unless the base is
+    /// `1`, or the exponent is a literal in disguise, like `n - n`
+    fn pow(&self, t: &TermRef, a: &TermRef, n: &TermRef) -> Option<(Normal, Eqn)> {
+        if is_number(a, 1) {
+            let e = self.ax("ℤ_one_pow", [n.clone()], t.clone(), term_ref!(n 1));
+            return Some((self.constant(1.into()), e));
+        }
+        if let Term::Number { .. } = n.as_ref() {
+            return None;
+        }
+        let (nn, e_n) = self.canonical(n);
+        nn.as_constant()?;
+        Some(self.then_normalize(self.cong(e_n, |x| app_ref!(pow(), a, x))))
+    }
+
+    /// `↑n` is `0` for `zero`, and `↑m + 1` for `succ m`, by the computation rules of
+    /// the eliminator which `z_of_n` is defined with
+    fn z_of_n(&self, t: &TermRef, n: &TermRef) -> (Normal, Eqn) {
+        let body = self.prover.engine().body_of_definition("z_of_n").ok();
+        let rect = body.as_ref().and_then(spine).map(|(_, args)| args);
+        match (spine(n), rect) {
+            (Some(("zero", _)), Some(rect)) => {
+                let proof = self.prover.apply("ℕ_rect_zero", rect);
+                let e = Eqn::new(z(), t.clone(), term_ref!(n 0), proof);
+                (self.constant(0.into()), e)
+            }
+            (Some(("succ", m)), Some(rect)) if m.len() == 1 => {
+                let rhs = self.add(app_ref!(z_of_n(), m[0]), term_ref!(n 1));
+                let proof = self.prover.apply("ℕ_rect_succ", [rect, m].concat());
+                self.then_normalize(Eqn::new(z(), t.clone(), rhs, proof))
+            }
+            _ => self.atom(self.canonical_atom(t)),
+        }
+    }
+
+    fn len(&self, t: &TermRef, ty: &TermRef, l: &TermRef) -> (Normal, Eqn) {
+        let len = |l: &TermRef| app_ref!(len1(), ty, l);
+        let e = match spine(l) {
+            Some(("nil", args)) if args.len() == 1 => {
+                self.ax("ℤ_len_nil", [args[0].clone()], t.clone(), term_ref!(n 0))
+            }
+            Some(("set_empty", args)) if args.len() == 1 => self.ax(
+                "ℤ_len_set_empty",
+                [args[0].clone()],
+                t.clone(),
+                term_ref!(n 0),
+            ),
+            Some(("cons", args)) if args.len() == 3 => {
+                let rhs = self.add(term_ref!(n 1), len(&args[2]));
+                let args: [TermRef; 3] = args.try_into().unwrap();
+                self.ax("ℤ_len_cons", args, t.clone(), rhs)
+            }
+            Some(("plus_list", args)) if args.len() == 3 => {
+                let rhs = self.add(len(&args[1]), len(&args[2]));
+                let args: [TermRef; 3] = args.try_into().unwrap();
+                self.ax("ℤ_len_plus_list", args, t.clone(), rhs)
+            }
+            _ => return self.atom(self.canonical_atom(t)),
+        };
+        self.then_normalize(e)
+    }
+
+    fn count(&self, t: &TermRef, ty: &TermRef, x: &TermRef, l: &TermRef) -> (Normal, Eqn) {
+        let count = |l: &TermRef| app_ref!(cnt(), ty, x, l);
+        let e = match spine(l) {
+            Some(("nil", args)) if args.len() == 1 => {
+                let args = [args[0].clone(), x.clone()];
+                self.ax("ℤ_cnt_nil", args, t.clone(), term_ref!(n 0))
+            }
+            Some(("cons", args)) if args.len() == 3 && args[1] == *x => {
+                let rhs = self.add(term_ref!(n 1), count(&args[2]));
+                let args = [args[0].clone(), x.clone(), args[2].clone()];
+                self.ax("ℤ_cnt_cons_eq", args, t.clone(), rhs)
+            }
+            Some(("cons", args)) if args.len() == 3 && definitely_inequal(x, &args[1]) => {
+                let [a, y, rest]: [TermRef; 3] = args.try_into().unwrap();
+                let neq = self.literal_neq(&a, x, &y);
+                let rhs = count(&rest);
+                self.ax(
+                    "ℤ_cnt_cons_neq",
+                    [a, x.clone(), y, rest, neq],
+                    t.clone(),
+                    rhs,
+                )
+            }
+            Some(("plus_list", args)) if args.len() == 3 => {
+                let rhs = self.add(count(&args[1]), count(&args[2]));
+                let args = [args[0].clone(), x.clone(), args[1].clone(), args[2].clone()];
+                self.ax("ℤ_cnt_plus_list", args, t.clone(), rhs)
+            }
+            _ => return self.atom(self.canonical_atom(t)),
+        };
+        self.then_normalize(e)
+    }
+
+    /// The proof of `x = y → False` for two different literals of type `ty`. Counting
+    /// `w` in the list `[x]` computes to `1` for `w = x`, and to `0` for `w = y`.
+    pub fn literal_neq(&self, ty: &TermRef, x: &TermRef, y: &TermRef) -> TermRef {
+        let p = self.prover;
+        let singleton = app_ref!(cons(), ty, x, app_ref!(nil(), ty));
+        p.abstract_over(app_ref!(eq(), ty, x, y), |h| {
+            let (zero, one) = (term_ref!(n 0), term_ref!(n 1));
+            let x_is_y = Eqn::new(ty.clone(), x.clone(), y.clone(), h);
+            let counted = p.rewrite(&x_is_y, p.refl(z(), one.clone()), |w| {
+                eq_z(app_ref!(cnt(), ty, w, singleton), one.clone())
+            });
+            let zero_is_one = Eqn::new(z(), zero.clone(), one, counted);
+            let irrefl = p.apply("ℤ_lt_irrefl", [zero.clone()]);
+            let not_lt = p.rewrite(&zero_is_one, irrefl, |w| not(lt_z(zero, w)));
+            app_ref!(not_lt, p.apply("ℤ_zero_lt_one", []))
+        })
+    }
+
+    /// `sigma 0 r f`, which is unrolled when `r` has a small constant, and otherwise
+    /// is split into the sums of the monomials of `f`
+    fn sigma(&self, r: &TermRef, f: &TermRef) -> (Normal, Eqn) {
+        let (rn, e_r) = self.canonical(r);
+        let rc = e_r.rhs.clone();
+        let mut steps = vec![self.cong(e_r, |x| sigma0(x, f.clone()))];
+        let c = rn.constant.clone();
+        let bound = BigInt::from(5);
+        if rn.monomials.is_empty() && c == 0.into() {
+            steps.push(self.ax(
+                "ℤ_sigma_zero",
+                [f.clone()],
+                sigma0(rc, f.clone()),
+                term_ref!(n 0),
+            ));
+            return (self.constant(0.into()), self.chain(steps));
+        }
+        if c > 0.into() && c <= bound {
+            // sigma 0 r f = sigma 0 (r - 1) f + f (r - 1)
+            let prev = rn.with_constant(&c - 1).poly().into_canonical_term();
+            let succ = self.add(prev.clone(), term_ref!(n 1));
+            let e_r = self.prove_eq(&rc, &succ).unwrap();
+            let rhs = self.add(
+                sigma0(prev.clone(), f.clone()),
+                brain::normalize(app_ref!(f, prev)),
+            );
+            steps.push(self.cong(e_r, |x| sigma0(x, f.clone())));
+            steps.push(self.ax(
+                "ℤ_sigma_succ",
+                [prev, f.clone()],
+                sigma0(succ, f.clone()),
+                rhs,
+            ));
+            return self.then_normalize(self.chain(steps));
+        }
+        if c < 0.into() && c >= -bound {
+            // sigma 0 r f = sigma 0 (r + 1) f - f r
+            let next = rn.with_constant(&c + 1).poly().into_canonical_term();
+            let succ = self.add(rc.clone(), term_ref!(n 1));
+            let s = sigma0(rc.clone(), f.clone());
+            let fr = brain::normalize(app_ref!(f, rc));
+            let m1 = -1;
+            let minus_fr = self.mul(term_ref!(n m1), fr.clone());
+            let e_r = self.prove_eq(&succ, &next).unwrap();
+            let sigma_succ = self.ax(
+                "ℤ_sigma_succ",
+                [rc.clone(), f.clone()],
+                sigma0(succ, f.clone()),
+                self.add(s.clone(), fr.clone()),
+            );
+            steps.push(self.identity([s, fr], |[s, x]| {
+                (
+                    s.clone(),
+                    self.add(
+                        self.add(s.clone(), x.clone()),
+                        self.mul(term_ref!(n m1), x.clone()),
+                    ),
+                )
+            }));
+            steps.push(self.add_right(self.sym(sigma_succ), &minus_fr));
+            steps.push(self.add_right(self.cong(e_r, |x| sigma0(x, f.clone())), &minus_fr));
+            return self.then_normalize(self.chain(steps));
+        }
+        steps.push(self.sigma_split(&rc, f));
+        self.then_normalize(self.chain(steps))
+    }
+
+    /// `sigma 0 r f = Σ c * sigma 0 r (λ i, m i) + k * r` for the monomials `c * m i` of
+    /// the body of `f`, where `m i` is the product of the atoms which depend on `i`,
+    /// and `k` is its constant. The sums `sigma 0 r (λ i, m i)` are atoms from now on.
+    fn sigma_split(&self, r: &TermRef, f: &TermRef) -> Eqn {
+        let p = self.prover;
+        let i = p.local(z());
+        let Term::Axiom {
+            unique_name: i_name,
+            ..
+        } = i.as_ref()
+        else {
+            unreachable!("locals are axioms");
+        };
+        let body = brain::normalize(app_ref!(f, i));
+        let (bn, e_body) = self.normalize(&body);
+        // each monomial as its coefficient, and its product of the atoms which depend on `i`
+        let parts: Vec<(TermRef, Option<TermRef>)> = bn
+            .monomials
+            .iter()
+            .map(|(k, atoms)| {
+                let (dep, indep): (Vec<_>, Vec<_>) = atoms
+                    .iter()
+                    .cloned()
+                    .partition(|x| x.axioms().any(|name| name == i_name));
+                let coef = if indep.is_empty() {
+                    self.num(k)
+                } else {
+                    self.mul(self.num(k), self.carrier.monomial(&indep))
+                };
+                (coef, (!dep.is_empty()).then(|| self.carrier.monomial(&dep)))
+            })
+            .collect();
+        let part_term = |(coef, dep): &(TermRef, Option<TermRef>)| match dep {
+            Some(dep) => self.mul(coef.clone(), dep.clone()),
+            None => coef.clone(),
+        };
+        let constant = self.num(&bn.constant);
+        let sum_of = |parts: &[(TermRef, Option<TermRef>)]| {
+            let sum = parts
+                .iter()
+                .rev()
+                .fold(constant.clone(), |r, x| self.add(part_term(x), r));
+            Prover::lambda(&i, sum)
+        };
+        let reshaped = sum_of(&parts);
+        let e_body = self
+            .prove_eq(&brain::normalize(app_ref!(reshaped, i)), &bn.term())
+            .map(|e| p.trans(e_body, self.sym(e)))
+            .expect("the monomials are only grouped differently");
+        let lhs = sigma0(r.clone(), f.clone());
+        let mut steps = vec![if e_body.is_trivial() {
+            Eqn::by_computation(z(), lhs, sigma0(r.clone(), reshaped))
+        } else {
+            let ext = Prover::lambda(&i, p.proof(&e_body));
+            let args = [term_ref!(n 0), r.clone(), f.clone(), reshaped.clone(), ext];
+            self.ax("ℤ_sigma_ext", args, lhs, sigma0(r.clone(), reshaped))
+        }];
+        // the sums of the previous monomials, which the next steps are added to
+        let mut done: Vec<TermRef> = vec![];
+        let add_done =
+            |e: Eqn, done: &[TermRef]| done.iter().rev().fold(e, |e, x| self.add_left(e, x));
+        for (k, part) in parts.iter().enumerate() {
+            let head = Prover::lambda(&i, part_term(part));
+            let rest = sum_of(&parts[k + 1..]);
+            let split = self.ax(
+                "ℤ_sigma_plus",
+                [r.clone(), head.clone(), rest.clone()],
+                sigma0(r.clone(), sum_of(&parts[k..])),
+                self.add(
+                    sigma0(r.clone(), head.clone()),
+                    sigma0(r.clone(), rest.clone()),
+                ),
+            );
+            let (coef, dep) = part;
+            let summed = match dep {
+                Some(dep) => {
+                    let dep = Prover::lambda(&i, dep.clone());
+                    let s = sigma0(r.clone(), dep.clone());
+                    self.opaque.borrow_mut().push(s.clone());
+                    let args = [r.clone(), coef.clone(), dep];
+                    self.ax(
+                        "ℤ_sigma_mult",
+                        args,
+                        sigma0(r.clone(), head),
+                        self.mul(coef.clone(), s),
+                    )
+                }
+                None => {
+                    let rhs = self.mul(coef.clone(), r.clone());
+                    self.ax(
+                        "ℤ_sigma_const",
+                        [r.clone(), coef.clone()],
+                        sigma0(r.clone(), head),
+                        rhs,
+                    )
+                }
+            };
+            let step = self.chain([
+                split,
+                self.add_right(summed.clone(), &sigma0(r.clone(), rest)),
+            ]);
+            steps.push(add_done(step, &done));
+            done.push(summed.rhs);
+        }
+        let rhs = self.mul(constant.clone(), r.clone());
+        let last = self.ax(
+            "ℤ_sigma_const",
+            [r.clone(), constant.clone()],
+            sigma0(r.clone(), sum_of(&[])),
+            rhs,
+        );
+        steps.push(add_done(last, &done));
+        self.chain(steps)
+    }
+}
@@ -1,15 +1,21 @@
 use crate::parser::term_pretty_print;
-use std::{cmp::Ordering, fmt::Debug, hash::Hash, rc::Rc};
+use std::{cmp::Ordering, collections::HashMap, fmt::Debug, hash::Hash};
 
+pub mod compute;
+pub mod delta;
+pub mod inductive;
 pub mod infer;
+mod intern;
+pub mod level;
 mod subtyping;
 
-pub use subtyping::subtype_and_infer;
+pub use intern::TermRef;
+pub use subtyping::{coerce, subtype_and_infer};
 
 #[cfg(test)]
 mod tests;
 
-#[derive(Debug, Clone, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, Serialize, Deserialize)]
 pub struct Abstraction {
     pub var_ty: TermRef,
     pub hint_name: Option<String>,
@@ -31,10 +37,23 @@ impl Hash for Abstraction {
     }
 }
 
+// we implement this manually to ignore hint_name, so the order agrees with the equality
+impl PartialOrd for Abstraction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Abstraction {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.var_ty, &self.body).cmp(&(&other.var_ty, &other.body))
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Term {
     Axiom { ty: TermRef, unique_name: String },
-    Universe { index: usize },
+    Universe { level: Level },
     Forall(Abstraction),
     Fun(Abstraction),
     Var { index: usize },
@@ -43,8 +62,6 @@ pub enum Term {
     Wild { index: usize, scope: usize },
 }
 
-pub type TermRef = Rc<Term>;
-
 impl Debug for Term {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(&term_pretty_print::<String, _>(
@@ -66,7 +83,7 @@ macro_rules! term {
     {forall $ty:expr , $($i:tt)*} => (crate::Term::Forall(crate::Abstraction { var_ty: term_ref!($ty), hint_name: None, body: (term_ref!($( $i)*)) }));
     {fun $ty:expr , $($i:tt)*} => (crate::Term::Fun(crate::Abstraction { var_ty: term_ref!($ty), hint_name: None, body: (term_ref!($( $i)*)) }));
     {axiom $name:expr , $($i:tt)*} => (crate::Term::Axiom { ty: term_ref!($( $i)*), unique_name: ($name).to_string() });
-    {universe $input:expr} => (crate::Term::Universe { index: ($input) });
+    {universe $input:expr} => (crate::Term::Universe { level: crate::brain::level::Level::Const($input) });
     {v $input:expr} => (crate::Term::Var { index: ($input) });
     {n $input:expr} => (crate::Term::Number { value: ($input).into() });
     {_ $input:expr} => (crate::Term::Wild { index: ($input), scope: 0 });
@@ -118,6 +135,7 @@ pub enum ErrorReason {
     IsNotUniverse,
     LoopOfInference(usize, TermRef),
     WildNeedLocalVar(usize),
+    UniverseInconsistency(Level, Level),
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -156,10 +174,61 @@ impl Error {
         self.context.push(context);
         self
     }
+
+    pub fn reason(&self) -> &ErrorReason {
+        &self.reason
+    }
+}
+
+impl Report for Error {
+    fn code(&self) -> &'static str {
+        match &self.reason {
+            ForiegnVariableInTerm(_) => "E0201",
+            TypeMismatch(..) => "E0202",
+            IsNotFunc { .. } => "E0203",
+            ContainsWild => "E0204",
+            IsNotUniverse => "E0205",
+            LoopOfInference(..) => "E0206",
+            WildNeedLocalVar(_) => "E0207",
+            UniverseInconsistency(..) => "E0208",
+        }
+    }
+
+    fn message(&self, print: TermPrinter) -> Message {
+        let universe = |level: &Level| print(&Term::Universe { level: *level });
+        match &self.reason {
+            ForiegnVariableInTerm(i) => Message::new("foreign_variable", vec![i.to_string()]),
+            TypeMismatch(a, b) => Message::new("type_mismatch", vec![print(a), print(b)]),
+            IsNotFunc { value, ty } => Message::new("is_not_func", vec![print(value), print(ty)]),
+            ContainsWild => Message::new("contains_wild", vec![]),
+            IsNotUniverse => Message::new("is_not_universe", vec![]),
+            LoopOfInference(i, t) => {
+                Message::new("loop_of_inference", vec![i.to_string(), print(t)])
+            }
+            WildNeedLocalVar(i) => Message::new("wild_need_local_var", vec![i.to_string()]),
+            UniverseInconsistency(a, b) => {
+                Message::new("universe_inconsistency", vec![universe(a), universe(b)])
+            }
+        }
+    }
+
+    fn context(&self, print: TermPrinter) -> Vec<Message> {
+        self.context
+            .iter()
+            .map(|x| match x {
+                ErrorContext::InMatching(a, b) => {
+                    Message::new("in_matching", vec![print(a), print(b)])
+                }
+                ErrorContext::InTypechecking(t) => Message::new("in_typechecking", vec![print(t)]),
+            })
+            .collect()
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+use crate::report::{Message, Report, TermPrinter};
+use level::Level;
 use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
 use ErrorReason::*;
@@ -178,6 +247,9 @@ pub fn map_reduce_wild<T>(
             (None, None) => None,
         }
     };
+    if !t.has_wild() {
+        return None;
+    }
     match t {
         Term::Axiom { .. } | Term::Universe { .. } | Term::Var { .. } | Term::Number { .. } => None,
         Term::App { func, op } => combine(func, op),
@@ -218,78 +290,66 @@ pub fn predict_wild(t: &Term, predict: &impl Fn(usize, usize) -> bool) -> bool {
 }
 
 pub fn fill_axiom(t: TermRef, converter: impl Fn(&str, TermRef, usize) -> TermRef) -> TermRef {
+    /// results of the shared subterms, by their depth
+    type Memo = HashMap<(TermRef, usize), TermRef>;
     fn for_abs(
         abs: &Abstraction,
         f: &impl Fn(&str, TermRef, usize) -> TermRef,
         depth: usize,
+        memo: &mut Memo,
     ) -> Abstraction {
         let abs = abs.clone();
         Abstraction {
-            var_ty: fa_rec(abs.var_ty, f, depth),
+            var_ty: fa_rec(abs.var_ty, f, depth, memo),
             hint_name: abs.hint_name,
-            body: fa_rec(abs.body, f, depth + 1),
+            body: fa_rec(abs.body, f, depth + 1, memo),
         }
     }
-    fn fa_rec(t: TermRef, f: &impl Fn(&str, TermRef, usize) -> TermRef, depth: usize) -> TermRef {
-        match t.as_ref() {
-            Term::Axiom { ty, unique_name } => f(unique_name, ty.clone(), depth),
-            Term::Wild { .. } | Term::Number { .. } | Term::Var { .. } | Term::Universe { .. } => t,
-            Term::Forall(abs) => TermRef::new(Term::Forall(for_abs(abs, f, depth))),
-            Term::Fun(abs) => TermRef::new(Term::Fun(for_abs(abs, f, depth))),
-            Term::App { func, op } => {
-                app_ref!(fa_rec(func.clone(), f, depth), fa_rec(op.clone(), f, depth))
-            }
+    fn fa_rec(
+        t: TermRef,
+        f: &impl Fn(&str, TermRef, usize) -> TermRef,
+        depth: usize,
+        memo: &mut Memo,
+    ) -> TermRef {
+        if !t.has_axiom() {
+            return t;
         }
-    }
-    fa_rec(t, &converter, 0)
-}
-
-/// if expression contains some axiom, it will computes predict(i1) || predict(i2) || ... || predict(in)
-/// when ik is unique_name of axioms. In case of no axiom, it will return false
-pub fn map_reduce_axiom<T>(
-    t: &Term,
-    map: &mut impl FnMut(&str) -> Option<T>,
-    reduce: &impl Fn(T, T) -> T,
-) -> Option<T> {
-    let mut combine = |a, b| {
-        let a = map_reduce_axiom(a, map, reduce);
-        let b = map_reduce_axiom(b, map, reduce);
-        match (a, b) {
-            (Some(a), Some(b)) => Some(reduce(a, b)),
-            (Some(a), None) | (None, Some(a)) => Some(a),
-            (None, None) => None,
+        if let Term::Axiom { ty, unique_name } = t.as_ref() {
+            return f(unique_name, ty.clone(), depth);
         }
-    };
-    match t {
-        Term::Wild { .. } | Term::Universe { .. } | Term::Var { .. } | Term::Number { .. } => None,
-        Term::App { func, op } => combine(func, op),
-        Term::Forall(Abstraction {
-            var_ty,
-            body,
-            hint_name: _,
-        })
-        | Term::Fun(Abstraction {
-            var_ty,
-            body,
-            hint_name: _,
-        }) => combine(var_ty, body),
-        Term::Axiom { unique_name, .. } => map(unique_name),
+        let key = (t, depth);
+        if let Some(r) = memo.get(&key) {
+            return r.clone();
+        }
+        let r = match key.0.as_ref() {
+            Term::Forall(abs) => TermRef::new(Term::Forall(for_abs(abs, f, depth, memo))),
+            Term::Fun(abs) => TermRef::new(Term::Fun(for_abs(abs, f, depth, memo))),
+            Term::App { func, op } => {
+                app_ref!(
+                    fa_rec(func.clone(), f, depth, memo),
+                    fa_rec(op.clone(), f, depth, memo)
+                )
+            }
+            Term::Axiom { .. }
+            | Term::Wild { .. }
+            | Term::Number { .. }
+            | Term::Var { .. }
+            | Term::Universe { .. } => key.0.clone(),
+        };
+        memo.insert(key, r.clone());
+        r
     }
+    fa_rec(t, &converter, 0, &mut HashMap::new())
 }
 
 /// if expression contains some axiom, it will computes predict(i1) || predict(i2) || ... || predict(in)
 /// when ik is unique_name of axioms. In case of no axiom, it will return false
 pub fn predict_axiom(t: &Term, predict: impl Fn(&str) -> bool) -> bool {
-    map_reduce_axiom(
-        t,
-        &mut |x| if predict(x) { Some(()) } else { None },
-        &|_, _| (),
-    )
-    .is_some()
+    t.any_axiom(predict)
 }
 
 pub fn contains_wild(t: &Term) -> bool {
-    predict_wild(t, &|_, _| true)
+    t.has_wild()
 }
 
 pub fn remove_unused_var(t: TermRef, depth: usize) -> Option<TermRef> {
@@ -310,6 +370,9 @@ pub fn remove_unused_var(t: TermRef, depth: usize) -> Option<TermRef> {
             hint_name,
         })
     }
+    if t.loose_bound() <= depth {
+        return Some(t);
+    }
     Some(match t.as_ref() {
         Term::Axiom { .. } | Term::Universe { .. } | Term::Wild { .. } | Term::Number { .. } => t,
         Term::App { func, op } => {
@@ -361,6 +424,9 @@ pub fn fill_wild_with_depth(
             body,
         }
     }
+    if !t.has_wild() {
+        return t;
+    }
     match t.as_ref() {
         Term::Axiom { .. } | Term::Universe { .. } | Term::Var { .. } | Term::Number { .. } => t,
         Term::App { func, op } => app_ref!(
@@ -414,39 +480,62 @@ pub fn normalize(t: TermRef) -> TermRef {
 }
 
 pub fn subst(exp: TermRef, to_put: TermRef) -> TermRef {
-    fn for_abs(abs: Abstraction, to_put: TermRef, i: usize) -> Abstraction {
-        let var_ty = inner(abs.var_ty, to_put.clone(), i);
-        let to_put = increase_foreign_vars(to_put, 0);
-        let body = inner(abs.body, to_put, i + 1);
-        Abstraction {
-            var_ty,
-            body,
-            hint_name: abs.hint_name,
-        }
+    /// `to_put` by the depth, and the results of the shared subterms by their depth
+    struct Subst {
+        to_put: Vec<TermRef>,
+        memo: HashMap<(TermRef, usize), TermRef>,
     }
-    fn inner(exp: TermRef, to_put: TermRef, i: usize) -> TermRef {
-        match exp.as_ref() {
-            Term::Var { index } => match i.cmp(index) {
-                Ordering::Less => term_ref!(v index - 1),
-                Ordering::Equal => to_put,
-                Ordering::Greater => {
-                    let x = *index;
-                    term_ref!(v x)
-                }
-            },
-            Term::Axiom { .. }
-            | Term::Universe { .. }
-            | Term::Number { .. }
-            | Term::Wild { .. } => exp,
-            Term::Forall(a) => TermRef::new(Term::Forall(for_abs(a.clone(), to_put, i))),
-            Term::Fun(a) => TermRef::new(Term::Fun(for_abs(a.clone(), to_put, i))),
-            Term::App { func, op } => TermRef::new(Term::App {
-                func: inner(func.clone(), to_put.clone(), i),
-                op: inner(op.clone(), to_put, i),
-            }),
+    impl Subst {
+        /// `to_put` under `i` binders
+        fn shifted(&mut self, i: usize) -> TermRef {
+            while self.to_put.len() <= i {
+                let last = self.to_put.last().expect("to_put of depth 0").clone();
+                self.to_put.push(increase_foreign_vars(last, 0));
+            }
+            self.to_put[i].clone()
+        }
+
+        fn for_abs(&mut self, abs: &Abstraction, i: usize) -> Abstraction {
+            Abstraction {
+                var_ty: self.inner(abs.var_ty.clone(), i),
+                body: self.inner(abs.body.clone(), i + 1),
+                hint_name: abs.hint_name.clone(),
+            }
+        }
+
+        fn inner(&mut self, exp: TermRef, i: usize) -> TermRef {
+            if exp.loose_bound() <= i {
+                return exp;
+            }
+            if let Term::Var { index } = exp.as_ref() {
+                return match i.cmp(index) {
+                    Ordering::Less => term_ref!(v index - 1),
+                    Ordering::Equal => self.shifted(i),
+                    Ordering::Greater => exp,
+                };
+            }
+            let key = (exp, i);
+            if let Some(r) = self.memo.get(&key) {
+                return r.clone();
+            }
+            let r = match key.0.as_ref() {
+                Term::Forall(a) => TermRef::new(Term::Forall(self.for_abs(a, i))),
+                Term::Fun(a) => TermRef::new(Term::Fun(self.for_abs(a, i))),
+                Term::App { func, op } => TermRef::new(Term::App {
+                    func: self.inner(func.clone(), i),
+                    op: self.inner(op.clone(), i),
+                }),
+                _ => unreachable!("closed terms are returned as they are"),
+            };
+            self.memo.insert(key, r.clone());
+            r
         }
     }
-    inner(exp, to_put, 0)
+    Subst {
+        to_put: vec![to_put],
+        memo: HashMap::new(),
+    }
+    .inner(exp, 0)
 }
 
 pub fn increase_foreign_vars(term: TermRef, depth: usize) -> TermRef {
@@ -467,6 +556,9 @@ pub fn increase_foreign_vars(term: TermRef, depth: usize) -> TermRef {
             hint_name,
         }
     }
+    if term.loose_bound() <= depth {
+        return term;
+    }
     match term.as_ref() {
         Term::Var { index } if *index >= depth => TermRef::new(Term::Var { index: index + 1 }),
         Term::Axiom { .. }
@@ -486,7 +578,11 @@ pub fn increase_foreign_vars(term: TermRef, depth: usize) -> TermRef {
 
 pub fn type_of(term: TermRef) -> Result<TermRef> {
     deny_wild(&term)?;
-    infer::type_of_inner(term, &[], &mut infer::InferResults::new(0))
+    let mut infers = infer::InferResults::new(0).with_computation();
+    infers.levels.reserve(&term);
+    let ty = infer::type_of_inner(term, &[], &mut infers)?;
+    infers.levels.solve()?;
+    Ok(infers.levels.fill(ty))
 }
 
 pub fn get_forall_depth(term: &Term) -> usize {
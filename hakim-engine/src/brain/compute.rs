@@ -0,0 +1,145 @@
+use num_bigint::{BigInt, Sign};
+
+use super::{normalize, Abstraction, Term, TermRef};
+use crate::{
+    app_ref,
+    library::prelude::{cons, nil},
+    term_ref,
+};
+
+/// Exponents larger than this are left unevaluated, so `2 ^ 10000000` doesn't
+/// allocate a huge number.
+const MAX_RESULT_BITS: u64 = 1 << 16;
+
+/// Normalizes the term and evaluates the builtin functions on literals, so
+/// `2 ^ 10` becomes `1024`, `|[1, 2] ++ [3]|` becomes `3` and `proj1 (a, b)` becomes `a`.
+pub fn compute(t: TermRef) -> TermRef {
+    evaluate(normalize(t))
+}
+
+fn evaluate(t: TermRef) -> TermRef {
+    let for_abs = |abs: &Abstraction| Abstraction {
+        var_ty: evaluate(abs.var_ty.clone()),
+        hint_name: abs.hint_name.clone(),
+        body: evaluate(abs.body.clone()),
+    };
+    match t.as_ref() {
+        Term::Forall(abs) => TermRef::new(Term::Forall(for_abs(abs))),
+        Term::Fun(abs) => TermRef::new(Term::Fun(for_abs(abs))),
+        Term::App { func, op } => {
+            let t = app_ref!(evaluate(func.clone()), evaluate(op.clone()));
+            reduce(&t).unwrap_or(t)
+        }
+        Term::Axiom { .. }
+        | Term::Universe { .. }
+        | Term::Var { .. }
+        | Term::Number { .. }
+        | Term::Wild { .. } => t,
+    }
+}
+
+/// Name of the head axiom and the arguments of an application.
+fn spine(mut t: &TermRef) -> Option<(&str, Vec<&TermRef>)> {
+    let mut args = vec![];
+    while let Term::App { func, op } = t.as_ref() {
+        args.push(op);
+        t = func;
+    }
+    args.reverse();
+    match t.as_ref() {
+        Term::Axiom { unique_name, .. } => Some((unique_name, args)),
+        _ => None,
+    }
+}
+
+fn number(t: &Term) -> Option<&BigInt> {
+    match t {
+        Term::Number { value } => Some(value),
+        _ => None,
+    }
+}
+
+/// Elements of a list literal, like `[1, 2, 3]`.
+fn list_elements(t: &TermRef) -> Option<Vec<TermRef>> {
+    match spine(t)? {
+        ("nil", args) if args.len() == 1 => Some(vec![]),
+        ("cons", args) if args.len() == 3 => {
+            let mut r = vec![args[1].clone()];
+            r.extend(list_elements(args[2])?);
+            Some(r)
+        }
+        _ => None,
+    }
+}
+
+/// Whether equality of the literals is decided by comparing them. Characters are
+/// literals only with the codes which the parser gives them.
+fn is_literal(t: &TermRef) -> bool {
+    match spine(t) {
+        Some(("chr", args)) => {
+            args.len() == 1
+                && number(args[0]).is_some_and(|x| x.sign() != Sign::Minus && x.bits() <= 8)
+        }
+        _ => number(t).is_some(),
+    }
+}
+
+/// The remainder of dividing by the positive `m`, which is never negative.
+fn modulo(a: &BigInt, m: &BigInt) -> BigInt {
+    ((a % m) + m) % m
+}
+
+/// Evaluates the application, if its head is a builtin and its arguments are literals.
+fn reduce(t: &TermRef) -> Option<TermRef> {
+    let (name, args) = spine(t)?;
+    let num = |i: usize| number(args[i]);
+    let r = match (name, args.len()) {
+        ("plus", 2) => term_ref!(n num(0)? + num(1)?),
+        ("minus", 2) => term_ref!(n num(0)? - num(1)?),
+        ("mult", 2) => term_ref!(n num(0)? * num(1)?),
+        ("pow", 2) => {
+            let (a, n) = (num(0)?, num(1)?);
+            // negative powers are left unevaluated, since nothing defines them
+            let n = u32::try_from(n).ok()?;
+            if a.bits() * u64::from(n) > MAX_RESULT_BITS {
+                return None;
+            }
+            term_ref!(n a.pow(n))
+        }
+        ("mod_of", 2) => {
+            let (a, b) = (num(0)?, num(1)?);
+            if b.sign() == Sign::NoSign {
+                return None;
+            }
+            term_ref!(n modulo(a, &b.magnitude().clone().into()))
+        }
+        ("proj1", 3) | ("proj2", 3) => match spine(args[2])? {
+            ("pair", pair) if pair.len() == 4 => pair[if name == "proj1" { 2 } else { 3 }].clone(),
+            _ => return None,
+        },
+        ("len1", 2) => match number(args[1]) {
+            Some(x) => term_ref!(n x.magnitude().clone()),
+            None => term_ref!(n BigInt::from(list_elements(args[1])?.len())),
+        },
+        ("cnt", 3) => {
+            let x = args[1];
+            let l = list_elements(args[2])?;
+            if !is_literal(x) || !l.iter().all(is_literal) {
+                return None;
+            }
+            term_ref!(n BigInt::from(l.iter().filter(|y| *y == x).count()))
+        }
+        ("plus_list", 3) => {
+            let ty = args[0];
+            let mut elements = list_elements(args[1])?;
+            elements.extend(list_elements(args[2])?);
+            let mut r = app_ref!(nil(), ty);
+            for x in elements.into_iter().rev() {
+                r = app_ref!(cons(), ty, x, r);
+            }
+            r
+        }
+        _ => return None,
+    };
+    Some(r)
+}
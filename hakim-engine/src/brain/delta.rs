@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use super::{fill_axiom, normalize, Term, TermRef};
+use crate::term_ref;
+
+/// How eagerly the unifier replaces a definition with its body. Proof checking
+/// always expands definitions, so this only affects which terms tactics can match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Transparency {
+    /// Expanded before matching, so the name behaves like a notation
+    Transparent,
+    /// Expanded by the unifier only when the terms can not be matched otherwise
+    #[default]
+    OnDemand,
+    /// Never expanded by the unifier, only by the `unfold` tactic
+    Opaque,
+}
+
+/// Bodies of the definitions, with their transparency
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Definitions(im::HashMap<String, (TermRef, Transparency)>);
+
+impl Definitions {
+    pub fn insert(&mut self, name: &str, body: TermRef) {
+        self.0
+            .insert(name.to_string(), (body, Transparency::default()));
+    }
+
+    pub fn body(&self, name: &str) -> Option<&TermRef> {
+        self.0.get(name).map(|x| &x.0)
+    }
+
+    /// Returns `false` if there is no definition with this name.
+    pub fn set_transparency(&mut self, name: &str, transparency: Transparency) -> bool {
+        match self.0.get_mut(name) {
+            Some(x) => {
+                x.1 = transparency;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn unfoldable(&self, name: &str) -> Option<&TermRef> {
+        match self.0.get(name)? {
+            (_, Transparency::Opaque) => None,
+            (body, _) => Some(body),
+        }
+    }
+
+    /// Expands the transparent definitions of the term.
+    pub(crate) fn expand_transparent(&self, term: TermRef) -> TermRef {
+        if self
+            .0
+            .values()
+            .all(|(_, x)| *x != Transparency::Transparent)
+        {
+            return term;
+        }
+        fill_axiom(term, |name, ty, _| match self.0.get(name) {
+            Some((body, Transparency::Transparent)) => self.expand_transparent(body.clone()),
+            _ => term_ref!(axiom name, ty),
+        })
+    }
+
+    /// Unfolds the definition in the head of the term, like `prime` in `prime 5`, and
+    /// normalizes the result. Returns `None` if the head is not an unfoldable definition.
+    pub(crate) fn unfold_head(&self, term: &TermRef) -> Option<TermRef> {
+        fn unfold(term: &TermRef, defs: &Definitions) -> Option<TermRef> {
+            match term.as_ref() {
+                Term::Axiom { unique_name, .. } => defs.unfoldable(unique_name).cloned(),
+                Term::App { func, op } => Some(TermRef::new(Term::App {
+                    func: unfold(func, defs)?,
+                    op: op.clone(),
+                })),
+                _ => None,
+            }
+        }
+        if self.is_empty() {
+            return None;
+        }
+        Some(normalize(unfold(term, self)?))
+    }
+}
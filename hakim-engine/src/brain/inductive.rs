@@ -0,0 +1,343 @@
+use crate::{
+    app_ref,
+    library::prelude::{eq, u},
+    report::{Message, Report, TermPrinter},
+    term_ref,
+};
+
+use super::{fill_axiom, normalize, predict_axiom, subst, Abstraction, Term, TermRef};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InductiveError {
+    /// The type of an inductive should be its parameters followed by `U`
+    BadArity,
+    /// The constructor doesn't return the inductive applied to its parameters
+    BadConclusion(String),
+    /// The inductive occurs in the constructor somewhere other than the conclusion of
+    /// an argument. Nested occurrences like `list T` are rejected as well.
+    NotStrictlyPositive(String),
+}
+
+type Result<T> = std::result::Result<T, InductiveError>;
+
+impl Report for InductiveError {
+    fn code(&self) -> &'static str {
+        match self {
+            InductiveError::BadArity => "E0501",
+            InductiveError::BadConclusion(_) => "E0502",
+            InductiveError::NotStrictlyPositive(_) => "E0503",
+        }
+    }
+
+    fn message(&self, _: TermPrinter) -> Message {
+        match self {
+            InductiveError::BadArity => Message::new("bad_arity", vec![]),
+            InductiveError::BadConclusion(x) => Message::new("bad_conclusion", vec![x.clone()]),
+            InductiveError::NotStrictlyPositive(x) => {
+                Message::new("not_strictly_positive", vec![x.clone()])
+            }
+        }
+    }
+}
+
+/// A fresh axiom which stands for a bound variable while a type is built, and is
+/// turned into a variable by `close`. Names of these axioms are not valid identifiers,
+/// so they can not collide with the names of the engine.
+#[derive(Debug, Clone)]
+struct Local {
+    axiom: TermRef,
+    hint: String,
+}
+
+impl Local {
+    fn new(name: String, ty: TermRef, hint: &str) -> Self {
+        Local {
+            axiom: term_ref!(axiom name, ty),
+            hint: hint.to_string(),
+        }
+    }
+
+    fn ty(&self) -> TermRef {
+        match self.axiom.as_ref() {
+            Term::Axiom { ty, .. } => ty.clone(),
+            _ => unreachable!(),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self.axiom.as_ref() {
+            Term::Axiom { unique_name, .. } => unique_name,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Abstracts the locals out of the term, the first local being the outermost binder.
+fn close(term: TermRef, locals: &[Local], fun: bool) -> TermRef {
+    let mut r = term;
+    for local in locals.iter().rev() {
+        let body = fill_axiom(r, |name, ty, depth| {
+            if name == local.name() {
+                term_ref!(v depth)
+            } else {
+                term_ref!(axiom name, ty)
+            }
+        });
+        let abs = Abstraction {
+            var_ty: local.ty(),
+            hint_name: Some(local.hint.clone()),
+            body,
+        };
+        r = TermRef::new(if fun {
+            Term::Fun(abs)
+        } else {
+            Term::Forall(abs)
+        });
+    }
+    r
+}
+
+fn apply_all(func: TermRef, args: impl IntoIterator<Item = TermRef>) -> TermRef {
+    args.into_iter().fold(func, |f, x| app_ref!(f, x))
+}
+
+fn apply_locals<'a>(func: TermRef, args: impl IntoIterator<Item = &'a Local>) -> TermRef {
+    apply_all(func, args.into_iter().map(|x| x.axiom.clone()))
+}
+
+/// Rebuilds the first `binders` foralls of `ty`, as foralls or lambdas, around `body`.
+/// The body is applied to the variables of the binders, outermost first.
+fn rebuild_binders(ty: &TermRef, binders: usize, fun: bool, body: TermRef) -> TermRef {
+    if binders == 0 {
+        return body;
+    }
+    let Term::Forall(abs) = ty.as_ref() else {
+        unreachable!("binders are counted from the type");
+    };
+    let body = rebuild_binders(&abs.body, binders - 1, fun, body);
+    let abs = Abstraction {
+        var_ty: abs.var_ty.clone(),
+        hint_name: abs.hint_name.clone(),
+        body,
+    };
+    TermRef::new(if fun {
+        Term::Fun(abs)
+    } else {
+        Term::Forall(abs)
+    })
+}
+
+/// Applies `func`, a closed term, to the variables of the innermost `binders` binders.
+fn apply_bound_vars(func: TermRef, binders: usize) -> TermRef {
+    apply_all(func, (0..binders).rev().map(|i| term_ref!(v i)))
+}
+
+#[derive(Debug, Clone)]
+struct Argument {
+    local: Local,
+    /// For recursive arguments, number of binders before the inductive in their type
+    recursive: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Constructor {
+    name: String,
+    axiom: TermRef,
+    args: Vec<Argument>,
+}
+
+/// A checked inductive declaration with uniform parameters, which can generate its
+/// eliminator and computation rules.
+#[derive(Debug, Clone)]
+pub struct Inductive {
+    name: String,
+    params: Vec<Local>,
+    /// The inductive applied to its parameters
+    applied: TermRef,
+    constructors: Vec<Constructor>,
+}
+
+impl Inductive {
+    /// Checks an inductive declaration. `ty` is the axiom of the inductive itself, and
+    /// constructors are given with their full types, including the parameters.
+    pub fn new(ty: TermRef, constructors: Vec<(String, TermRef)>) -> Result<Self> {
+        let (name, mut arity) = match ty.as_ref() {
+            Term::Axiom { unique_name, ty } => (unique_name.clone(), ty.clone()),
+            _ => return Err(InductiveError::BadArity),
+        };
+        let mut params = vec![];
+        while let Term::Forall(abs) = arity.as_ref() {
+            let hint = abs.hint_name.as_deref().unwrap_or("A");
+            let local = Local::new(format!("{name}#{}", params.len()), abs.var_ty.clone(), hint);
+            arity = subst(abs.body.clone(), local.axiom.clone());
+            params.push(local);
+        }
+        if arity != u() {
+            return Err(InductiveError::BadArity);
+        }
+        let applied = apply_locals(ty, &params);
+        let mut r = Inductive {
+            name,
+            params,
+            applied,
+            constructors: vec![],
+        };
+        for (name, ty) in constructors {
+            let c = r.check_constructor(name, normalize(ty))?;
+            r.constructors.push(c);
+        }
+        Ok(r)
+    }
+
+    fn mentions_self(&self, t: &Term) -> bool {
+        predict_axiom(t, |x| x == self.name)
+    }
+
+    fn check_constructor(&self, name: String, full_ty: TermRef) -> Result<Constructor> {
+        let bad_conclusion = || InductiveError::BadConclusion(name.clone());
+        let mut ty = full_ty.clone();
+        for param in &self.params {
+            match ty.as_ref() {
+                Term::Forall(abs) if abs.var_ty == param.ty() => {
+                    ty = subst(abs.body.clone(), param.axiom.clone());
+                }
+                _ => return Err(bad_conclusion()),
+            }
+        }
+        let mut args = vec![];
+        while let Term::Forall(abs) = ty.as_ref() {
+            let recursive = self.recursive_binders(&abs.var_ty);
+            if recursive.is_none() && self.mentions_self(&abs.var_ty) {
+                return Err(InductiveError::NotStrictlyPositive(name));
+            }
+            let hint = abs.hint_name.as_deref().unwrap_or("x");
+            let local = Local::new(format!("{name}#{}", args.len()), abs.var_ty.clone(), hint);
+            ty = subst(abs.body.clone(), local.axiom.clone());
+            args.push(Argument { local, recursive });
+        }
+        if ty != self.applied {
+            return Err(bad_conclusion());
+        }
+        Ok(Constructor {
+            axiom: term_ref!(axiom name.clone(), full_ty),
+            name,
+            args,
+        })
+    }
+
+    /// If the type is `∀ x1 .. xk, T params` and the inductive doesn't occur in the
+    /// binders, returns `k`.
+    fn recursive_binders(&self, ty: &TermRef) -> Option<usize> {
+        match ty.as_ref() {
+            Term::Forall(abs) if !self.mentions_self(&abs.var_ty) => {
+                Some(self.recursive_binders(&abs.body)? + 1)
+            }
+            _ if *ty == self.applied => Some(0),
+            _ => None,
+        }
+    }
+
+    pub fn eliminator_name(&self) -> String {
+        format!("{}_rect", self.name)
+    }
+
+    pub fn induction_name(&self) -> String {
+        format!("{}_ind", self.name)
+    }
+
+    fn motive(&self) -> Local {
+        let ty = term_ref!(forall self.applied, u());
+        Local::new(format!("{}#P", self.name), ty, "P")
+    }
+
+    /// The constructor applied to the parameters and its arguments
+    fn constructed(&self, c: &Constructor) -> TermRef {
+        apply_locals(
+            apply_locals(c.axiom.clone(), &self.params),
+            c.args.iter().map(|x| &x.local),
+        )
+    }
+
+    /// Locals for the arguments of the case of a constructor, with an induction
+    /// hypothesis after each recursive argument.
+    fn case_locals(&self, c: &Constructor, motive: &Local) -> Vec<Local> {
+        let mut r = vec![];
+        for arg in &c.args {
+            r.push(arg.local.clone());
+            if let Some(binders) = arg.recursive {
+                let conclusion = app_ref!(
+                    motive.axiom,
+                    apply_bound_vars(arg.local.axiom.clone(), binders)
+                );
+                let ty = rebuild_binders(&arg.local.ty(), binders, false, conclusion);
+                let name = format!("{}#IH", arg.local.name());
+                r.push(Local::new(name, ty, &format!("{}_IH", arg.local.hint)));
+            }
+        }
+        r
+    }
+
+    fn cases(&self, motive: &Local) -> Vec<Local> {
+        self.constructors
+            .iter()
+            .map(|c| {
+                let conclusion = app_ref!(motive.axiom, self.constructed(c));
+                let ty = close(conclusion, &self.case_locals(c, motive), false);
+                Local::new(format!("{}#case", c.name), ty, &format!("case_{}", c.name))
+            })
+            .collect()
+    }
+
+    /// `∀ params, ∀ P: T params -> U, cases -> ∀ t: T params, P t`
+    pub fn eliminator(&self) -> TermRef {
+        let motive = self.motive();
+        let t = Local::new(format!("{}#t", self.name), self.applied.clone(), "t");
+        let mut locals = self.params.clone();
+        locals.push(motive.clone());
+        locals.extend(self.cases(&motive));
+        locals.push(t.clone());
+        normalize(close(app_ref!(motive.axiom, t.axiom), &locals, false))
+    }
+
+    /// Equations which say the eliminator on a constructor is its case, named
+    /// `T_rect_C` for each constructor `C`.
+    pub fn computation_rules(&self) -> Vec<(String, TermRef)> {
+        let eliminator = term_ref!(axiom self.eliminator_name(), self.eliminator());
+        let motive = self.motive();
+        let cases = self.cases(&motive);
+        let mut prefix = self.params.clone();
+        prefix.push(motive.clone());
+        prefix.extend(cases.iter().cloned());
+        let applied_eliminator = apply_locals(eliminator, &prefix);
+        self.constructors
+            .iter()
+            .zip(&cases)
+            .map(|(c, case)| {
+                let constructed = self.constructed(c);
+                let lhs = app_ref!(applied_eliminator, constructed);
+                let mut rhs = case.axiom.clone();
+                for arg in &c.args {
+                    rhs = app_ref!(rhs, arg.local.axiom);
+                    if let Some(binders) = arg.recursive {
+                        let body = app_ref!(
+                            applied_eliminator,
+                            apply_bound_vars(arg.local.axiom.clone(), binders)
+                        );
+                        let ih = rebuild_binders(&arg.local.ty(), binders, true, body);
+                        rhs = app_ref!(rhs, ih);
+                    }
+                }
+                let ty = app_ref!(motive.axiom, constructed);
+                let statement = app_ref!(eq(), ty, lhs, rhs);
+                let mut locals = prefix.clone();
+                locals.extend(c.args.iter().map(|x| x.local.clone()));
+                let name = format!("{}_{}", self.eliminator_name(), c.name);
+                (name, normalize(close(statement, &locals, false)))
+            })
+            .collect()
+    }
+
+    pub fn params(&self) -> usize {
+        self.params.len()
+    }
+}
@@ -1,12 +1,16 @@
-use super::subtype_and_infer;
 use super::{
-    fill_wild, increase_foreign_vars, normalize, predict_wild, remove_unused_var, subst,
-    ErrorContext::*, ErrorReason::*, Result, Term, TermRef,
+    compute::compute,
+    delta::Definitions,
+    fill_wild, increase_foreign_vars,
+    level::{Level, Levels},
+    normalize, predict_wild, remove_unused_var, subst,
+    ErrorContext::*,
+    ErrorReason::*,
+    Result, Term, TermRef,
 };
 use crate::{app_ref, term_ref, Abstraction};
 
-use std::cmp::max;
-use std::iter::once;
+use std::{collections::HashMap, iter::once};
 
 #[derive(Debug, Clone)]
 pub struct Obligation {
@@ -20,6 +24,14 @@ pub struct InferResults {
     pub terms: Vec<TermRef>,
     pub tys: Vec<TermRef>,
     pub unresolved_obligations: Vec<Obligation>,
+    /// definitions which the unifier can unfold when the terms don't match
+    pub definitions: Definitions,
+    pub levels: Levels,
+    /// whether the builtin functions are evaluated on literals before matching
+    pub computation: bool,
+    /// types of the checked subterms without wilds, by the types of their loose
+    /// variables, since proof terms share many subterms
+    checked: HashMap<(TermRef, Vec<TermRef>), TermRef>,
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
@@ -63,6 +75,29 @@ impl InferResults {
             tys,
             n,
             unresolved_obligations: vec![],
+            definitions: Definitions::default(),
+            levels: Levels::default(),
+            computation: false,
+            checked: HashMap::new(),
+        }
+    }
+
+    pub fn with_definitions(mut self, definitions: Definitions) -> Self {
+        self.definitions = definitions;
+        self
+    }
+
+    pub fn with_computation(mut self) -> Self {
+        self.computation = true;
+        self
+    }
+
+    fn normalize(&self, t: TermRef) -> TermRef {
+        let t = self.definitions.expand_transparent(t);
+        if self.computation {
+            compute(t)
+        } else {
+            normalize(t)
         }
     }
 
@@ -72,8 +107,8 @@ impl InferResults {
 
     pub fn add_var_with_scope(&mut self, scope: usize) -> TermRef {
         let r = VarCategory::Term(self.n).to_term(scope);
-        self.terms.push(r.clone());
-        self.tys.push(VarCategory::Ty(self.n).to_term(scope));
+        self.terms.push(VarCategory::Term(self.n).to_term(0));
+        self.tys.push(VarCategory::Ty(self.n).to_term(0));
         self.n += 1;
         r
     }
@@ -144,7 +179,56 @@ impl InferResults {
         r
     }
     pub fn fill(&self, term: TermRef) -> TermRef {
-        fill_wild(term, &|i, s| self.get_with_scope(i, s))
+        self.levels
+            .fill(fill_wild(term, &|i, s| self.get_with_scope(i, s)))
+    }
+
+    /// Solves the universe levels, which should be done before the filled terms are
+    /// used outside of the inference.
+    pub fn solve_levels(&mut self) -> Result<()> {
+        self.levels.solve()?;
+        self.relax();
+        Ok(())
+    }
+
+    /// Matches the postponed pairs again, since the wilds which they were waiting for
+    /// may be known now.
+    fn retry_obligations(&mut self) -> Result<()> {
+        while !self.unresolved_obligations.is_empty() {
+            let obligations = std::mem::take(&mut self.unresolved_obligations);
+            let count = obligations.len();
+            for Obligation { eq: (t1, t2), .. } in obligations {
+                let t1 = self.normalize(self.fill(t1));
+                let t2 = self.normalize(self.fill(t2));
+                match_and_infer_without_normalize(t1, t2, self)?;
+            }
+            if self.unresolved_obligations.len() >= count {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `ty` as a `∀`, and makes it one if it is an unknown wild.
+    fn expect_forall(&mut self, ty: TermRef) -> Result<Option<Abstraction>> {
+        let ty = normalize(ty);
+        match ty.as_ref() {
+            Term::Forall(abs) => Ok(Some(abs.clone())),
+            Term::Wild { index, scope } if !self.is_unknown(*index) => {
+                self.expect_forall(self.get_with_scope(*index, *scope))
+            }
+            Term::Wild { index, scope } => {
+                let var_ty = self.add_var_with_scope(*scope);
+                let body = self.add_var_with_scope(*scope + 1);
+                self.set(*index, term_ref!(forall var_ty, body))?;
+                Ok(Some(Abstraction {
+                    var_ty,
+                    body,
+                    hint_name: None,
+                }))
+            }
+            _ => Ok(None),
+        }
     }
 
     fn relax(&mut self) {
@@ -159,6 +243,24 @@ fn match_and_infer_without_normalize(
     infers: &mut InferResults,
 ) -> Result<()> {
     fn main(t1: TermRef, t2: TermRef, infers: &mut InferResults) -> Result<()> {
+        let unfolded1 = infers.definitions.unfold_head(&t1);
+        let unfolded2 = infers.definitions.unfold_head(&t2);
+        if unfolded1.is_none() && unfolded2.is_none() {
+            return without_delta(t1, t2, infers);
+        }
+        // we first try to match the terms as they are, so `prime ?0` matches `prime 5`
+        // without unfolding it
+        let snapshot = infers.clone();
+        if without_delta(t1.clone(), t2.clone(), infers).is_ok() {
+            return Ok(());
+        }
+        *infers = snapshot;
+        main(unfolded1.unwrap_or(t1), unfolded2.unwrap_or(t2), infers)
+    }
+    fn without_delta(t1: TermRef, t2: TermRef, infers: &mut InferResults) -> Result<()> {
+        if t1 == t2 {
+            return Ok(());
+        }
         if let Some((i, scope)) = is_wild(&t1) {
             return match_wild(i, scope, t2, infers);
         }
@@ -199,12 +301,8 @@ fn match_and_infer_without_normalize(
                     Err(TypeMismatch(t1, t2).into())
                 }
             }
-            (Term::Universe { index: i1 }, Term::Universe { index: i2 }) => {
-                if i1 == i2 {
-                    Ok(())
-                } else {
-                    Err(TypeMismatch(t1, t2).into())
-                }
+            (Term::Universe { level: l1 }, Term::Universe { level: l2 }) => {
+                infers.levels.unify(*l1, *l2)
             }
             (Term::Number { value: i1 }, Term::Number { value: i2 }) => {
                 if i1 == i2 {
@@ -251,7 +349,18 @@ fn match_and_infer_without_normalize(
     }
     fn match_wild(i: usize, scope: usize, t: TermRef, infers: &mut InferResults) -> Result<()> {
         if infers.is_unknown(i) {
-            infers.set_with_scope(i, scope, t)
+            infers.set_with_scope(i, scope, t)?;
+            // a universe should have the type of the wild, which constrains the levels.
+            // Other values are checked when the filled term is type checked.
+            if let (VarCategory::Term(_), Term::Universe { level }) =
+                (VarCategory::from(i), infers.get(i).as_ref())
+            {
+                let ty = TermRef::new(Term::Universe {
+                    level: level.succ(),
+                });
+                match_and_infer(ty, infers.type_of(i), infers)?;
+            }
+            Ok(())
         } else {
             main(infers.get_with_scope(i, scope), t, infers)
         }
@@ -269,100 +378,127 @@ fn match_and_infer_without_normalize(
         exp: TermRef,
         infers: &mut InferResults,
     ) -> Result<()> {
-        let mut unresolved = || {
+        let (index, scope) = wild;
+        if !infers.is_unknown(index) {
+            let filled = infers.normalize(infers.fill(wild_func));
+            return main(filled, exp, infers);
+        }
+        let postpone = |infers: &mut InferResults| {
             infers.unresolved_obligations.push(Obligation {
-                var: wild.0,
+                var: index,
                 eq: (wild_func.clone(), exp.clone()),
             });
             Ok(())
         };
-        // here we handle matching ?w f1 with an expression containing f1. This is
-        // useful in infering induction. Since ?w can not contain foriegn variables, it
-        // can be determined uniquely.
-        if let Term::App { func, op } = wild_func.as_ref() {
-            let (wild, scope) = if let Term::Wild { index, scope } = func.as_ref() {
-                if !infers.is_unknown(*index) {
-                    return Ok(());
-                } else {
-                    (*index, *scope)
-                }
-            } else {
-                return unresolved();
-            };
-            let var = if let Term::Var { index } = op.as_ref() {
-                *index
-            } else {
-                return Ok(());
-            };
-            if var < scope {
-                // in this case wild can contain the variable, so we can not determine
-                // function uniquely
-                return Ok(());
+        // a flex-flex pair has many solutions, so we wait until one of the sides is known
+        if let Some(other) = func_is_wild(&exp) {
+            if !infers.is_unknown(other.0) {
+                return match_wild_func(other, exp, wild_func, infers);
             }
-            let var_ty = match infers.type_of(wild).as_ref() {
-                Term::Forall(x) => x.var_ty.clone(),
-                Term::Wild { index, scope } => {
-                    let a = infers.add_var_with_scope(*scope);
-                    let b = infers.add_var_with_scope(*scope + 1);
-                    infers.set(*index, term_ref!(forall a, b))?;
-                    a
-                }
-                // I think it should always infer the type of functions.
-                _ => unreachable!("Strange type appears in infer"),
-            };
-            let fbody = if var == 0 {
-                exp
-            } else {
-                replace_var(exp, 0, var)
-            };
-            infers.set(wild, term_ref!(fun var_ty, fbody))?;
-            return Ok(());
+            return postpone(infers);
         }
-        fn replace_var(exp: TermRef, depth: usize, var: usize) -> TermRef {
-            fn for_abs(abs: Abstraction, depth: usize, var: usize) -> Abstraction {
-                let var_ty = replace_var(abs.var_ty, depth, var);
-                let body = replace_var(abs.body, depth + 1, var + 1);
-                Abstraction {
-                    var_ty,
-                    body,
-                    hint_name: abs.hint_name,
+        // Miller patterns: in `?w x1 ... xn` with distinct bound variables, the only
+        // solution is `λ x1 ... xn, exp`.
+        let spine = match pattern_spine(&wild_func) {
+            Some(spine) => spine,
+            None => return postpone(infers),
+        };
+        let mut ty = infers.type_of(index);
+        let mut var_tys = vec![];
+        for _ in 0..spine.len() {
+            match infers.expect_forall(ty)? {
+                Some(abs) => {
+                    var_tys.push(abs.var_ty);
+                    ty = abs.body;
                 }
+                None => return postpone(infers),
             }
-            match exp.as_ref() {
-                Term::Var { index } => {
-                    let i = *index;
-                    TermRef::new(Term::Var {
-                        index: if i == var {
-                            depth
-                        } else if depth <= i && i < var {
-                            i + 1
-                        } else {
-                            i
-                        },
-                    })
-                }
-                Term::Axiom { .. }
-                | Term::Universe { .. }
-                | Term::Number { .. }
-                | Term::Wild { .. } => exp,
-                Term::Forall(a) => TermRef::new(Term::Forall(for_abs(a.clone(), depth, var))),
-                Term::Fun(a) => TermRef::new(Term::Fun(for_abs(a.clone(), depth, var))),
-                Term::App { func, op } => TermRef::new(Term::App {
-                    func: replace_var(func.clone(), depth, var),
-                    op: replace_var(op.clone(), depth, var),
-                }),
+        }
+        let body = match abstract_spine(exp.clone(), &spine, scope, 0) {
+            Some(body) => body,
+            // other wilds in the expression may lose the offending variable later
+            None if exp.has_wild() => return postpone(infers),
+            None => return Err(WildNeedLocalVar(index).into()),
+        };
+        let value = var_tys
+            .into_iter()
+            .rev()
+            .fold(body, |body, var_ty| term_ref!(fun var_ty, body));
+        infers.set(index, value)
+    }
+    /// Returns the variables of `?w x1 ... xn` if they are distinct, outermost first.
+    fn pattern_spine(t: &Term) -> Option<Vec<usize>> {
+        let mut spine = vec![];
+        let mut t = t;
+        while let Term::App { func, op } = t {
+            match op.as_ref() {
+                Term::Var { index } if !spine.contains(index) => spine.push(*index),
+                _ => return None,
             }
+            t = func;
+        }
+        spine.reverse();
+        Some(spine)
+    }
+    /// Turns `exp` into the body of `λ x1 ... xn, exp`, which lives outside of the
+    /// `scope` local variables of the wild. Returns `None` if `exp` needs a local
+    /// variable which is not in the spine.
+    fn abstract_spine(
+        exp: TermRef,
+        spine: &[usize],
+        scope: usize,
+        depth: usize,
+    ) -> Option<TermRef> {
+        fn for_abs(
+            abs: &Abstraction,
+            spine: &[usize],
+            scope: usize,
+            depth: usize,
+        ) -> Option<Abstraction> {
+            Some(Abstraction {
+                var_ty: abstract_spine(abs.var_ty.clone(), spine, scope, depth)?,
+                body: abstract_spine(abs.body.clone(), spine, scope, depth + 1)?,
+                hint_name: abs.hint_name.clone(),
+            })
+        }
+        if exp.loose_bound() <= depth {
+            return Some(exp);
         }
-        unresolved()
+        let n = spine.len();
+        Some(match exp.as_ref() {
+            Term::Var { index } if *index >= depth => {
+                let var = index - depth;
+                let index = match spine.iter().position(|x| *x == var) {
+                    Some(i) => depth + n - 1 - i,
+                    None if var < scope => return None,
+                    None => depth + var - scope + n,
+                };
+                TermRef::new(Term::Var { index })
+            }
+            Term::Axiom { .. }
+            | Term::Universe { .. }
+            | Term::Number { .. }
+            | Term::Var { .. }
+            | Term::Wild { .. } => exp,
+            Term::Forall(a) => TermRef::new(Term::Forall(for_abs(a, spine, scope, depth)?)),
+            Term::Fun(a) => TermRef::new(Term::Fun(for_abs(a, spine, scope, depth)?)),
+            Term::App { func, op } => TermRef::new(Term::App {
+                func: abstract_spine(func.clone(), spine, scope, depth)?,
+                op: abstract_spine(op.clone(), spine, scope, depth)?,
+            }),
+        })
     }
     main(t1.clone(), t2.clone(), infers)
         .map_err(|e| e.with_context(InMatching(t1.clone(), t2.clone())))
 }
 
 pub fn match_and_infer(t1: TermRef, t2: TermRef, infers: &mut InferResults) -> Result<()> {
-    let t1 = normalize(t1);
-    let t2 = normalize(t2);
-    match_and_infer_without_normalize(t1, t2, infers)
+    infers.levels.reserve(&t1);
+    infers.levels.reserve(&t2);
+    let t1 = infers.normalize(t1);
+    let t2 = infers.normalize(t2);
+    match_and_infer_without_normalize(t1, t2, infers)?;
+    infers.retry_obligations()
 }
 
 pub fn type_of_inner(
@@ -375,9 +511,21 @@ pub fn type_of_inner(
         var_ty_stack: &[TermRef],
         infers: &mut InferResults,
     ) -> Result<TermRef> {
+        let key = match term.as_ref() {
+            Term::App { .. } | Term::Fun(_) | Term::Forall(_) if !term.has_wild() => {
+                let vars = &var_ty_stack[var_ty_stack.len().saturating_sub(term.loose_bound())..];
+                Some((term.clone(), vars.to_vec())).filter(|_| !vars.iter().any(|x| x.has_wild()))
+            }
+            _ => None,
+        };
+        if let Some(ty) = key.as_ref().and_then(|key| infers.checked.get(key)) {
+            return Ok(ty.clone());
+        }
         let r = match term.as_ref() {
             Term::Axiom { ty, .. } => ty.clone(),
-            Term::Universe { index } => TermRef::new(Term::Universe { index: index + 1 }),
+            Term::Universe { level } => TermRef::new(Term::Universe {
+                level: level.succ(),
+            }),
             Term::Forall(Abstraction {
                 var_ty,
                 body,
@@ -396,7 +544,9 @@ pub fn type_of_inner(
                     type_of_inner(body.clone(), &new_var_stack, infers)?.as_ref(),
                     infers,
                 )?;
-                term_ref!(universe max(vtt, body_ty))
+                TermRef::new(Term::Universe {
+                    level: infers.levels.max(vtt, body_ty),
+                })
             }
             Term::Fun(Abstraction {
                 var_ty,
@@ -425,41 +575,42 @@ pub fn type_of_inner(
             Term::App { func, op } => {
                 let op_ty = type_of_inner(op.clone(), var_ty_stack, infers)?;
                 let func_type = type_of_inner(func.clone(), var_ty_stack, infers)?;
-                let func_type = normalize(func_type);
-                let (var_ty, body) = match func_type.as_ref() {
-                    Term::Forall(Abstraction {
-                        var_ty,
-                        body,
-                        hint_name: _,
-                    }) => (var_ty, body),
-                    _ => {
-                        return Err(IsNotFunc {
-                            value: func.clone(),
-                            ty: func_type,
+                let Abstraction { var_ty, body, .. } =
+                    match infers.expect_forall(func_type.clone())? {
+                        Some(abs) => abs,
+                        None => {
+                            return Err(IsNotFunc {
+                                value: func.clone(),
+                                ty: normalize(func_type),
+                            }
+                            .into())
                         }
-                        .into())
-                    }
-                };
-                subtype_and_infer(op_ty, var_ty.clone(), infers)?;
-                subst(body.clone(), op.clone())
+                    };
+                match_and_infer(op_ty, var_ty, infers)?;
+                subst(body, op.clone())
             }
             Term::Wild { index, scope } => infers.type_of_with_scope(*index, *scope),
         };
+        if let Some(key) = key {
+            infers.checked.insert(key, r.clone());
+        }
         Ok(r)
     }
     main(term.clone(), var_ty_stack, infers).map_err(|e| e.with_context(InTypechecking(term)))
 }
 
 pub fn type_of_and_infer(term: TermRef, infers: &mut InferResults) -> Result<TermRef> {
+    infers.levels.reserve(&term);
     type_of_inner(term, &[], infers)
 }
 
-fn get_universe_and_infer(term: &Term, infers: &mut InferResults) -> Result<usize> {
+fn get_universe_and_infer(term: &Term, infers: &mut InferResults) -> Result<Level> {
     match term {
-        Term::Universe { index } => Ok(*index),
+        Term::Universe { level } => Ok(*level),
         Term::Wild { index, scope: _ } => {
-            infers.set(*index, term_ref!(universe 0))?;
-            Ok(0)
+            let level = infers.levels.fresh();
+            infers.set(*index, TermRef::new(Term::Universe { level }))?;
+            Ok(level)
         }
         _ => Err(IsNotUniverse.into()),
     }
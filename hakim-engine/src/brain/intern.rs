@@ -0,0 +1,406 @@
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::{BTreeSet, HashMap},
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    rc::{Rc, Weak},
+};
+
+use rustc_hash::{FxBuildHasher, FxHasher};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Abstraction, Term};
+
+/// Names of the axioms of a term, shared between the nodes which have the same set.
+type AxiomSet = Option<Rc<BTreeSet<String>>>;
+
+/// A term in the store. Structurally equal terms, including their hint names,
+/// share one node, so cloning and comparing terms is cheap.
+pub struct TermRef(Rc<Node>);
+
+struct Node {
+    term: Term,
+    /// hash of the term, which ignores the hint names like `Term::hash`
+    hash: u64,
+    /// one more than the largest index of a loose variable, or zero for closed terms
+    loose_bound: usize,
+    has_wild: bool,
+    has_axiom: bool,
+    /// computed on the first use, since most nodes are never asked
+    axioms: OnceCell<AxiomSet>,
+}
+
+/// Keys of the store are already hashes, so a fast non-cryptographic hasher is enough.
+type FxHashMap<V> = HashMap<u64, V, FxBuildHasher>;
+
+/// Nodes which are alive, by their hash. Nodes which share a hash with another one
+/// go to `collisions`, so most of them don't need a vector.
+#[derive(Default)]
+struct Store {
+    nodes: FxHashMap<Weak<Node>>,
+    collisions: FxHashMap<Vec<Weak<Node>>>,
+    /// number of the entries which the next insert sweeps the store at
+    sweep_at: usize,
+}
+
+/// The store is never swept while it is smaller than this
+const MIN_SWEEP: usize = 1 << 12;
+
+impl Store {
+    fn find(&self, hash: u64, term: &Term) -> Option<Rc<Node>> {
+        let node = self.nodes.get(&hash)?.upgrade();
+        if let Some(node) = node.filter(|x| same_node(&x.term, term)) {
+            return Some(node);
+        }
+        self.collisions
+            .get(&hash)?
+            .iter()
+            .filter_map(|x| x.upgrade())
+            .find(|x| same_node(&x.term, term))
+    }
+
+    fn insert(&mut self, node: &Rc<Node>) {
+        let weak = Rc::downgrade(node);
+        match self.nodes.get(&node.hash) {
+            Some(x) if x.strong_count() > 0 => {
+                self.collisions.entry(node.hash).or_default().push(weak)
+            }
+            _ => {
+                self.nodes.insert(node.hash, weak);
+            }
+        }
+        if self.nodes.len() > self.sweep_at {
+            self.sweep();
+            self.sweep_at = MIN_SWEEP.max(2 * self.nodes.len());
+        }
+    }
+
+    /// Removes all of the dropped nodes. Nodes which are dropped while the store is
+    /// busy stay in it until a sweep, and since the store is swept only when it doubles
+    /// in size, sweeping costs constant time for each insert.
+    fn sweep(&mut self) {
+        let dead: Vec<u64> = self
+            .nodes
+            .iter()
+            .filter(|(_, x)| x.strong_count() == 0)
+            .map(|(hash, _)| *hash)
+            .chain(self.collisions.keys().copied())
+            .collect();
+        for hash in dead {
+            self.remove_dead(hash);
+        }
+    }
+
+    /// Removes the dropped nodes with this hash.
+    fn remove_dead(&mut self, hash: u64) {
+        let mut collisions = self.collisions.remove(&hash).unwrap_or_default();
+        collisions.retain(|x| x.strong_count() > 0);
+        if self.nodes.get(&hash).is_some_and(|x| x.strong_count() == 0) {
+            match collisions.pop() {
+                Some(x) => self.nodes.insert(hash, x),
+                None => self.nodes.remove(&hash),
+            };
+        }
+        if !collisions.is_empty() {
+            self.collisions.insert(hash, collisions);
+        }
+    }
+}
+
+thread_local! {
+    static STORE: RefCell<Store> = RefCell::new(Store::default());
+}
+
+/// Whether the terms are the same node, when their children are already in the store.
+fn same_node(a: &Term, b: &Term) -> bool {
+    let abs = |a: &Abstraction, b: &Abstraction| {
+        a.hint_name == b.hint_name
+            && Rc::ptr_eq(&a.var_ty.0, &b.var_ty.0)
+            && Rc::ptr_eq(&a.body.0, &b.body.0)
+    };
+    match (a, b) {
+        (Term::Forall(a), Term::Forall(b)) | (Term::Fun(a), Term::Fun(b)) => abs(a, b),
+        (Term::App { func: f1, op: o1 }, Term::App { func: f2, op: o2 }) => {
+            Rc::ptr_eq(&f1.0, &f2.0) && Rc::ptr_eq(&o1.0, &o2.0)
+        }
+        (
+            Term::Axiom {
+                ty: t1,
+                unique_name: n1,
+            },
+            Term::Axiom {
+                ty: t2,
+                unique_name: n2,
+            },
+        ) => n1 == n2 && Rc::ptr_eq(&t1.0, &t2.0),
+        _ => a == b,
+    }
+}
+
+fn union(a: &AxiomSet, b: &AxiomSet) -> AxiomSet {
+    match (a, b) {
+        (None, x) | (x, None) => x.clone(),
+        (Some(x), Some(y)) if x.is_superset(y) => a.clone(),
+        (Some(x), Some(y)) if y.is_superset(x) => b.clone(),
+        (Some(x), Some(y)) => Some(Rc::new(x.union(y).cloned().collect())),
+    }
+}
+
+impl Node {
+    fn new(term: Term, hash: u64) -> Self {
+        Node {
+            hash,
+            loose_bound: term.loose_bound(),
+            has_wild: term.has_wild(),
+            has_axiom: term.has_axiom(),
+            axioms: OnceCell::new(),
+            term,
+        }
+    }
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        // the store may be gone at the end of the thread, or busy if this node is
+        // dropped while looking up another one, and then the dead entry stays there
+        let _ = STORE.try_with(|store| {
+            if let Ok(mut store) = store.try_borrow_mut() {
+                store.remove_dead(self.hash);
+            }
+        });
+    }
+}
+
+impl TermRef {
+    /// Returns the node of the term in the store, which is created if it doesn't exist.
+    pub fn new(term: Term) -> Self {
+        let hash = {
+            let mut hasher = FxHasher::default();
+            term.hash(&mut hasher);
+            hasher.finish()
+        };
+        let found = STORE.with(|store| store.borrow().find(hash, &term));
+        if let Some(node) = found {
+            return TermRef(node);
+        }
+        let node = Rc::new(Node::new(term, hash));
+        STORE.with(|store| store.borrow_mut().insert(&node));
+        TermRef(node)
+    }
+
+    /// Number of binders which the term needs to be closed. Variables with index
+    /// at least this are not in the term.
+    pub fn loose_bound(&self) -> usize {
+        self.0.loose_bound
+    }
+
+    pub fn has_wild(&self) -> bool {
+        self.0.has_wild
+    }
+
+    pub fn has_axiom(&self) -> bool {
+        self.0.has_axiom
+    }
+
+    fn axiom_set(&self) -> &AxiomSet {
+        self.0.axioms.get_or_init(|| match self.as_ref() {
+            // traversals don't go into the types of axioms
+            Term::Axiom { unique_name, .. } => Some(Rc::new(BTreeSet::from([unique_name.clone()]))),
+            Term::Forall(abs) | Term::Fun(abs) => {
+                union(abs.var_ty.axiom_set(), abs.body.axiom_set())
+            }
+            Term::App { func, op } => union(func.axiom_set(), op.axiom_set()),
+            Term::Universe { .. } | Term::Number { .. } | Term::Var { .. } | Term::Wild { .. } => {
+                None
+            }
+        })
+    }
+
+    /// Names of the axioms in the term, excluding the ones in types of the axioms.
+    pub fn axioms(&self) -> impl Iterator<Item = &str> {
+        self.axiom_set()
+            .iter()
+            .flat_map(|x| x.iter().map(|x| x.as_str()))
+    }
+}
+
+impl Term {
+    /// Like `TermRef::loose_bound`, in constant time.
+    pub fn loose_bound(&self) -> usize {
+        match self {
+            Term::Var { index } => index + 1,
+            Term::Forall(abs) | Term::Fun(abs) => abs
+                .var_ty
+                .loose_bound()
+                .max(abs.body.loose_bound().saturating_sub(1)),
+            Term::App { func, op } => func.loose_bound().max(op.loose_bound()),
+            Term::Axiom { .. }
+            | Term::Universe { .. }
+            | Term::Number { .. }
+            | Term::Wild { .. } => 0,
+        }
+    }
+
+    /// Whether the term has a wild, in constant time.
+    pub fn has_wild(&self) -> bool {
+        match self {
+            Term::Wild { .. } => true,
+            Term::Forall(abs) | Term::Fun(abs) => abs.var_ty.has_wild() || abs.body.has_wild(),
+            Term::App { func, op } => func.has_wild() || op.has_wild(),
+            Term::Axiom { .. } | Term::Universe { .. } | Term::Number { .. } | Term::Var { .. } => {
+                false
+            }
+        }
+    }
+
+    /// Whether the term has an axiom, in constant time.
+    pub fn has_axiom(&self) -> bool {
+        match self {
+            Term::Axiom { .. } => true,
+            Term::Forall(abs) | Term::Fun(abs) => abs.var_ty.has_axiom() || abs.body.has_axiom(),
+            Term::App { func, op } => func.has_axiom() || op.has_axiom(),
+            Term::Universe { .. } | Term::Number { .. } | Term::Var { .. } | Term::Wild { .. } => {
+                false
+            }
+        }
+    }
+
+    /// Whether the predicate holds for some axiom of the term, without walking
+    /// the term.
+    pub fn any_axiom(&self, predict: impl Fn(&str) -> bool) -> bool {
+        match self {
+            Term::Axiom { unique_name, .. } => predict(unique_name),
+            Term::Forall(abs) | Term::Fun(abs) => {
+                abs.var_ty.axioms().any(&predict) || abs.body.axioms().any(&predict)
+            }
+            Term::App { func, op } => func.axioms().any(&predict) || op.axioms().any(&predict),
+            Term::Universe { .. } | Term::Number { .. } | Term::Var { .. } | Term::Wild { .. } => {
+                false
+            }
+        }
+    }
+}
+
+impl Deref for TermRef {
+    type Target = Term;
+
+    fn deref(&self) -> &Term {
+        &self.0.term
+    }
+}
+
+impl AsRef<Term> for TermRef {
+    fn as_ref(&self) -> &Term {
+        &self.0.term
+    }
+}
+
+impl Clone for TermRef {
+    fn clone(&self) -> Self {
+        TermRef(self.0.clone())
+    }
+}
+
+impl PartialEq for TermRef {
+    fn eq(&self, other: &Self) -> bool {
+        // different nodes can be equal only if they differ in hint names
+        Rc::ptr_eq(&self.0, &other.0)
+            || (self.0.hash == other.0.hash && self.0.term == other.0.term)
+    }
+}
+
+impl Eq for TermRef {}
+
+impl Hash for TermRef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0.hash);
+    }
+}
+
+impl PartialOrd for TermRef {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TermRef {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        if Rc::ptr_eq(&self.0, &other.0) {
+            return std::cmp::Ordering::Equal;
+        }
+        self.0.term.cmp(&other.0.term)
+    }
+}
+
+impl Debug for TermRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.term.fmt(f)
+    }
+}
+
+impl Serialize for TermRef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.term.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TermRef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Term::deserialize(deserializer).map(TermRef::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::{Node, Store};
+    use crate::{brain::increase_foreign_vars, engine::Engine, Term};
+
+    #[test]
+    fn structurally_equal_terms_share_node() {
+        let eng = Engine::default();
+        let a = eng.parse_text("∀ x: ℤ, x + 2 = 5").unwrap();
+        let b = eng.parse_text("∀ x: ℤ, x + 2 = 5").unwrap();
+        assert!(Rc::ptr_eq(&a.0, &b.0));
+        // hint names are kept, but the terms are still equal
+        let c = eng.parse_text("∀ y: ℤ, y + 2 = 5").unwrap();
+        assert!(!Rc::ptr_eq(&a.0, &c.0));
+        assert_eq!(a, c);
+        // closed terms are not rebuilt
+        assert!(Rc::ptr_eq(&increase_foreign_vars(a.clone(), 0).0, &a.0));
+    }
+
+    #[test]
+    fn cached_metadata() {
+        let eng = Engine::default();
+        let (t, _) = eng.parse_text_with_wild("λ x: ℤ, x + ?a < 3").unwrap();
+        assert_eq!(t.loose_bound(), 0);
+        assert!(t.has_wild());
+        let mut axioms = t.axioms().collect::<Vec<_>>();
+        axioms.sort();
+        assert_eq!(axioms, ["lt", "plus", "ℤ"]);
+        let Term::Fun(abs) = t.as_ref() else {
+            panic!("expected a lambda");
+        };
+        assert_eq!(abs.body.loose_bound(), 1);
+    }
+
+    #[test]
+    fn sweep_dead_nodes() {
+        let mut store = Store::default();
+        let a = Rc::new(Node::new(Term::Var { index: 0 }, 7));
+        let b = Rc::new(Node::new(Term::Var { index: 1 }, 7));
+        store.insert(&a);
+        store.insert(&b);
+        assert_eq!(store.collisions[&7].len(), 1);
+        drop(a);
+        store.sweep();
+        assert!(store.collisions.is_empty());
+        assert!(Rc::ptr_eq(&store.nodes[&7].upgrade().unwrap(), &b));
+        drop(b);
+        store.sweep();
+        assert!(store.nodes.is_empty());
+    }
+}
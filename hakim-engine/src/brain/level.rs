@@ -0,0 +1,272 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Abstraction, ErrorReason::UniverseInconsistency, Result, Term, TermRef};
+
+/// Level of a universe. Level variables only appear while inferring, and are replaced
+/// by constants when the constraints between them are solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Level {
+    Const(usize),
+    /// A level variable plus an offset
+    Var {
+        var: usize,
+        offset: usize,
+    },
+}
+
+impl Level {
+    pub fn var(var: usize) -> Self {
+        Level::Var { var, offset: 0 }
+    }
+
+    pub fn lift(self, k: usize) -> Self {
+        match self {
+            Level::Const(x) => Level::Const(x + k),
+            Level::Var { var, offset } => Level::Var {
+                var,
+                offset: offset + k,
+            },
+        }
+    }
+
+    pub fn succ(self) -> Self {
+        self.lift(1)
+    }
+
+    pub fn as_const(self) -> Option<usize> {
+        match self {
+            Level::Const(x) => Some(x),
+            Level::Var { .. } => None,
+        }
+    }
+}
+
+/// Replaces the levels of the universes in the term, including in the type of axioms.
+pub fn map_levels(t: TermRef, f: &impl Fn(Level) -> Level) -> TermRef {
+    fn for_abs(abs: &Abstraction, f: &impl Fn(Level) -> Level) -> Option<Abstraction> {
+        let var_ty = rec(&abs.var_ty, f);
+        let body = rec(&abs.body, f);
+        if var_ty.is_none() && body.is_none() {
+            return None;
+        }
+        Some(Abstraction {
+            var_ty: var_ty.unwrap_or_else(|| abs.var_ty.clone()),
+            hint_name: abs.hint_name.clone(),
+            body: body.unwrap_or_else(|| abs.body.clone()),
+        })
+    }
+    // returns `None` if the term doesn't change, so unchanged parts are shared
+    fn rec(t: &TermRef, f: &impl Fn(Level) -> Level) -> Option<TermRef> {
+        let r = match t.as_ref() {
+            Term::Universe { level } => {
+                let new = f(*level);
+                if new == *level {
+                    return None;
+                }
+                Term::Universe { level: new }
+            }
+            Term::Axiom { ty, unique_name } => Term::Axiom {
+                ty: rec(ty, f)?,
+                unique_name: unique_name.clone(),
+            },
+            Term::Forall(abs) => Term::Forall(for_abs(abs, f)?),
+            Term::Fun(abs) => Term::Fun(for_abs(abs, f)?),
+            Term::App { func, op } => {
+                let (new_func, new_op) = (rec(func, f), rec(op, f));
+                if new_func.is_none() && new_op.is_none() {
+                    return None;
+                }
+                Term::App {
+                    func: new_func.unwrap_or_else(|| func.clone()),
+                    op: new_op.unwrap_or_else(|| op.clone()),
+                }
+            }
+            Term::Var { .. } | Term::Number { .. } | Term::Wild { .. } => return None,
+        };
+        Some(TermRef::new(r))
+    }
+    rec(&t, f).unwrap_or(t)
+}
+
+/// Whether a universe appears in the term, excluding the type of axioms. Lifting
+/// the levels of a term without one gives an instance of the same statement.
+pub fn mentions_universe(t: &Term) -> bool {
+    match t {
+        Term::Universe { .. } => true,
+        Term::Forall(abs) | Term::Fun(abs) => {
+            mentions_universe(&abs.var_ty) || mentions_universe(&abs.body)
+        }
+        Term::App { func, op } => mentions_universe(func) || mentions_universe(op),
+        Term::Axiom { .. } | Term::Var { .. } | Term::Number { .. } | Term::Wild { .. } => false,
+    }
+}
+
+/// Calls `f` on the level of every universe in the term, including in the type of axioms.
+pub fn for_each_level(t: &Term, f: &mut impl FnMut(Level)) {
+    match t {
+        Term::Universe { level } => f(*level),
+        Term::Axiom { ty, .. } => for_each_level(ty, f),
+        Term::Forall(abs) | Term::Fun(abs) => {
+            for_each_level(&abs.var_ty, f);
+            for_each_level(&abs.body, f);
+        }
+        Term::App { func, op } => {
+            for_each_level(func, f);
+            for_each_level(op, f);
+        }
+        Term::Var { .. } | Term::Number { .. } | Term::Wild { .. } => (),
+    }
+}
+
+/// Adds `k` to every universe level of the term. Typing is preserved by lifting, so a
+/// lifted axiom is as consistent as the original one, if it is assumed for all levels.
+pub fn lift_levels(t: TermRef, k: usize) -> TermRef {
+    if k == 0 {
+        return t;
+    }
+    map_levels(t, &|l| l.lift(k))
+}
+
+/// Level variables of an inference, the solved ones and the postponed constraints
+/// between them.
+#[derive(Debug, Clone, Default)]
+pub struct Levels {
+    solutions: Vec<Option<Level>>,
+    /// `(l, a, b)` means `l = max(a, b)`, which is checked when `a` and `b` are known
+    maxes: Vec<(Level, Level, Level)>,
+}
+
+impl Levels {
+    pub fn is_empty(&self) -> bool {
+        self.solutions.is_empty()
+    }
+
+    pub fn fresh(&mut self) -> Level {
+        self.solutions.push(None);
+        Level::var(self.solutions.len() - 1)
+    }
+
+    /// Makes sure variables of the term exist, so fresh variables won't collide
+    /// with them.
+    pub fn reserve(&mut self, t: &Term) {
+        for_each_level(t, &mut |l| {
+            if let Level::Var { var, .. } = l {
+                if self.solutions.len() <= var {
+                    self.solutions.resize(var + 1, None);
+                }
+            }
+        });
+    }
+
+    pub fn resolve(&self, l: Level) -> Level {
+        match l {
+            Level::Var { var, offset } => match self.solutions.get(var) {
+                Some(Some(x)) => self.resolve(*x).lift(offset),
+                _ => l,
+            },
+            Level::Const(_) => l,
+        }
+    }
+
+    fn set(&mut self, var: usize, l: Level) {
+        if self.solutions.len() <= var {
+            self.solutions.resize(var + 1, None);
+        }
+        self.solutions[var] = Some(l);
+    }
+
+    pub fn unify(&mut self, a: Level, b: Level) -> Result<()> {
+        use Level::*;
+        let (a, b) = (self.resolve(a), self.resolve(b));
+        match (a, b) {
+            (Const(x), Const(y)) if x == y => Ok(()),
+            (Var { var, offset }, Const(c)) | (Const(c), Var { var, offset }) if c >= offset => {
+                self.set(var, Const(c - offset));
+                Ok(())
+            }
+            (Var { var: v, offset: o }, Var { var: w, offset: p }) if v == w && o == p => Ok(()),
+            (Var { var: v, offset: o }, Var { var: w, offset: p }) if v != w => {
+                if o >= p {
+                    self.set(
+                        w,
+                        Var {
+                            var: v,
+                            offset: o - p,
+                        },
+                    );
+                } else {
+                    self.set(
+                        v,
+                        Var {
+                            var: w,
+                            offset: p - o,
+                        },
+                    );
+                }
+                Ok(())
+            }
+            _ => Err(UniverseInconsistency(a, b).into()),
+        }
+    }
+
+    /// The level of `∀ x: A, B` where `a` and `b` are levels of `A` and `B`
+    pub fn max(&mut self, a: Level, b: Level) -> Level {
+        let (a, b) = (self.resolve(a), self.resolve(b));
+        match (a, b) {
+            (Level::Const(x), Level::Const(y)) => Level::Const(x.max(y)),
+            (x, Level::Const(0)) | (Level::Const(0), x) => x,
+            _ if a == b => a,
+            _ => {
+                let l = self.fresh();
+                self.maxes.push((l, a, b));
+                l
+            }
+        }
+    }
+
+    /// Solves the postponed constraints and checks they are consistent. Variables
+    /// which are not determined by the constraints become zero, which is the
+    /// smallest solution.
+    pub fn solve(&mut self) -> Result<()> {
+        loop {
+            let maxes = std::mem::take(&mut self.maxes);
+            let count = maxes.len();
+            for (l, a, b) in maxes {
+                let (l, a, b) = (self.resolve(l), self.resolve(a), self.resolve(b));
+                match (l.as_const(), a.as_const(), b.as_const()) {
+                    (_, Some(x), Some(y)) => self.unify(l, Level::Const(x.max(y)))?,
+                    _ if a == b => self.unify(l, a)?,
+                    // the known operand is smaller, so the other one is the maximum
+                    (Some(m), Some(x), None) if x < m => self.unify(l, b)?,
+                    (Some(m), None, Some(y)) if y < m => self.unify(l, a)?,
+                    _ => self.maxes.push((l, a, b)),
+                }
+            }
+            if self.maxes.is_empty() {
+                break;
+            }
+            if self.maxes.len() == count {
+                // nothing is solved, so we set the first unknown operand to zero
+                let (_, a, b) = self.maxes[0];
+                let Level::Var { var, .. } = (if a.as_const().is_none() { a } else { b }) else {
+                    unreachable!("a constant max is already solved");
+                };
+                self.set(var, Level::Const(0));
+            }
+        }
+        for i in 0..self.solutions.len() {
+            if self.solutions[i].is_none() {
+                self.solutions[i] = Some(Level::Const(0));
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the solved level variables of the term.
+    pub fn fill(&self, t: TermRef) -> TermRef {
+        if self.is_empty() {
+            return t;
+        }
+        map_levels(t, &|l| self.resolve(l))
+    }
+}
@@ -1,114 +1,200 @@
 use super::{
+    increase_foreign_vars,
     infer::{match_and_infer, InferResults},
-    Result, TermRef,
+    normalize, remove_unused_var, Abstraction, Error, ErrorReason, Result, Term, TermRef,
 };
-
-//use super::{contains_wild, remove_unused_var, Abstraction, Term};
-
-/// This function currently works equal to match and infer, but it is useful as a comment
-/// to say we just need sub to be a subtype of super, not exactly equal to it, and we may
-/// want to restore subtyping one day
-pub fn subtype_and_infer(sub: TermRef, spr: TermRef, infers: &mut InferResults) -> Result<()> {
-    match_and_infer(sub, spr, infers)
-}
-
-/*
+use crate::{app_ref, term_ref};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum SubTypeAware {
     False,
-    Imply(TermRef, TermRef),
+    Forall(Abstraction),
     Or(TermRef, TermRef),
-    Unknown(TermRef),
+    Unknown,
 }
 
 impl SubTypeAware {
-    fn detect(term: TermRef) -> Self {
+    fn detect(term: &TermRef) -> Self {
         match term.as_ref() {
             Term::Axiom { unique_name, .. } => match unique_name.as_str() {
                 "False" => SubTypeAware::False,
-                _ => SubTypeAware::Unknown(term),
+                _ => SubTypeAware::Unknown,
             },
             Term::Wild { .. }
             | Term::Universe { .. }
             | Term::Fun(_)
             | Term::Var { .. }
-            | Term::Number { .. } => SubTypeAware::Unknown(term),
+            | Term::Number { .. } => SubTypeAware::Unknown,
             Term::App { func, op: op2 } => match func.as_ref() {
                 Term::App { func, op } => match func.as_ref() {
-                    Term::Axiom { unique_name, .. } => match unique_name.as_str() {
-                        "or" => SubTypeAware::Or(op2.clone(), op.clone()),
-                        _ => SubTypeAware::Unknown(term),
-                    },
-                    _ => SubTypeAware::Unknown(term),
+                    Term::Axiom { unique_name, .. } if unique_name == "or" => {
+                        SubTypeAware::Or(op.clone(), op2.clone())
+                    }
+                    _ => SubTypeAware::Unknown,
                 },
-                _ => SubTypeAware::Unknown(term),
+                _ => SubTypeAware::Unknown,
             },
-            Term::Forall(Abstraction { body, var_ty, .. }) => {
-                if let Some(x) = remove_unused_var(body.clone(), 0) {
-                    SubTypeAware::Imply(var_ty.clone(), x)
-                } else {
-                    SubTypeAware::Unknown(term)
-                }
-            }
+            Term::Forall(abs) => SubTypeAware::Forall(abs.clone()),
         }
     }
 }
 
-pub fn subtype_and_infer(sub: TermRef, spr: TermRef, infers: &mut InferResults) -> Result<()> {
-    if contains_wild(&sub) || contains_wild(&spr) {
-        // FIXME: we currently give up on subtype inference with wilds
-        return match_and_infer(sub, spr, infers);
-    }
-    let subc = SubTypeAware::detect(sub.clone());
-    let sprc = SubTypeAware::detect(spr.clone());
-    if subc == SubTypeAware::False {
-        return Ok(());
+/// A function from the proofs of `sub` to the proofs of `spr`, or `None` if a proof
+/// of `sub` is already a proof of `spr`.
+pub type Coercion = Option<TermRef>;
+
+/// Turns a proof of `sub` into a proof of `spr` with the coercion between them.
+pub fn coerce(coercion: Coercion, proof: TermRef) -> TermRef {
+    match coercion {
+        Some(c) => app_ref!(c, proof),
+        None => proof,
     }
-    if let (SubTypeAware::Imply(subl, subr), SubTypeAware::Imply(sprl, sprr)) =
-        (subc.clone(), sprc.clone())
-    {
-        // function input is contra-variant, sub and super are reserverd
-        subtype_and_infer(sprl, subl, infers)?;
-        // function output is co-variant (normal)
-        subtype_and_infer(subr, sprr, infers)?;
-        return Ok(());
+}
+
+/// Infers wilds so that a proof of `sub` can be turned into a proof of `spr`, and
+/// returns the coercion which does it. It is the same as `match_and_infer`, except that
+/// `False` is a subtype of everything, `A` is a subtype of `A ∨ B`, and implications are
+/// contravariant in their premise and covariant in their conclusion. The kernel only
+/// accepts exact matches, so the coercion is built from the library lemmas `False_ind`,
+/// `or_introl`, `or_intror` and `or_ind`, which `lemma` finds by their names.
+pub fn subtype_and_infer(
+    sub: TermRef,
+    spr: TermRef,
+    infers: &mut InferResults,
+    lemma: &impl Fn(&str) -> Option<TermRef>,
+) -> Result<Coercion> {
+    let aware = |t: &TermRef| SubTypeAware::detect(t) != SubTypeAware::Unknown;
+    if !aware(&sub) && !aware(&spr) {
+        match_and_infer(sub, spr, infers)?;
+        return Ok(None);
     }
-    if let SubTypeAware::Or(a, b) = subc {
-        subtype_and_infer(a, spr.clone(), infers)?;
-        return subtype_and_infer(b, spr, infers);
+    // we prefer the exact match, so the inference is the same as before when it works
+    let snapshot = infers.clone();
+    let error = match match_and_infer(sub.clone(), spr.clone(), infers) {
+        Ok(()) => return Ok(None),
+        Err(e) => e,
+    };
+    *infers = snapshot;
+    subtype_by_rules(normalize(sub), normalize(spr), infers, lemma).map_err(|_| error)
+}
+
+fn lift(t: TermRef) -> TermRef {
+    increase_foreign_vars(t, 0)
+}
+
+/// `λ x: var_ty, body`
+fn fun(var_ty: TermRef, body: TermRef) -> TermRef {
+    term_ref!(fun var_ty, body)
+}
+
+/// `f ∘ c` as a function on the proofs of `sub`
+fn compose(f: TermRef, c: Coercion, sub: TermRef) -> TermRef {
+    match c {
+        None => f,
+        Some(c) => fun(sub, app_ref!(lift(f), app_ref!(lift(c), term_ref!(v 0)))),
     }
-    if let SubTypeAware::Or(a, b) = sprc {
-        let mut my_infers = infers.clone();
-        if subtype_and_infer(sub.clone(), a, &mut my_infers).is_ok() {
-            *infers = my_infers;
-            return Ok(());
+}
+
+fn subtype_by_rules(
+    sub: TermRef,
+    spr: TermRef,
+    infers: &mut InferResults,
+    lemma: &impl Fn(&str) -> Option<TermRef>,
+) -> Result<Coercion> {
+    let get = |name: &str| {
+        lemma(name).ok_or_else(|| Error::from(ErrorReason::TypeMismatch(sub.clone(), spr.clone())))
+    };
+    match (SubTypeAware::detect(&sub), SubTypeAware::detect(&spr)) {
+        (SubTypeAware::False, _) => {
+            let proof = app_ref!(get("False_ind")?, term_ref!(v 0), lift(spr));
+            Ok(Some(fun(sub, proof)))
+        }
+        // a disjunction with an unknown branch would match every `spr` by filling the
+        // branches with it, which is never what the user wants
+        (SubTypeAware::Or(a, b), _) if !is_wild(&a) && !is_wild(&b) => {
+            let ca = subtype_and_infer(a.clone(), spr.clone(), infers, lemma)?;
+            let cb = subtype_and_infer(b.clone(), spr.clone(), infers, lemma)?;
+            let case =
+                |ty: &TermRef, c: Coercion| lift(c.unwrap_or(fun(ty.clone(), term_ref!(v 0))));
+            let or_ind = app_ref!(
+                get("or_ind")?,
+                lift(a.clone()),
+                lift(b.clone()),
+                term_ref!(v 0)
+            );
+            let proof = app_ref!(or_ind, lift(spr), case(&a, ca), case(&b, cb));
+            Ok(Some(fun(sub, proof)))
         }
-        // previous infers is now polluted
-        my_infers = infers.clone();
-        if subtype_and_infer(sub.clone(), b, &mut my_infers).is_ok() {
-            *infers = my_infers;
-            return Ok(());
+        (_, SubTypeAware::Or(a, b)) => {
+            let snapshot = infers.clone();
+            if let Ok(c) = subtype_and_infer(sub.clone(), a.clone(), infers, lemma) {
+                return Ok(Some(compose(app_ref!(get("or_introl")?, a, b), c, sub)));
+            }
+            *infers = snapshot;
+            let c = subtype_and_infer(sub.clone(), b.clone(), infers, lemma)?;
+            Ok(Some(compose(app_ref!(get("or_intror")?, a, b), c, sub)))
+        }
+        (SubTypeAware::Forall(a1), SubTypeAware::Forall(a2)) => {
+            let sub_body = remove_unused_var(a1.body.clone(), 0);
+            let spr_body = remove_unused_var(a2.body.clone(), 0);
+            // `λ f: sub, λ x: spr_premise, ...`, where `f` is the var 1 and `x` is the var 0
+            let lift2 = |c: Coercion| c.map(|x| lift(lift(x)));
+            let wrap = |body: TermRef| fun(sub.clone(), fun(lift(a2.var_ty.clone()), body));
+            if let (Some(sub_body), Some(spr_body)) = (sub_body, spr_body) {
+                // an implication, which its premise is contravariant, so sub and
+                // super are reversed
+                let premise = subtype_and_infer(a2.var_ty.clone(), a1.var_ty, infers, lemma)?;
+                let conclusion = subtype_and_infer(sub_body, spr_body, infers, lemma)?;
+                if premise.is_none() && conclusion.is_none() {
+                    return Ok(None);
+                }
+                let arg = coerce(lift2(premise), term_ref!(v 0));
+                let proof = coerce(lift2(conclusion), app_ref!(term_ref!(v 1), arg));
+                return Ok(Some(wrap(proof)));
+            }
+            // the body depends on the variable, so its type should be exactly the same
+            match_and_infer(a1.var_ty, a2.var_ty.clone(), infers)?;
+            let body = subtype_and_infer(a1.body, a2.body, infers, lemma)?;
+            // the coercion of the body is under the binder of `x`, so only the vars
+            // outside of it are shifted for `f`
+            Ok(body.map(|c| {
+                let c = increase_foreign_vars(c, 1);
+                wrap(app_ref!(c, app_ref!(term_ref!(v 1), term_ref!(v 0))))
+            }))
+        }
+        _ => {
+            match_and_infer(sub, spr, infers)?;
+            Ok(None)
         }
     }
-    match_and_infer(sub, spr, infers)
+}
+
+fn is_wild(term: &TermRef) -> bool {
+    matches!(term.as_ref(), Term::Wild { .. })
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::engine::Engine;
+    use crate::{
+        engine::tests::{build_engine, EngineLevel},
+        interactive::Session,
+    };
 
+    /// Whether `apply` turns a proof of `sub` into a proof of `spr`, which the kernel
+    /// accepts without subtyping.
     fn is_subtype(sub: &str, spr: &str) -> bool {
-        let query = format!("∀ A B C D E F: Universe, ∀ a: ({sub}), (λ x: ({spr}), False) a");
-        let eng = Engine::default();
-        let r = eng.parse_text(&query);
-        r.is_ok()
+        let goal = format!("∀ A B C D E F: U, ({sub}) -> ({spr})");
+        let mut session = Session::new(build_engine(EngineLevel::Full), &goal).unwrap();
+        session.run_tactic("intros A B C D E F a").unwrap();
+        session.run_tactic("apply a").is_ok()
+            && session.is_finished()
+            && session.check_proof().is_ok()
     }
 
     #[test]
     fn false_simple() {
-        //assert!(is_subtype("False", "2 = 3"));
-        //assert!(is_subtype("False", "2 = 2"));
+        assert!(is_subtype("False", "2 = 3"));
+        assert!(is_subtype("False", "2 = 2"));
         assert!(is_subtype("False", "A"));
         assert!(is_subtype("False", "A ∨ B"));
         assert!(!is_subtype("A", "False"));
@@ -137,6 +223,12 @@ mod tests {
         assert!(!is_subtype("A -> B", "~ A"));
         assert!(!is_subtype("A -> B", "B"));
     }
-}
 
-*/
+    #[test]
+    fn dependent_forall() {
+        assert!(is_subtype("∀ x: ℤ, x < 2", "∀ x: ℤ, x < 2 ∨ x = 2"));
+        assert!(is_subtype("∀ x: ℤ, False", "∀ x: ℤ, x = 2"));
+        assert!(!is_subtype("∀ x: ℤ, x < 2 ∨ x = 2", "∀ x: ℤ, x < 2"));
+        assert!(!is_subtype("∀ x: ℤ, x < 2", "∀ x: ℤ, ∀ y: ℤ, x < y"));
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     engine::Engine,
 };
 
-use super::{infer::type_of_and_infer, type_of};
+use super::{infer::type_of_and_infer, normalize, type_of, Result};
 
 fn wild_need_local(exp: &str) {
     let eng = Engine::default();
@@ -43,6 +43,20 @@ fn fail_type(exp: &str) {
     }
 }
 
+fn infer_pattern(pattern: &str, exp: &str) -> Result<InferResults> {
+    let eng = Engine::default();
+    let (pattern, cnt) = eng.parse_text_with_wild(pattern).unwrap();
+    let exp = eng.parse_text(exp).unwrap();
+    let mut infers = InferResults::new(cnt);
+    type_of_and_infer(pattern.clone(), &mut infers)?;
+    match_and_infer(pattern.clone(), exp.clone(), &mut infers)?;
+    if infers.unresolved_obligations.is_empty() {
+        infers.solve_levels()?;
+        assert_eq!(normalize(infers.fill(pattern)), exp);
+    }
+    Ok(infers)
+}
+
 fn fail_match_infer(a: &str, b: &str) {
     let eng = Engine::default();
     let (a, c1) = eng.parse_text_with_wild(a).unwrap();
@@ -155,3 +169,54 @@ fn tuples() {
     check_type("((1, 2), 3)", "(ℤ ∧ ℤ) ∧ ℤ");
     check_type("([1, 2], 3)", "list ℤ ∧ ℤ");
 }
+
+#[test]
+fn universe_levels() {
+    check_type("set U", "Universe1");
+    check_type("set (set ℤ)", "U");
+    check_type("λ x: set U, x", "set U → set U");
+    fail_type("(λ A: U, A) (∀ A: U, A)");
+    fail_type("λ f: U → U, f U");
+    fail_type("λ f: U → U, f (set U)");
+}
+
+#[test]
+fn level_constraints() {
+    use super::level::{Level, Levels};
+    let mut levels = Levels::default();
+    let (a, b) = (levels.fresh(), levels.fresh());
+    let c = levels.max(a, b.succ());
+    levels.unify(c, Level::Const(3)).unwrap();
+    levels.unify(a, Level::Const(1)).unwrap();
+    levels.solve().unwrap();
+    assert_eq!(levels.resolve(b), Level::Const(2));
+    assert!(levels.unify(a.succ(), a).is_err());
+    assert!(levels.unify(b, Level::Const(0)).is_err());
+}
+
+#[test]
+fn higher_order_patterns() {
+    infer_pattern("∀ x: ℤ, ?f x", "∀ x: ℤ, 0 < x + 1").unwrap();
+    infer_pattern("∀ x y: ℤ, ?f y x = 2", "∀ x y: ℤ, x * y + 1 = 2").unwrap();
+    infer_pattern("∀ A: U, ∀ a: A, ?f A a", "∀ A: U, ∀ a: A, eq A a a ∧ 0 < 1").unwrap();
+    // the wild is solved by the second conjunct, and the first one is checked later
+    infer_pattern("?f 0 ∧ ∀ x: ℤ, ?f x", "0 < 1 ∧ ∀ x: ℤ, x < 1").unwrap();
+    assert!(infer_pattern("?f 0 ∧ ∀ x: ℤ, ?f x", "2 < 1 ∧ ∀ x: ℤ, x < 1").is_err());
+    // `?f` lives outside of `x` and `y`, so it can not depend on `y`
+    match infer_pattern(
+        "?f 0 = 0 ∧ (∀ x y: ℤ, ?f x = 0)",
+        "0 = 0 ∧ (∀ x y: ℤ, y = 0)",
+    ) {
+        Err(e) if matches!(e.reason, super::ErrorReason::WildNeedLocalVar(_)) => (),
+        r => panic!(
+            "Expected WildNeedLocalVar error but got {:?}",
+            r.map(|x| x.terms)
+        ),
+    }
+}
+
+#[test]
+fn postponed_patterns() {
+    let infers = infer_pattern("∀ x: ℤ, ?f (x + 1)", "∀ x: ℤ, 0 < x + 1").unwrap();
+    assert_eq!(infers.unresolved_obligations.len(), 1);
+}
@@ -1,16 +1,28 @@
-use std::collections::HashMap;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    path::PathBuf,
+    rc::Rc,
+};
 
-use super::interactive::Session;
+use super::interactive::{tactic, Session};
 use crate::{
     brain::{
         self,
-        infer::{type_of_and_infer, InferResults},
+        delta::{Definitions, Transparency},
+        fill_axiom,
+        inductive::InductiveError,
+        infer::{match_and_infer, type_of_and_infer, InferResults},
+        level::{for_each_level, lift_levels, map_levels, mentions_universe, Level},
         normalize, predict_axiom, type_of, Term, TermRef,
     },
-    interactive::SuggRule,
-    library::{all_names, load_library_by_name, prelude},
+    interactive::{is_builtin_tactic, SuggRule, TacticMacro},
+    library::{
+        check_library_by_text, load_library_by_name, prelude, FsResolver, LibraryResolver,
+        Resolvers, SyntaxError,
+    },
     parser::{
-        self, ast_to_term, fix_wild_scope, is_valid_ident, parse, pos_of_span,
+        self, ast_to_term, fix_wild_scope, insert_coercions, is_valid_ident, parse, pos_of_span,
         term_pretty_print_to_html, term_pretty_print_to_string, term_to_ast, BinOp, ParserConfig,
         PrettyPrintConfig,
     },
@@ -18,15 +30,77 @@ use crate::{
     term_ref,
 };
 
+mod audit;
+pub use self::audit::Assumptions;
+
+mod report;
+use crate::report::{Message, Report, TermPrinter};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Engine {
     name_dict: im::HashMap<String, TermRef>,
-    definitions: im::HashMap<String, TermRef>,
+    definitions: Definitions,
     hidden_args: im::HashMap<String, usize>,
+    /// axioms which can be used at any universe level, by lifting the levels of their type
+    universe_polymorphic: im::HashSet<String>,
     libs: im::HashMap<String, ()>,
+    name_kinds: im::HashMap<String, NameKind>,
+    /// axioms mentioned in the proof term of each checked theorem
+    proof_deps: im::HashMap<String, Vec<String>>,
+    /// replay proofs of theorems when loading libraries
+    verify_proofs: bool,
+    /// sources of the library texts, searched in order
+    #[serde(skip)]
+    resolvers: Resolvers,
     pub params: im::HashMap<String, String>,
     pub hyp_suggs: im::Vector<SuggRule>,
     pub goal_suggs: im::Vector<SuggRule>,
+    /// tactics which are declared in libraries
+    tactic_macros: im::HashMap<String, TacticMacro>,
+}
+
+/// What a name loaded from a library is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NameKind {
+    Axiom,
+    Todo,
+    Definition,
+    /// A theorem which its proof is not checked, since it is loaded without verification
+    Theorem,
+    /// A theorem which its proof is replayed and its proof term is checked
+    CheckedTheorem,
+    /// An inductive type, or its constructors, eliminator, induction lemma and computation
+    /// rules, which are generated by the kernel from a checked declaration
+    Inductive,
+}
+
+#[derive(Debug)]
+pub enum LibraryProofFailure {
+    /// The monitor is the state of the proof just before the failed tactic
+    TacticFailed {
+        error: tactic::Error,
+        monitor: String,
+    },
+    Incomplete {
+        monitor: String,
+    },
+    InvalidProofTerm(Error),
+}
+
+#[derive(Debug)]
+pub struct LibraryProofError {
+    pub library: String,
+    pub theorem: String,
+    /// The failed tactic, or the last tactic of an incomplete proof
+    pub tactic: Option<String>,
+    pub failure: LibraryProofFailure,
+}
+
+/// Result of replaying the proof of a single theorem
+#[derive(Debug)]
+pub struct TheoremCheck {
+    pub theorem: String,
+    pub error: Option<LibraryProofError>,
 }
 
 #[derive(Debug)]
@@ -35,11 +109,23 @@ pub enum Error {
     InvalidIdentName(String),
     UnknownLibrary(String),
     UnknownDefinition(String),
-    InvalidSentence(String),
+    UnknownName(String),
+    /// A library file which is not well formed
+    LibrarySyntax(String, SyntaxError),
     ParserError(parser::Error),
     BrainError(brain::Error),
     InvalidTypeForAxiom(String),
     GoalWithWildCard(TermRef),
+    IncompleteProof,
+    UnknownAxiomInProof(String),
+    LibraryProof(Box<LibraryProofError>),
+    Inductive(InductiveError),
+    /// A library tactic which its script can not be parsed
+    InvalidTacticMacro(String, Box<tactic::Error>),
+    /// A library tactic which is used by itself or by a tactic declared before it
+    RecursiveTacticMacro(String),
+    /// An axiom made universe polymorphic, while its statement has no universe
+    NotUniverseGeneric(String),
 }
 
 impl From<parser::Error> for Error {
@@ -48,6 +134,12 @@ impl From<parser::Error> for Error {
     }
 }
 
+impl From<InductiveError> for Error {
+    fn from(e: InductiveError) -> Self {
+        Inductive(e)
+    }
+}
+
 impl From<brain::Error> for Error {
     fn from(e: brain::Error) -> Self {
         BrainError(e)
@@ -57,6 +149,90 @@ impl From<brain::Error> for Error {
 use serde::{Deserialize, Serialize};
 use Error::*;
 
+impl Report for Error {
+    fn code(&self) -> &'static str {
+        match self {
+            DuplicateName(_) => "E0301",
+            InvalidIdentName(_) => "E0302",
+            UnknownLibrary(_) => "E0303",
+            UnknownDefinition(_) => "E0304",
+            UnknownName(_) => "E0305",
+            LibrarySyntax(..) => "E0306",
+            InvalidTypeForAxiom(_) => "E0307",
+            GoalWithWildCard(_) => "E0308",
+            IncompleteProof => "E0309",
+            UnknownAxiomInProof(_) => "E0310",
+            LibraryProof(_) => "E0311",
+            InvalidTacticMacro(..) => "E0312",
+            RecursiveTacticMacro(_) => "E0313",
+            NotUniverseGeneric(_) => "E0314",
+            ParserError(e) => e.code(),
+            BrainError(e) => e.code(),
+            Inductive(e) => e.code(),
+        }
+    }
+
+    fn message(&self, print: TermPrinter) -> Message {
+        let msg = |key, x: &String| Message::new(key, vec![x.clone()]);
+        match self {
+            DuplicateName(x) => msg("duplicate_name", x),
+            InvalidIdentName(x) => msg("invalid_ident_name", x),
+            UnknownLibrary(x) => msg("unknown_library", x),
+            UnknownDefinition(x) => msg("unknown_definition", x),
+            UnknownName(x) => msg("unknown_name", x),
+            LibrarySyntax(lib, e) => {
+                Message::new("library_syntax", vec![lib.clone(), e.to_string()])
+            }
+            InvalidTypeForAxiom(x) => msg("invalid_type_for_axiom", x),
+            GoalWithWildCard(t) => Message::new("goal_with_wildcard", vec![print(t)]),
+            IncompleteProof => Message::new("incomplete_proof", vec![]),
+            UnknownAxiomInProof(x) => msg("unknown_axiom_in_proof", x),
+            LibraryProof(e) => {
+                Message::new("library_proof", vec![e.library.clone(), e.theorem.clone()])
+            }
+            InvalidTacticMacro(x, _) => msg("invalid_tactic_macro", x),
+            RecursiveTacticMacro(x) => msg("recursive_tactic_macro", x),
+            NotUniverseGeneric(x) => msg("not_universe_generic", x),
+            ParserError(e) => e.message(print),
+            BrainError(e) => e.message(print),
+            Inductive(e) => e.message(print),
+        }
+    }
+
+    fn span(&self) -> Option<(usize, usize)> {
+        match self {
+            ParserError(e) => e.span(),
+            _ => None,
+        }
+    }
+
+    fn context(&self, print: TermPrinter) -> Vec<Message> {
+        let with_message = |e: &dyn Report| {
+            let mut r = e.context(print);
+            r.insert(0, e.message(print));
+            r
+        };
+        match self {
+            BrainError(e) => e.context(print),
+            LibraryProof(e) => {
+                let mut r = match &e.failure {
+                    LibraryProofFailure::TacticFailed { error, .. } => with_message(error),
+                    LibraryProofFailure::Incomplete { .. } => {
+                        vec![Message::new("incomplete_proof", vec![])]
+                    }
+                    LibraryProofFailure::InvalidProofTerm(error) => with_message(error),
+                };
+                if let Some(tactic) = &e.tactic {
+                    r.push(Message::new("in_tactic", vec![tactic.clone()]));
+                }
+                r
+            }
+            InvalidTacticMacro(_, e) => with_message(e.as_ref()),
+            _ => vec![],
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 impl Default for Engine {
@@ -68,12 +244,12 @@ impl Default for Engine {
 impl Engine {
     pub fn new(params: &str) -> Self {
         let name_dict = prelude::init_dict();
-        let definitions = im::HashMap::default();
-        let hidden_args = im::HashMap::<String, usize>::from(HashMap::from([
-            ("finite".to_string(), 1),
-            ("cnt".to_string(), 1),
-        ]));
+        let definitions = Definitions::default();
+        let hidden_args = prelude::init_hidden_args();
+        let universe_polymorphic = prelude::init_universe_polymorphic();
         let libs = im::HashMap::<String, ()>::default();
+        let name_kinds = im::HashMap::default();
+        let proof_deps = im::HashMap::default();
         let hyp_suggs = im::Vector::default();
         let goal_suggs = im::Vector::default();
         let params = params
@@ -81,15 +257,24 @@ impl Engine {
             .filter_map(|x| x.trim().split_once('='))
             .map(|(x, y)| (x.to_owned(), y.to_owned()))
             .collect();
-        Self {
+        let mut eng = Self {
             name_dict,
             definitions,
             hidden_args,
+            universe_polymorphic,
             libs,
+            name_kinds,
+            proof_deps,
+            verify_proofs: false,
+            resolvers: Resolvers::default(),
             params,
             hyp_suggs,
             goal_suggs,
-        }
+            tactic_macros: im::HashMap::default(),
+        };
+        prelude::init_naturals(&mut eng).expect("the natural numbers of the prelude are valid");
+        prelude::init_arith(&mut eng).expect("the arithmetic axioms of the prelude are valid");
+        eng
     }
 
     pub fn generate_name(&self, base: &str) -> String {
@@ -130,27 +315,86 @@ impl Engine {
     pub fn add_axiom_with_term(&mut self, name: &str, term: TermRef) -> Result<()> {
         let term = normalize(term);
         let ty = type_of(term.clone())?;
-        if !matches!(ty.as_ref(), Term::Universe { index: _ }) {
+        if !matches!(ty.as_ref(), Term::Universe { .. }) {
             return Err(InvalidTypeForAxiom(name.to_string()));
         }
         let axiom = term_ref!(axiom name, term);
         self.add_name(name, axiom)
     }
 
-    pub(crate) fn add_definition(&mut self, name: &str, body: &str) -> Result<()> {
+    pub(crate) fn add_definition(
+        &mut self,
+        name: &str,
+        body: &str,
+        hidden_args: usize,
+    ) -> Result<()> {
         let body = self.parse_text(body)?;
-        self.definitions.insert(name.to_string(), body.clone());
+        self.definitions.insert(name, body.clone());
         let ty = type_of(body)?;
-        self.add_axiom_with_term(name, ty)
+        self.add_axiom_with_term(name, ty)?;
+        self.hidden_args.insert(name.to_string(), hidden_args);
+        Ok(())
+    }
+
+    /// Adds an inductive type with its constructors, eliminator `name_rect`, its
+    /// computation rules `name_rect_C` and the induction lemma `name_ind`. `params` is
+    /// a prefix like `∀ A: U, ` which is shared by the type and the constructors, and
+    /// becomes hidden arguments of the generated names.
+    pub(crate) fn add_inductive(
+        &mut self,
+        name: &str,
+        params: &str,
+        constructors: &[(String, String)],
+    ) -> Result<()> {
+        let mut eng = self.clone();
+        eng.add_axiom_with_term(name, eng.parse_text(&format!("{params}U"))?)?;
+        let ty = eng.name_dict[name].clone();
+        let constructors = constructors
+            .iter()
+            .map(|(c, ty)| Ok((c.clone(), eng.parse_text(&format!("{params}{ty}"))?)))
+            .collect::<Result<Vec<_>>>()?;
+        let inductive = brain::inductive::Inductive::new(ty, constructors.clone())?;
+        let hidden = inductive.params();
+        let eliminator = inductive.eliminator();
+        // the induction lemma is the eliminator itself, under the name which proofs use
+        let generated = constructors
+            .into_iter()
+            .chain([
+                (inductive.eliminator_name(), eliminator.clone()),
+                (inductive.induction_name(), eliminator),
+            ])
+            .chain(inductive.computation_rules());
+        eng.set_name_kind(name, NameKind::Inductive);
+        for (x, ty) in generated {
+            eng.add_axiom_with_term(&x, ty)?;
+            eng.hidden_args.insert(x.clone(), hidden);
+            eng.set_name_kind(&x, NameKind::Inductive);
+        }
+        *self = eng;
+        Ok(())
     }
 
     pub(crate) fn body_of_definition(&self, def: &str) -> Result<TermRef> {
-        match self.definitions.get(def) {
+        match self.definitions.body(def) {
             Some(x) => Ok(x.clone()),
             None => Err(UnknownDefinition(def.to_string())),
         }
     }
 
+    /// Sets how eagerly tactics unfold the definition while matching terms.
+    pub fn set_transparency(&mut self, def: &str, transparency: Transparency) -> Result<()> {
+        if self.definitions.set_transparency(def, transparency) {
+            Ok(())
+        } else {
+            Err(UnknownDefinition(def.to_string()))
+        }
+    }
+
+    /// Inference results which can unfold the definitions of the engine
+    pub(crate) fn infer_results(&self, n: usize) -> InferResults {
+        InferResults::new(n).with_definitions(self.definitions.clone())
+    }
+
     pub fn add_axiom(&mut self, name: &str, ty: &str, hidden_args: usize) -> Result<()> {
         let parsed = self.parse_text(ty)?;
         self.add_axiom_with_term(name, parsed)?;
@@ -158,10 +402,109 @@ impl Engine {
         Ok(())
     }
 
+    /// Makes the axiom usable at any universe level. Its type is assumed for every
+    /// lifting of its levels, so it should not depend on being at the lowest level.
+    /// The statement should mention a universe, otherwise it is the same at every level.
+    pub fn make_universe_polymorphic(&mut self, name: &str) -> Result<()> {
+        match self.name_dict.get(name).map(|x| x.as_ref()) {
+            Some(Term::Axiom { ty, .. }) if self.definitions.body(name).is_none() => {
+                if !mentions_universe(ty) {
+                    return Err(NotUniverseGeneric(name.to_string()));
+                }
+                self.universe_polymorphic.insert(name.to_string());
+                Ok(())
+            }
+            _ => Err(UnknownName(name.to_string())),
+        }
+    }
+
+    /// Gives each occurrence of a universe polymorphic axiom its own level variable,
+    /// which is solved by the inference.
+    fn instantiate_universes(&self, term: TermRef) -> TermRef {
+        if self.universe_polymorphic.is_empty() {
+            return term;
+        }
+        let next_var = Cell::new(0);
+        fill_axiom(term, |name, ty, _| {
+            let axiom = term_ref!(axiom name, ty);
+            if !self.universe_polymorphic.contains(name) {
+                return axiom;
+            }
+            let var = next_var.get();
+            next_var.set(var + 1);
+            map_levels(axiom, &|l| match l {
+                Level::Const(offset) => Level::Var { var, offset },
+                l => l,
+            })
+        })
+    }
+
+    /// Whether the axiom is registered in the engine, or is a lifting of a registered
+    /// universe polymorphic axiom.
+    fn is_registered_axiom(&self, axiom: &TermRef) -> bool {
+        let Term::Axiom { unique_name, ty } = axiom.as_ref() else {
+            return false;
+        };
+        let Some(registered) = self.name_dict.get(unique_name) else {
+            return false;
+        };
+        if registered == axiom {
+            return true;
+        }
+        if !self.universe_polymorphic.contains(unique_name) {
+            return false;
+        }
+        let first_level = |t: &Term| {
+            let mut r = None;
+            for_each_level(t, &mut |l| {
+                r.get_or_insert(l);
+            });
+            r.and_then(|x| x.as_const())
+        };
+        match (first_level(ty), first_level(registered)) {
+            (Some(a), Some(b)) if a >= b => lift_levels(registered.clone(), a - b) == *axiom,
+            _ => false,
+        }
+    }
+
+    /// Replaces definitions with their bodies, including in the type of axioms.
+    pub(crate) fn expand_definitions(&self, term: TermRef) -> TermRef {
+        if self.definitions.is_empty() {
+            return term;
+        }
+        fill_axiom(term, |name, ty, _| match self.definitions.body(name) {
+            Some(body) => self.expand_definitions(body.clone()),
+            None => term_ref!(axiom name, self.expand_definitions(ty)),
+        })
+    }
+
+    /// Checks that `proof` is a closed term of type `goal`. Every axiom in the proof
+    /// should be registered in the engine, except the trusted decision procedures
+    /// which their name starts with `$`.
+    pub fn check_proof(&self, proof: TermRef, goal: TermRef) -> Result<()> {
+        let unknown = RefCell::new(None);
+        fill_axiom(proof.clone(), |name, ty, _| {
+            let axiom = term_ref!(axiom name, ty);
+            if !name.starts_with('$') && !self.is_registered_axiom(&axiom) {
+                *unknown.borrow_mut() = Some(name.to_string());
+            }
+            axiom
+        });
+        if let Some(name) = unknown.into_inner() {
+            return Err(UnknownAxiomInProof(name));
+        }
+        let ty = type_of(self.expand_definitions(proof))?;
+        let mut infers = InferResults::new(0).with_computation();
+        match_and_infer(ty, self.expand_definitions(goal), &mut infers)?;
+        Ok(())
+    }
+
     pub fn calc_type_and_infer(&self, text: &str) -> Result<TermRef> {
         let (term, ig) = self.parse_text_with_wild(text).unwrap();
-        let ty = type_of_and_infer(term, &mut InferResults::new(ig)).unwrap();
-        Ok(ty)
+        let mut infers = InferResults::new(ig);
+        let ty = type_of_and_infer(term, &mut infers).unwrap();
+        infers.solve_levels()?;
+        Ok(infers.fill(ty))
     }
 
     pub fn calc_type(&self, text: &str) -> Result<TermRef> {
@@ -170,15 +513,76 @@ impl Engine {
         Ok(normalize(ty))
     }
 
+    /// Loads all libraries which their name starts with `name`, or the library with
+    /// exactly this name, if the resolvers can not list it.
     pub fn load_library(&mut self, name: &str) -> Result<()> {
-        for lib in all_names() {
+        let mut found = false;
+        for lib in self.resolvers.names() {
             if lib.starts_with(name) {
-                self.load_library_single(lib)?;
+                found = true;
+                self.load_library_single(&lib)?;
             }
         }
+        if !found {
+            self.load_library_single(name)?;
+        }
         Ok(())
     }
 
+    /// Adds a source of libraries. Resolvers are searched in the order they are added,
+    /// after the bundled libraries.
+    pub fn add_library_resolver(&mut self, resolver: impl LibraryResolver + 'static) {
+        self.resolvers.push(Rc::new(resolver));
+    }
+
+    /// Adds a directory to search for libraries. Library `/a/b` is the file `a/b.v`
+    /// in the directory.
+    pub fn add_library_path(&mut self, path: impl Into<PathBuf>) {
+        self.add_library_resolver(FsResolver::new(path));
+    }
+
+    pub(crate) fn library_text(&self, name: &str) -> Option<String> {
+        self.resolvers.load(name)
+    }
+
+    /// Loads a library from its text and replays the proof of each of its theorems.
+    /// Unlike `load_library_verified`, a failed proof doesn't stop loading, and the
+    /// result of every theorem is reported. Failed theorems are loaded as unchecked.
+    pub fn check_library_text(&mut self, name: &str, text: &str) -> Result<Vec<TheoremCheck>> {
+        let r = check_library_by_text(self, name, text)?;
+        self.libs.insert(name.to_string(), ());
+        Ok(r)
+    }
+
+    /// Like `load_library`, but replays proofs of all theorems in the loaded libraries
+    /// and checks their proof terms. Libraries which are already loaded are not checked.
+    pub fn load_library_verified(&mut self, name: &str) -> Result<()> {
+        let verify_proofs = std::mem::replace(&mut self.verify_proofs, true);
+        let r = self.load_library(name);
+        self.verify_proofs = verify_proofs;
+        r
+    }
+
+    pub(crate) fn is_verifying_proofs(&self) -> bool {
+        self.verify_proofs
+    }
+
+    pub(crate) fn set_name_kind(&mut self, name: &str, kind: NameKind) {
+        self.name_kinds.insert(name.to_string(), kind);
+    }
+
+    /// What the name is, if it is loaded from a library
+    pub fn kind_of_name(&self, name: &str) -> Option<NameKind> {
+        self.name_kinds.get(name).copied()
+    }
+
+    pub fn names_of_kind(&self, kind: NameKind) -> impl Iterator<Item = &str> {
+        self.name_kinds
+            .iter()
+            .filter(move |(_, k)| **k == kind)
+            .map(|(name, _)| name.as_str())
+    }
+
     pub fn load_library_single(&mut self, name: &str) -> Result<()> {
         if self.has_library(name) {
             return Ok(());
@@ -212,8 +616,9 @@ impl Engine {
                 names_with_hidden_args: self.hidden_args.clone(),
             },
         )?;
+        let term = insert_coercions(term)?;
         let n = infer_cnt.0;
-        let term = fix_wild_scope(term, n);
+        let term = self.instantiate_universes(fix_wild_scope(term, n));
         // check if all axioms in the generated term are registered in the engine
         predict_axiom(&term, |x| {
             if !self.name_dict.contains_key(x) {
@@ -226,9 +631,10 @@ impl Engine {
 
     pub fn parse_text(&self, text: &str) -> Result<TermRef> {
         let (term, infer_cnt) = self.parse_text_with_wild(text)?;
-        let mut infers = InferResults::new(infer_cnt);
+        let mut infers = self.infer_results(infer_cnt);
         let ty = type_of_and_infer(term.clone(), &mut infers)?;
         type_of_and_infer(ty, &mut infers)?;
+        infers.solve_levels()?;
         let term = infers.fill(term);
         Ok(term)
     }
@@ -277,6 +683,12 @@ impl Engine {
         pos_of_span(&ast, span, &c)
     }
 
+    /// The term of the name, like the axioms of the prelude which tactics build their
+    /// proofs from
+    pub(crate) fn name_term(&self, name: &str) -> Option<TermRef> {
+        self.name_dict.get(name).cloned()
+    }
+
     pub(crate) fn type_of_name(&self, name: &str) -> Result<TermRef> {
         let x = self.parse_text(name)?;
         Ok(type_of(x)?)
@@ -290,6 +702,26 @@ impl Engine {
         self.goal_suggs.push_back(sugg);
     }
 
+    /// Adds a tactic which is declared in a library. Tactics can only use the tactics
+    /// declared before them, so they can not run each other in a loop.
+    pub(crate) fn add_tactic_macro(&mut self, name: &str, tactic: TacticMacro) -> Result<()> {
+        if !is_valid_ident(name) {
+            return Err(InvalidIdentName(name.to_string()));
+        }
+        if self.tactic_macros.contains_key(name) || is_builtin_tactic(name) {
+            return Err(DuplicateName(name.to_string()));
+        }
+        if tactic.mentions(name) || self.tactic_macros.values().any(|x| x.mentions(name)) {
+            return Err(RecursiveTacticMacro(name.to_string()));
+        }
+        self.tactic_macros.insert(name.to_string(), tactic);
+        Ok(())
+    }
+
+    pub(crate) fn tactic_macro(&self, name: &str) -> Option<&TacticMacro> {
+        self.tactic_macros.get(name)
+    }
+
     pub(crate) fn is_disabled_tactic(&self, name: &str) -> bool {
         self.params
             .get("disabled_tactics")
@@ -384,4 +816,20 @@ pub mod tests {
             "∀ A: Universe, ∀ P: A → Universe, ∀ e: ∃ x: A, P x, P (ex_value A P e)"
         );
     }
+
+    #[test]
+    fn kernel_has_no_subtyping() {
+        let eng = build_engine(EngineLevel::Full);
+        let check = |proof: &str, goal: &str| {
+            let proof = eng.parse_text(proof).unwrap();
+            eng.check_proof(proof, eng.parse_text(goal).unwrap())
+        };
+        assert!(check("λ A B: U, λ a: A, a", "∀ A B: U, A -> A").is_ok());
+        assert!(check("λ A B: U, λ a: A, a", "∀ A B: U, A -> A ∨ B").is_err());
+        assert!(check("λ A: U, λ f: False, f", "∀ A: U, False -> A").is_err());
+        assert!(check("ℤ", "ℤ ∨ ℤ").is_err());
+        assert!(eng
+            .parse_text("(λ x: 2 = 3 ∨ 2 = 2, x) (eq_refl ℤ 2)")
+            .is_err());
+    }
 }
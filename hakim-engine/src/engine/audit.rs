@@ -0,0 +1,170 @@
+use std::collections::{BTreeSet, HashSet};
+
+use serde::Serialize;
+
+use crate::brain::{Abstraction, Term};
+
+use super::{Engine, Error::*, NameKind, Result};
+
+/// Everything that a theorem rests on, without a checked proof
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Assumptions {
+    pub axioms: BTreeSet<String>,
+    pub todos: BTreeSet<String>,
+    /// decision procedures like `lia` which are trusted without a proof term
+    pub tactics: BTreeSet<String>,
+    /// theorems which are loaded without verification, so their proof is trusted
+    pub unchecked_theorems: BTreeSet<String>,
+    /// library names which are assumed at every universe level
+    pub universe_polymorphic: BTreeSet<String>,
+}
+
+/// Adds name of axioms in the term to `r`. Decision procedures are axioms with
+/// a name starting with `$`, and the axioms in their type are also added.
+fn mentioned_axioms(term: &Term, r: &mut HashSet<String>) {
+    match term {
+        Term::Axiom { ty, unique_name } => {
+            if unique_name.starts_with('$') {
+                mentioned_axioms(ty, r);
+            }
+            r.insert(unique_name.clone());
+        }
+        Term::Forall(Abstraction { var_ty, body, .. })
+        | Term::Fun(Abstraction { var_ty, body, .. }) => {
+            mentioned_axioms(var_ty, r);
+            mentioned_axioms(body, r);
+        }
+        Term::App { func, op } => {
+            mentioned_axioms(func, r);
+            mentioned_axioms(op, r);
+        }
+        Term::Universe { .. } | Term::Var { .. } | Term::Number { .. } | Term::Wild { .. } => (),
+    }
+}
+
+impl Engine {
+    pub(crate) fn add_proof_dependencies(&mut self, name: &str, proof: &Term) {
+        let mut deps = HashSet::new();
+        mentioned_axioms(proof, &mut deps);
+        let mut deps = deps.into_iter().collect::<Vec<_>>();
+        deps.sort();
+        self.proof_deps.insert(name.to_string(), deps);
+    }
+
+    /// Names that the statement or body of the name directly depends on
+    fn direct_dependencies(&self, name: &str) -> HashSet<String> {
+        let mut r = HashSet::new();
+        if let Some(Term::Axiom { ty, .. }) = self.name_dict.get(name).map(|x| x.as_ref()) {
+            mentioned_axioms(ty, &mut r);
+        }
+        if let Some(body) = self.definitions.body(name) {
+            mentioned_axioms(body, &mut r);
+        }
+        if let Some(deps) = self.proof_deps.get(name) {
+            r.extend(deps.iter().cloned());
+        }
+        r.remove(name);
+        r
+    }
+
+    /// Lists axioms, todos and trusted tactics that the name transitively depends on.
+    /// Theorems should be loaded with `load_library_verified` for their proofs to be
+    /// considered, otherwise they are reported as unchecked.
+    pub fn assumptions_of(&self, name: &str) -> Result<Assumptions> {
+        if !self.name_dict.contains_key(name) {
+            return Err(UnknownName(name.to_string()));
+        }
+        let mut r = Assumptions::default();
+        let mut visited = HashSet::new();
+        let mut stack = vec![name.to_string()];
+        while let Some(name) = stack.pop() {
+            if !visited.insert(name.clone()) {
+                continue;
+            }
+            if let Some(tactic) = name.strip_prefix('$') {
+                r.tactics.insert(tactic.to_string());
+                continue;
+            }
+            let kind = self.kind_of_name(&name);
+            if kind.is_some() && self.universe_polymorphic.contains(&name) {
+                r.universe_polymorphic.insert(name.clone());
+            }
+            match kind {
+                Some(NameKind::Axiom) => {
+                    r.axioms.insert(name.clone());
+                }
+                Some(NameKind::Todo) => {
+                    r.todos.insert(name.clone());
+                }
+                Some(NameKind::Theorem) => {
+                    r.unchecked_theorems.insert(name.clone());
+                }
+                Some(NameKind::CheckedTheorem)
+                | Some(NameKind::Definition)
+                | Some(NameKind::Inductive)
+                | None => (),
+            }
+            stack.extend(self.direct_dependencies(&name));
+        }
+        Ok(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{Engine, NameKind};
+
+    fn set(items: &[&str]) -> std::collections::BTreeSet<String> {
+        items.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn todo_and_tactics() {
+        let mut eng = Engine::default();
+        eng.load_library_verified("/Arith").unwrap();
+        let r = eng.assumptions_of("lt_multiply_negative").unwrap();
+        assert!(r.todos.is_empty());
+        assert_eq!(r.tactics, set(&["assumption", "nia"]));
+        assert!(r.axioms.is_empty());
+        assert!(r.unchecked_theorems.is_empty());
+        let r = eng.assumptions_of("pow_lt_r").unwrap();
+        assert_eq!(r.todos, set(&["pow_lt_r"]));
+    }
+
+    #[test]
+    fn transitive() {
+        let mut eng = Engine::default();
+        eng.load_library_verified("/Arith").unwrap();
+        let r = eng.assumptions_of("pow_lt_l").unwrap();
+        assert!(r.axioms.contains("z_recdep_simple"));
+        assert!(r.axioms.contains("pow_unfold_l"));
+        assert!(r.tactics.contains("nia"));
+        let r = eng.assumptions_of("z_recdep_simple").unwrap();
+        assert_eq!(r.axioms, set(&["z_recdep_simple"]));
+    }
+
+    #[test]
+    fn unchecked() {
+        let mut eng = Engine::default();
+        eng.load_library("/Arith").unwrap();
+        assert_eq!(eng.kind_of_name("lt_trans"), Some(NameKind::Theorem));
+        let r = eng.assumptions_of("lt_trans").unwrap();
+        assert_eq!(r.unchecked_theorems, set(&["lt_trans"]));
+        assert!(eng.assumptions_of("not_a_name").is_err());
+    }
+
+    #[test]
+    fn universe_polymorphic() {
+        let mut eng = Engine::default();
+        let text = "Axiom box: U -> U. UniversePolymorphic box.
+            Theorem box_id: ∀ A: U, box A -> box A.
+            Proof. intros A H. apply H. Qed.";
+        eng.check_library_text("/Test", text).unwrap();
+        let r = eng.assumptions_of("box_id").unwrap();
+        assert_eq!(r.universe_polymorphic, set(&["box"]));
+        assert_eq!(r.axioms, set(&["box"]));
+        // builtins like `eq` are not reported
+        let r = eng.assumptions_of("eq").unwrap();
+        assert!(r.universe_polymorphic.is_empty());
+    }
+}
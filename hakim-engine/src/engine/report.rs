@@ -0,0 +1,78 @@
+use crate::report::{ErrorReport, Report};
+
+use super::Engine;
+
+impl Engine {
+    /// Reports the error, with terms printed in the notations of the engine
+    pub fn report(&self, e: &(impl Report + ?Sized)) -> ErrorReport {
+        ErrorReport::new(e, &|t| self.pretty_print(t))
+    }
+}
+
+/// Prints the error as its report, with the default notations for terms
+macro_rules! display_by_report {
+    ($($ty:ty),*) => {
+        $(
+            impl std::fmt::Display for $ty {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    let report = $crate::report::ErrorReport::new(self, &|t| format!("{t:?}"));
+                    write!(f, "{report}")
+                }
+            }
+        )*
+    };
+}
+
+display_by_report!(
+    super::Error,
+    crate::parser::Error,
+    crate::brain::Error,
+    crate::brain::inductive::InductiveError,
+    crate::interactive::tactic::Error
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::Engine;
+
+    #[test]
+    fn parse_error() {
+        let eng = Engine::default();
+        let e = eng.parse_text("2 + (3 * ").unwrap_err();
+        let report = eng.report(&e);
+        assert_eq!(report.code, "E0101");
+        let e = eng.parse_text("2 + 3 )").unwrap_err();
+        let report = eng.report(&e);
+        assert_eq!(report.code, "E0108");
+        assert_eq!(report.span, Some((6, 7)));
+        assert_eq!(
+            report.text,
+            "unexpected `)` after the end of the expression"
+        );
+    }
+
+    #[test]
+    fn type_error() {
+        let eng = Engine::default();
+        let e = eng.parse_text("2 + (λ x: ℤ, x)").unwrap_err();
+        let report = eng.report(&e);
+        assert_eq!(report.code, "E0202");
+        assert_eq!(report.message.key, "type_mismatch");
+        assert!(report.context.iter().any(|x| x.key == "in_typechecking"));
+        assert!(report.to_string().starts_with("error[E0202]: `"));
+    }
+
+    #[test]
+    fn tactic_error() {
+        let eng = Engine::default();
+        let mut session = eng.interactive_session("∀ x: ℤ, x = x").unwrap();
+        let e = session.run_tactic("intros x y").unwrap_err();
+        assert_eq!(e.to_string(), "error[E0405]: intros expects forall");
+        let e = session.run_tactic("foo").unwrap_err();
+        assert_eq!(eng.report(&e).text, "unknown tactic `foo`");
+        let e = session.run_tactic("apply (x +)").unwrap_err();
+        let report = eng.report(&e);
+        assert_eq!(report.code, "E0104");
+        assert_eq!(report.span, Some((4, 5)));
+    }
+}
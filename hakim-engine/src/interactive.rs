@@ -12,30 +12,44 @@ use crate::parser::is_whity_char;
 mod tests;
 
 mod action_of_tactic;
+mod focus;
+mod history;
 mod history_auto;
 mod monitor;
 mod natural;
+mod proof_term;
 mod proof_tree;
+mod script;
 pub mod suggest;
 pub mod tactic;
 
-use tactic::{add_hyp, apply, destruct, intros, lia, replace, rewrite};
+use tactic::{
+    add_hyp, apply, destruct, intros, lia, lra, nia, replace, rewrite, ring, ring_simplify,
+};
 
 use self::action_of_tactic::GraphicalAction;
+use self::focus::{split_focus, split_selector, Focus};
+use self::history::HistoryNode;
 use self::history_auto::history_lookup_auto;
 use self::monitor::Monitor;
 use self::natural::NaturalProof;
+pub(crate) use self::proof_term::ProofTerm;
+use self::proof_tree::ProofTree;
+pub(crate) use self::script::TacticMacro;
+use self::script::{Script, COMBINATORS};
 use self::suggest::{
     suggest_on_goal, suggest_on_goal_dblclk, suggest_on_hyp, suggest_on_hyp_dblclk,
 };
 
 pub use self::action_of_tactic::action_of_tactic;
+pub use self::history::{Branch, BranchDiff};
 pub use self::suggest::{SuggClass, SuggRule, Suggestion};
 use self::tactic::{
-    add_from_lib, assumption, auto_list, auto_set, chain, remove_hyp, revert, unfold,
+    add_from_lib, assumption, auto_list, auto_set, chain, compute, remove_hyp, revert, simpl,
+    unfold,
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Hyp {
     pub ty: TermRef,
     name: String,
@@ -52,19 +66,33 @@ pub struct Frame {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Snapshot {
     pub frames: im::Vector<Frame>,
+    /// proof of the root goal, with the hole `i` for `frames[i]`
+    proof: ProofTerm,
+    /// the open bullets and braces, the innermost last
+    #[serde(default)]
+    focus: im::Vector<Focus>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct HistoryRecord {
     tactic: String,
     snapshot: Snapshot,
+    /// for a tactic with a selector, the number of goals which each selected goal is
+    /// replaced with, in the order of the goals. For a `Seq`, the number of goals
+    /// which each of its tactics replaces the focused goal with.
+    #[serde(default)]
+    goal_counts: Vec<usize>,
+    /// for a `Seq`, the goal which each of its tactics is run on
+    #[serde(default)]
+    seq_frames: Vec<Frame>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Session {
-    history: im::Vector<HistoryRecord>,
-    /// frames that will used for redo
-    undone: im::Vector<HistoryRecord>,
+    /// every state of the proof, as the tree of the `history` module
+    nodes: im::Vector<HistoryNode>,
+    /// the node of the current state
+    current: usize,
 }
 
 fn smart_split(text: &str) -> Vec<String> {
@@ -97,17 +125,30 @@ fn smart_split(text: &str) -> Vec<String> {
     r
 }
 
+/// The tactics which a `Seq` line runs one after another
+fn flatten_seq(line: &str) -> Vec<String> {
+    let Some(x) = line.trim().strip_prefix("Seq ") else {
+        return vec![line.trim().to_string()];
+    };
+    smart_split(x)
+        .iter()
+        .flat_map(|tac| {
+            let inner = tac.strip_prefix('(').and_then(|x| x.strip_suffix(')'));
+            flatten_seq(inner.unwrap_or(tac))
+        })
+        .collect()
+}
+
 impl Session {
     pub fn new(engine: Engine, goal: &str) -> Result<Self, Error> {
         let snapshot = Snapshot::new(engine, goal)?;
         let hr = HistoryRecord {
             snapshot,
             tactic: "Proof".to_string(),
+            goal_counts: vec![],
+            seq_frames: vec![],
         };
-        Ok(Session {
-            history: vector![hr],
-            undone: vector![],
-        })
+        Ok(Session::from_root(hr))
     }
 
     pub fn from_middle_of_lib(lib: &str, name: &str, review: bool) -> Option<Self> {
@@ -124,11 +165,11 @@ impl Session {
     }
 
     pub fn initial_engine(&self) -> Engine {
-        self.history[0].snapshot.frames[0].engine.clone()
+        self.root().snapshot.frames[0].engine.clone()
     }
 
     pub fn last_snapshot(&self) -> &Snapshot {
-        &self.history.last().unwrap().snapshot
+        &self.current_record().snapshot
     }
 
     pub fn run_tactic(&mut self, line: &str) -> Result<(), tactic::Error> {
@@ -142,11 +183,8 @@ impl Session {
         if line.trim() == "Redo" {
             return self.redo();
         }
-        let snapshot = self.last_snapshot().run_tactic(line)?;
-        self.add_history_record(HistoryRecord {
-            tactic: line.to_string(),
-            snapshot,
-        });
+        let record = self.last_snapshot().run_tactic_to_record(line)?;
+        self.add_history_record(record);
         Ok(())
     }
 
@@ -178,6 +216,21 @@ impl Session {
         r
     }
 
+    /// The finished proof as a theorem of a library, with bullets for the goals of
+    /// branching tactics. Suggestions which run a `Seq` of tactics are written as
+    /// the separate tactics.
+    pub fn to_script(&self, name: &str) -> Result<String, tactic::Error> {
+        if !self.is_finished() {
+            return Err(tactic::Error::UnfinishedProof);
+        }
+        let root = &self.root().snapshot.frames[0];
+        let goal = root.engine.pretty_print(&root.goal);
+        let mut r = format!("Theorem {name}: {goal}.\nProof.\n");
+        ProofTree::from(self.clone()).write_script(0, "    ".to_string(), "    ", 0, &mut r);
+        r.push_str("Qed.\n");
+        Ok(r)
+    }
+
     pub fn print(&self) {
         println!("{}", self.monitor_string());
     }
@@ -186,31 +239,17 @@ impl Session {
         self.last_snapshot().is_finished()
     }
 
-    pub fn undo(&mut self) -> Result<(), tactic::Error> {
-        if self.history.len() <= 1 {
-            return Err(tactic::Error::CanNotUndo);
-        }
-        let record = self.history.pop_back().unwrap();
-        self.undone.push_front(record);
-        Ok(())
-    }
-
-    pub fn redo(&mut self) -> Result<(), tactic::Error> {
-        let x = self.undone.pop_front().ok_or(tactic::Error::CanNotRedo)?;
-        self.history.push_back(x);
-        Ok(())
-    }
-
-    fn add_history_record(&mut self, r: HistoryRecord) {
-        self.history.push_back(r);
-        self.undone = vector![]; // clear redo tasks
+    /// The proof term of the goal, if the proof is finished.
+    pub fn proof_term(&self) -> Option<TermRef> {
+        self.last_snapshot().proof.clone().into_term()
     }
 
-    pub fn get_history(&self) -> (Vec<String>, Vec<String>) {
-        (
-            self.history.iter().map(|x| x.tactic.clone()).collect(),
-            self.undone.iter().map(|x| x.tactic.clone()).collect(),
-        )
+    /// Checks the proof term of a finished proof against the goal, independent
+    /// of the tactics which built it.
+    pub fn check_proof(&self) -> Result<(), Error> {
+        let proof = self.proof_term().ok_or(Error::IncompleteProof)?;
+        let initial = &self.root().snapshot.frames[0];
+        initial.engine.check_proof(proof, initial.goal.clone())
     }
 
     pub fn suggest_on_goal_dblclk(&self) -> Option<Suggestion> {
@@ -263,6 +302,8 @@ impl From<Frame> for Snapshot {
     fn from(frame: Frame) -> Self {
         Self {
             frames: vector![frame],
+            proof: ProofTerm::Hole(0),
+            focus: vector![],
         }
     }
 }
@@ -289,6 +330,62 @@ impl Snapshot {
     }
 
     pub fn run_tactic(&self, line: &str) -> Result<Self, tactic::Error> {
+        Ok(self.run_tactic_to_record(line)?.snapshot)
+    }
+
+    /// Runs the tactic, and records how it changes the goals, which is needed to
+    /// build the proof tree from the history.
+    pub(crate) fn run_tactic_to_record(&self, line: &str) -> Result<HistoryRecord, tactic::Error> {
+        let record = |snapshot, goal_counts, seq_frames| HistoryRecord {
+            tactic: line.to_string(),
+            snapshot,
+            goal_counts,
+            seq_frames,
+        };
+        let (commands, rest) = split_focus(line);
+        let mut snapshot = self.clone();
+        for command in &commands {
+            snapshot = snapshot.run_focus_command(command.clone())?;
+        }
+        if !commands.is_empty() && rest.is_empty() {
+            return Ok(record(snapshot, vec![], vec![]));
+        }
+        if let Some((selector, script)) = split_selector(rest) {
+            let (snapshot, counts) = snapshot.run_selected(selector, &Script::parse(script)?)?;
+            return Ok(record(snapshot, counts, vec![]));
+        }
+        let script = Script::parse(rest)?;
+        if let Script::Atom(x) = &script {
+            let tactics = flatten_seq(x);
+            let on_focused_goal = |x: &String| {
+                split_focus(x).0.is_empty()
+                    && split_selector(x).is_none()
+                    && !x.starts_with("Switch ")
+            };
+            if x.starts_with("Seq ") && tactics.iter().all(on_focused_goal) {
+                if snapshot.focused_goals() == 0 {
+                    return Err(tactic::Error::NoFocusedGoal);
+                }
+                let mut counts = vec![];
+                let mut frames = vec![];
+                for tactic in &tactics {
+                    let frame = snapshot.last_frame().ok_or(tactic::Error::NoFocusedGoal)?;
+                    frames.push(frame.clone());
+                    let next = snapshot.run_tactic(tactic)?;
+                    counts.push(next.frames.len() + 1 - snapshot.frames.len());
+                    snapshot = next;
+                }
+                return Ok(record(snapshot, counts, frames));
+            }
+        }
+        Ok(record(snapshot.run_script(&script)?, vec![], vec![]))
+    }
+
+    /// Runs a tactic without combinators on the focused goal
+    fn run_atom(&self, line: &str) -> Result<Self, tactic::Error> {
+        if self.focused_goals() == 0 {
+            return Err(tactic::Error::NoFocusedGoal);
+        }
         if let Some(x) = line.strip_prefix("Switch ") {
             let t: usize = x.parse().map_err(|_| tactic::Error::BadArg {
                 arg: x.to_string(),
@@ -313,7 +410,8 @@ impl Snapshot {
             return Ok(snapshot);
         }
         let frame = snapshot.pop_frame();
-        let new_frames = frame.run_tactic(line)?;
+        let (new_frames, proof) = frame.run_tactic_with_proof(line)?;
+        snapshot.proof = snapshot.proof.fill_last_hole(snapshot.frames.len(), proof);
         snapshot.frames.extend(new_frames);
         Ok(snapshot)
     }
@@ -328,15 +426,23 @@ impl Snapshot {
 
     fn switch_frame(&self, i: usize) -> tactic::Result<Self> {
         let mut result = self.clone();
-        if i >= result.frames.len() {
+        if i >= result.focused_goals() {
             return Err(tactic::Error::InvalidGoalNumber {
                 i,
-                n: result.frames.len(),
+                n: result.focused_goals(),
             });
         }
-        result
-            .frames
-            .swap(result.frames.len() - 1, result.frames.len() - 1 - i);
+        let (a, b) = (result.frames.len() - 1, result.frames.len() - 1 - i);
+        result.frames.swap(a, b);
+        result.proof = result.proof.fill_holes(&|j| {
+            ProofTerm::Hole(if j == a {
+                b
+            } else if j == b {
+                a
+            } else {
+                j
+            })
+        });
         Ok(result)
     }
 
@@ -345,6 +451,47 @@ impl Snapshot {
     }
 }
 
+type TacticFn = for<'a> fn(
+    Frame,
+    std::vec::IntoIter<&'a str>,
+) -> Result<(Vec<Frame>, ProofTerm), tactic::Error>;
+
+/// The built-in tactics, which `Frame::run_tactic_with_proof` dispatches on
+const TACTICS: &[(&str, TacticFn)] = &[
+    ("intros", |frame, args| intros(frame, args)),
+    ("rewrite", |frame, args| rewrite(frame, args)),
+    ("replace", |frame, args| replace(frame, args)),
+    ("unfold", |frame, args| unfold(frame, args)),
+    ("simpl", |frame, args| simpl(frame, args)),
+    ("compute", |frame, args| compute(frame, args)),
+    ("apply", |frame, args| apply(frame, args)),
+    ("add_hyp", |frame, args| add_hyp(frame, args)),
+    ("remove_hyp", |frame, args| remove_hyp(frame, args)),
+    ("revert", |frame, args| revert(frame, args)),
+    ("chain", |frame, args| chain(frame, args)),
+    ("destruct", |frame, args| destruct(frame, args)),
+    ("add_from_lib", |frame, args| add_from_lib(frame, args)),
+    ("lia", |frame, _| lia(frame)),
+    ("lra", |frame, _| lra(frame)),
+    ("nia", |frame, _| nia(frame)),
+    ("ring", |frame, args| ring(frame, args)),
+    ("ring_simplify", |frame, args| ring_simplify(frame, args)),
+    ("auto_set", |frame, _| auto_set(frame)),
+    ("auto_list", |frame, _| auto_list(frame)),
+    ("assumption", |frame, _| assumption(frame)),
+];
+
+/// Commands of the session, which are run before the tactics
+const SESSION_COMMANDS: &[&str] = &["Switch", "Seq", "Undo", "UndoAll", "Redo"];
+
+/// Whether the engine runs the name itself, as a tactic, a command or a combinator,
+/// so a tactic macro can not be declared with it.
+pub(crate) fn is_builtin_tactic(name: &str) -> bool {
+    TACTICS.iter().any(|(x, _)| *x == name)
+        || SESSION_COMMANDS.contains(&name)
+        || COMBINATORS.contains(&name)
+}
+
 impl Frame {
     pub fn add_hyp_with_name(&mut self, name: &str, ty: TermRef) -> tactic::Result<()> {
         self.engine.add_axiom_with_term(name, ty.clone())?;
@@ -411,30 +558,29 @@ impl Frame {
     }
 
     pub fn run_tactic(&self, line: &str) -> Result<Vec<Self>, tactic::Error> {
+        Ok(self.run_tactic_with_proof(line)?.0)
+    }
+
+    /// Runs the tactic and returns the new goals, alongside with a proof of the
+    /// goal of this frame which its holes are the proofs of the new goals.
+    pub(crate) fn run_tactic_with_proof(
+        &self,
+        line: &str,
+    ) -> Result<(Vec<Self>, ProofTerm), tactic::Error> {
         let parts = smart_split(line);
         let mut parts = parts.iter().map(|x| x.as_str());
         let name = parts.next().ok_or(tactic::Error::EmptyTactic)?;
         if self.engine.is_disabled_tactic(name) {
             return Err(tactic::Error::DisabledTactic(name.to_string()));
         }
+        let args = parts.collect::<Vec<_>>().into_iter();
         let frame = self.clone();
-        match name {
-            "intros" => intros(frame, parts),
-            "rewrite" => rewrite(frame, parts),
-            "replace" => replace(frame, parts),
-            "unfold" => unfold(frame, parts),
-            "apply" => apply(frame, parts),
-            "add_hyp" => add_hyp(frame, parts),
-            "remove_hyp" => remove_hyp(frame, parts),
-            "revert" => revert(frame, parts),
-            "chain" => chain(frame, parts),
-            "destruct" => destruct(frame, parts),
-            "add_from_lib" => add_from_lib(frame, parts),
-            "lia" => lia(frame),
-            "auto_set" => auto_set(frame),
-            "auto_list" => auto_list(frame),
-            "assumption" => assumption(frame),
-            _ => Err(tactic::Error::UnknownTactic(name.to_string())),
+        if let Some((_, tactic)) = TACTICS.iter().find(|(x, _)| *x == name) {
+            return tactic(frame, args);
+        }
+        match self.engine.tactic_macro(name) {
+            Some(tactic) => tactic.run(name, frame, args),
+            None => Err(tactic::Error::UnknownTactic(name.to_string())),
         }
     }
 
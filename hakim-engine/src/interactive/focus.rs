@@ -0,0 +1,344 @@
+//! Focusing on some of the goals. A bullet (`-`, `+`, `*` or a repetition of them)
+//! or `{` focuses on the current goal and hides the others, until the goal is solved.
+//! The next bullet of the same kind and `}` fail if the goal is not solved yet, so a
+//! broken proof is reported where it breaks. A selector runs a script on a goal other
+//! than the current one, like `2: lia`, or on every goal in focus, like `all: lia`.
+
+use serde::{Deserialize, Serialize};
+
+use super::{script::Script, tactic, Snapshot};
+
+use tactic::{Error::*, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum FocusKind {
+    Brace,
+    Bullet(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Focus {
+    kind: FocusKind,
+    /// number of goals at the bottom of the stack which are hidden by this focus
+    hidden: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FocusCommand {
+    Open,
+    Close,
+    Bullet(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Selector {
+    /// The n-th goal in focus, from 1
+    Goal(usize),
+    All,
+}
+
+/// Splits the bullets and braces at the start of the line from the rest of it
+pub(crate) fn split_focus(mut line: &str) -> (Vec<FocusCommand>, &str) {
+    let mut r = vec![];
+    loop {
+        line = line.trim_start();
+        let word = line.split_whitespace().next().unwrap_or("");
+        let command = match word {
+            "{" => FocusCommand::Open,
+            "}" => FocusCommand::Close,
+            _ if !word.is_empty()
+                && ["-", "+", "*"]
+                    .iter()
+                    .any(|b| word.chars().all(|c| b.starts_with(c))) =>
+            {
+                FocusCommand::Bullet(word.to_string())
+            }
+            _ => return (r, line.trim_end()),
+        };
+        r.push(command);
+        line = &line[word.len()..];
+    }
+}
+
+/// Splits a selector like `2:` or `all:` from the start of the line
+pub(crate) fn split_selector(line: &str) -> Option<(Selector, &str)> {
+    let (selector, rest) = line.split_once(':')?;
+    let selector = match selector.trim() {
+        "all" => Selector::All,
+        x if !x.is_empty() && x.chars().all(|c| c.is_ascii_digit()) => {
+            Selector::Goal(x.parse().ok()?)
+        }
+        _ => return None,
+    };
+    Some((selector, rest))
+}
+
+impl Snapshot {
+    /// Number of goals at the bottom of the stack which are out of focus
+    pub(crate) fn hidden_goals(&self) -> usize {
+        self.focus.last().map_or(0, |x| x.hidden)
+    }
+
+    /// Number of goals which tactics can work on
+    pub(crate) fn focused_goals(&self) -> usize {
+        self.frames.len() - self.hidden_goals()
+    }
+
+    /// Focuses on the current goal
+    fn push_focus(mut self, kind: FocusKind) -> Result<Self> {
+        if self.focused_goals() == 0 {
+            return Err(NoFocusedGoal);
+        }
+        let hidden = self.frames.len() - 1;
+        self.focus.push_back(Focus { kind, hidden });
+        Ok(self)
+    }
+
+    /// Closes the focus `i` and the ones inside it, if its goal is solved
+    fn close_focus(mut self, i: usize) -> Result<Self> {
+        if self.frames.len() > self.focus[i].hidden {
+            return Err(UnfinishedFocus);
+        }
+        self.focus.truncate(i);
+        Ok(self)
+    }
+
+    pub(crate) fn run_focus_command(&self, command: FocusCommand) -> Result<Self> {
+        let r = self.clone();
+        match command {
+            FocusCommand::Open => r.push_focus(FocusKind::Brace),
+            FocusCommand::Close => {
+                let i = r
+                    .focus
+                    .iter()
+                    .rposition(|x| x.kind == FocusKind::Brace)
+                    .ok_or(NoOpenBrace)?;
+                r.close_focus(i)
+            }
+            FocusCommand::Bullet(bullet) => {
+                let kind = FocusKind::Bullet(bullet);
+                // the previous bullet of this kind, if it is not outside of a brace
+                let previous = r
+                    .focus
+                    .iter()
+                    .rposition(|x| x.kind == FocusKind::Brace || x.kind == kind)
+                    .filter(|i| r.focus[*i].kind == kind);
+                let r = match previous {
+                    Some(i) => r.close_focus(i)?,
+                    None => r,
+                };
+                r.push_focus(kind)
+            }
+        }
+    }
+
+    /// Runs the script on the selected goals, and returns the number of goals which
+    /// each of them is replaced with.
+    pub(crate) fn run_selected(
+        &self,
+        selector: Selector,
+        script: &Script,
+    ) -> Result<(Self, Vec<usize>)> {
+        let (start, end) = (self.hidden_goals(), self.frames.len());
+        let goals = match selector {
+            Selector::Goal(n) => {
+                if n == 0 || n > end - start {
+                    return Err(InvalidGoalNumber {
+                        i: n,
+                        n: end - start,
+                    });
+                }
+                end - n..end - n + 1
+            }
+            Selector::All => start..end,
+        };
+        let mut r = self.clone();
+        let mut counts = vec![];
+        for i in goals.rev() {
+            let next = r.on_goal(i, |x| x.run_script(script))?;
+            counts.push(next.frames.len() + 1 - r.frames.len());
+            r = next;
+        }
+        counts.reverse();
+        Ok((r, counts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_focus, split_selector, FocusCommand, Selector};
+    use crate::interactive::{
+        proof_tree::ProofTree,
+        tests::{run_interactive, run_interactive_to_end, run_interactive_to_fail, EngineLevel},
+    };
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            split_focus("} -- lia"),
+            (
+                vec![FocusCommand::Close, FocusCommand::Bullet("--".to_string())],
+                "lia"
+            )
+        );
+        assert_eq!(split_focus("{"), (vec![FocusCommand::Open], ""));
+        assert_eq!(split_focus("-x"), (vec![], "-x"));
+        assert_eq!(split_focus("+-"), (vec![], "+-"));
+        assert_eq!(split_selector("2: lia"), Some((Selector::Goal(2), " lia")));
+        assert_eq!(split_selector("all:lia"), Some((Selector::All, "lia")));
+        assert_eq!(split_selector("apply (λ x: ℤ, x)"), None);
+    }
+
+    const AND3: &str = "∀ A B C: U, A -> B -> C -> C ∧ (B ∧ A)";
+
+    #[test]
+    fn bullets() {
+        run_interactive_to_end(
+            AND3,
+            r#"
+            intros A B C a b c
+            apply and_intro
+            - apply and_intro
+              + apply a
+              + apply b
+            - apply c
+            "#,
+        );
+        run_interactive_to_end(
+            AND3,
+            r#"
+            intros A B C a b c
+            apply and_intro
+            *
+            apply and_intro
+            -- apply a
+            -- apply b
+            * apply c
+            "#,
+        );
+        run_interactive_to_fail(
+            AND3,
+            r#"
+            intros A B C a b c
+            apply and_intro
+            - apply and_intro
+              + apply a
+            "#,
+            "- apply c",
+        );
+        run_interactive_to_fail(
+            AND3,
+            r#"
+            intros A B C a b c
+            apply and_intro
+            - apply and_intro
+              + apply a
+            "#,
+            "apply b",
+        );
+    }
+
+    #[test]
+    fn braces() {
+        run_interactive_to_end(
+            AND3,
+            r#"
+            intros A B C a b c
+            apply and_intro
+            { apply and_intro
+              { apply a
+              }
+              apply b
+            }
+            apply c
+            "#,
+        );
+        run_interactive_to_fail(
+            AND3,
+            r#"
+            intros A B C a b c
+            apply and_intro
+            {
+            apply and_intro
+            apply a
+            "#,
+            "}",
+        );
+        run_interactive_to_fail(AND3, "intros A B C a b c", "}");
+    }
+
+    #[test]
+    fn selectors() {
+        run_interactive_to_end(
+            AND3,
+            r#"
+            intros A B C a b c
+            apply and_intro
+            2: apply c
+            apply and_intro
+            all: assumption
+            "#,
+        );
+        run_interactive_to_fail(
+            AND3,
+            r#"
+            intros A B C a b c
+            apply and_intro
+            "#,
+            "3: apply c",
+        );
+        run_interactive_to_fail(
+            AND3,
+            r#"
+            intros A B C a b c
+            apply and_intro
+            - apply and_intro
+            "#,
+            "3: apply c",
+        );
+    }
+
+    #[test]
+    fn proof_tree() {
+        let session = run_interactive(
+            AND3,
+            r#"
+            intros A B C a b c
+            apply and_intro
+            2: apply c
+            - apply and_intro
+              all: assumption
+            "#,
+            EngineLevel::Full,
+        );
+        let tree = ProofTree::from(session);
+        assert!(tree.is_solved(0));
+        assert_eq!(
+            tree.tactics(0),
+            [
+                "intros A B C a b c",
+                "apply and_intro",
+                "apply and_intro",
+                "assumption",
+                "assumption",
+                "apply c"
+            ]
+        );
+        // the selected goals are replaced with different numbers of goals
+        let session = run_interactive(
+            AND3,
+            r#"
+            intros A B C a b c
+            apply and_intro
+            all: try apply and_intro
+            all: assumption
+            "#,
+            EngineLevel::Full,
+        );
+        let tree = ProofTree::from(session);
+        assert!(tree.is_solved(0));
+        let tactics = tree.tactics(0);
+        assert_eq!(tactics.iter().filter(|x| *x == "assumption").count(), 3);
+        assert_eq!(tactics.len(), 2 + 2 + 3);
+    }
+}
@@ -0,0 +1,237 @@
+//! The history of a session, as a tree of the states of the proof. The root is the
+//! goal, and each child is the result of a tactic on its parent, so running a tactic
+//! after an undo starts a new branch and keeps the old one.
+
+use serde::{Deserialize, Serialize};
+
+use super::{tactic, HistoryRecord, Session};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct HistoryNode {
+    record: HistoryRecord,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// the child which redo goes to, which is the last visited one
+    redo: Option<usize>,
+}
+
+/// The path from the goal to a state which no tactic is run on
+#[derive(Debug, Clone, Serialize)]
+pub struct Branch {
+    /// the last node of the branch
+    pub node: usize,
+    pub tactics: Vec<String>,
+    pub is_finished: bool,
+    /// whether the current state is on this branch
+    pub is_current: bool,
+}
+
+/// The tactics which lead to two states of the history, after the tactics which
+/// are common between them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BranchDiff {
+    pub common: Vec<String>,
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+}
+
+impl Session {
+    pub(super) fn from_root(record: HistoryRecord) -> Self {
+        let root = HistoryNode {
+            record,
+            parent: None,
+            children: vec![],
+            redo: None,
+        };
+        Session {
+            nodes: im::vector![root],
+            current: 0,
+        }
+    }
+
+    pub(super) fn root(&self) -> &HistoryRecord {
+        &self.nodes[0].record
+    }
+
+    pub(super) fn current_record(&self) -> &HistoryRecord {
+        &self.nodes[self.current].record
+    }
+
+    /// Nodes from the root to `node`
+    fn path_to(&self, mut node: usize) -> Vec<usize> {
+        let mut r = vec![node];
+        while let Some(parent) = self.nodes[node].parent {
+            r.push(parent);
+            node = parent;
+        }
+        r.reverse();
+        r
+    }
+
+    fn tactics_of(&self, nodes: &[usize]) -> Vec<String> {
+        nodes
+            .iter()
+            .map(|x| self.nodes[*x].record.tactic.clone())
+            .collect()
+    }
+
+    /// Records from the goal to the current state
+    pub(crate) fn history(&self) -> impl Iterator<Item = &HistoryRecord> {
+        self.path_to(self.current)
+            .into_iter()
+            .map(|x| &self.nodes[x].record)
+    }
+
+    /// Moves to the result of a tactic on the current state. Running a tactic which
+    /// is run on this state before goes to its old node.
+    ///
+    /// Nodes are never removed, so the history keeps the snapshot of every state
+    /// visited in the session. Snapshots share their frames through `im`, so a node
+    /// costs about the goals which its tactic changed.
+    pub(super) fn add_history_record(&mut self, r: HistoryRecord) {
+        let children = &self.nodes[self.current].children;
+        let old = children
+            .iter()
+            .find(|x| self.nodes[**x].record.tactic == r.tactic);
+        let child = match old {
+            Some(x) => *x,
+            None => {
+                self.nodes.push_back(HistoryNode {
+                    record: r,
+                    parent: Some(self.current),
+                    children: vec![],
+                    redo: None,
+                });
+                let child = self.nodes.len() - 1;
+                self.nodes[self.current].children.push(child);
+                child
+            }
+        };
+        self.nodes[self.current].redo = Some(child);
+        self.current = child;
+    }
+
+    pub fn undo(&mut self) -> Result<(), tactic::Error> {
+        let parent = self.nodes[self.current]
+            .parent
+            .ok_or(tactic::Error::CanNotUndo)?;
+        self.nodes[parent].redo = Some(self.current);
+        self.current = parent;
+        Ok(())
+    }
+
+    pub fn redo(&mut self) -> Result<(), tactic::Error> {
+        self.current = self.nodes[self.current]
+            .redo
+            .ok_or(tactic::Error::CanNotRedo)?;
+        Ok(())
+    }
+
+    /// Tactics from the goal to the current state, and the tactics which redo runs
+    pub fn get_history(&self) -> (Vec<String>, Vec<String>) {
+        let mut redo = vec![];
+        let mut node = self.current;
+        while let Some(x) = self.nodes[node].redo {
+            redo.push(x);
+            node = x;
+        }
+        (
+            self.tactics_of(&self.path_to(self.current)),
+            self.tactics_of(&redo),
+        )
+    }
+
+    pub fn current_node(&self) -> usize {
+        self.current
+    }
+
+    /// Every branch of the history, in the order which they are started
+    pub fn branches(&self) -> Vec<Branch> {
+        let current = self.path_to(self.current);
+        (0..self.nodes.len())
+            .filter(|x| self.nodes[*x].children.is_empty())
+            .map(|node| {
+                let path = self.path_to(node);
+                Branch {
+                    node,
+                    tactics: self.tactics_of(&path[1..]),
+                    is_finished: self.nodes[node].record.snapshot.is_finished(),
+                    is_current: path.starts_with(&current),
+                }
+            })
+            .collect()
+    }
+
+    /// Goes to a state of the history, and makes redo follow the path to it
+    pub fn goto_node(&mut self, node: usize) -> Result<(), tactic::Error> {
+        if node >= self.nodes.len() {
+            return Err(tactic::Error::UnknownHistoryNode(node));
+        }
+        let path = self.path_to(node);
+        for x in path.windows(2) {
+            self.nodes[x[0]].redo = Some(x[1]);
+        }
+        self.current = node;
+        Ok(())
+    }
+
+    pub fn compare_branches(&self, left: usize, right: usize) -> Result<BranchDiff, tactic::Error> {
+        for node in [left, right] {
+            if node >= self.nodes.len() {
+                return Err(tactic::Error::UnknownHistoryNode(node));
+            }
+        }
+        let (left, right) = (self.path_to(left), self.path_to(right));
+        let common = left.iter().zip(&right).take_while(|(a, b)| a == b).count();
+        Ok(BranchDiff {
+            common: self.tactics_of(&left[1..common]),
+            left: self.tactics_of(&left[common..]),
+            right: self.tactics_of(&right[common..]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interactive::tests::{run_interactive, EngineLevel};
+
+    const GOAL: &str = "∀ a: ℤ, 0 < a -> 0 < a + a";
+
+    #[test]
+    fn branches() {
+        let mut s = run_interactive(GOAL, "intros a H\nlia", EngineLevel::Full);
+        let first = s.current_node();
+        s.run_tactic("Undo").unwrap();
+        s.run_tactic("nia").unwrap();
+        let second = s.current_node();
+        let branches = s.branches();
+        assert_eq!(branches.len(), 2);
+        assert_eq!(branches[0].tactics, ["intros a H", "lia"]);
+        assert!(branches[0].is_finished && !branches[0].is_current);
+        assert_eq!(branches[1].tactics, ["intros a H", "nia"]);
+        assert!(branches[1].is_current);
+        let diff = s.compare_branches(first, second).unwrap();
+        assert_eq!(diff.common, ["intros a H"]);
+        assert_eq!(diff.left, ["lia"]);
+        assert_eq!(diff.right, ["nia"]);
+        s.goto_node(first).unwrap();
+        assert!(s.is_finished());
+        assert_eq!(s.get_history().0, ["Proof", "intros a H", "lia"]);
+        assert!(s.goto_node(100).is_err());
+    }
+
+    #[test]
+    fn undo_keeps_branches() {
+        let mut s = run_interactive(GOAL, "intros a H\nlia", EngineLevel::Full);
+        s.run_tactic("UndoAll").unwrap();
+        s.run_tactic("intros").unwrap();
+        assert_eq!(s.branches().len(), 2);
+        s.run_tactic("Undo").unwrap();
+        assert_eq!(s.get_history().1, ["intros"]);
+        s.run_tactic("intros a H").unwrap();
+        assert_eq!(s.get_history().1, ["lia"]);
+        s.run_tactic("Redo").unwrap();
+        assert!(s.is_finished());
+        assert_eq!(s.branches().len(), 2);
+    }
+}
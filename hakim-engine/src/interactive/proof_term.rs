@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_ref,
+    brain::{contains_wild, fill_axiom, Term, TermRef},
+    term_ref, Abstraction,
+};
+
+use super::{tactic, Frame};
+
+/// A partial proof. Each `Hole(i)` stands for the proof of the i-th goal
+/// produced by a tactic, and hypotheses are referred by their axiom, so
+/// they can be abstracted by `Intro` when the proof is assembled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum ProofTerm {
+    Hole(usize),
+    Term(TermRef),
+    App(Box<ProofTerm>, Box<ProofTerm>),
+    Intro {
+        name: String,
+        ty: TermRef,
+        body: Box<ProofTerm>,
+    },
+}
+
+use ProofTerm::*;
+
+impl ProofTerm {
+    pub fn app(self, op: ProofTerm) -> Self {
+        App(Box::new(self), Box::new(op))
+    }
+
+    pub fn intro(name: &str, ty: TermRef, body: ProofTerm) -> Self {
+        Intro {
+            name: name.to_string(),
+            ty,
+            body: Box::new(body),
+        }
+    }
+
+    /// Converts a term which its unresolved wilds are the goals of a tactic into
+    /// a proof. `hole_of` should map index of each such wild into its goal number.
+    pub fn from_term_with_wild(
+        term: TermRef,
+        hole_of: &impl Fn(usize) -> Option<usize>,
+    ) -> tactic::Result<Self> {
+        match term.as_ref() {
+            Term::Wild { index, .. } => hole_of(*index)
+                .map(Hole)
+                .ok_or(tactic::Error::CanNotSolve("apply")),
+            Term::App { func, op } => {
+                let func = Self::from_term_with_wild(func.clone(), hole_of)?;
+                let op = Self::from_term_with_wild(op.clone(), hole_of)?;
+                Ok(match (func, op) {
+                    (Term(func), Term(op)) => Term(app_ref!(func, op)),
+                    (func, op) => func.app(op),
+                })
+            }
+            Term::Forall(Abstraction { var_ty, body, .. })
+            | Term::Fun(Abstraction { var_ty, body, .. }) => {
+                if contains_wild(var_ty) || contains_wild(body) {
+                    // goals are proved outside of binders, so they can not sit under them
+                    return Err(tactic::Error::CanNotSolve("apply"));
+                }
+                Ok(Term(term))
+            }
+            Term::Axiom { .. } | Term::Universe { .. } | Term::Var { .. } | Term::Number { .. } => {
+                Ok(Term(term))
+            }
+        }
+    }
+
+    /// A trusted proof of the goal of the frame, which its only evidence is that
+    /// the decision procedure `tactic_name` succeeded on it. It is an axiom
+    /// named `$tactic_name` with type `∀ hyps, goal` applied to the hyps.
+    pub fn oracle(tactic_name: &str, frame: &Frame) -> Self {
+        let mut ty = frame.goal.clone();
+        for hyp in frame.hyps.iter().rev() {
+            let body = fill_axiom(ty, |name, ty, depth| {
+                if name == hyp.name {
+                    term_ref!(v depth)
+                } else {
+                    term_ref!(axiom name, ty)
+                }
+            });
+            ty = TermRef::new(Term::Forall(Abstraction {
+                var_ty: hyp.ty.clone(),
+                hint_name: Some(hyp.name.clone()),
+                body,
+            }));
+        }
+        let mut proof = term_ref!(axiom format!("${}", tactic_name), ty);
+        for hyp in &frame.hyps {
+            proof = app_ref!(proof, term_ref!(axiom hyp.name.clone(), hyp.ty.clone()));
+        }
+        Term(proof)
+    }
+
+    /// Replaces every hole with the result of `f` on its number.
+    pub fn fill_holes(self, f: &impl Fn(usize) -> ProofTerm) -> Self {
+        match self {
+            Hole(i) => f(i),
+            Term(_) => self,
+            App(func, op) => func.fill_holes(f).app(op.fill_holes(f)),
+            Intro { name, ty, body } => Intro {
+                name,
+                ty,
+                body: Box::new(body.fill_holes(f)),
+            },
+        }
+    }
+
+    /// Puts `proof` in the hole `i` and shifts its holes by `i`, so holes of `proof`
+    /// lands after the holes before `i`. Holes after `i` should not exist.
+    pub fn fill_last_hole(self, i: usize, proof: ProofTerm) -> Self {
+        let proof = proof.fill_holes(&|j| Hole(j + i));
+        self.fill_holes(&|j| if i == j { proof.clone() } else { Hole(j) })
+    }
+
+    /// Builds the final proof term, or `None` if there is still some hole in the proof.
+    pub fn into_term(self) -> Option<TermRef> {
+        self.into_term_under(&mut vec![])
+    }
+
+    /// The term of the proof under the `Intro`s of `intros`, which abstracts them in
+    /// one pass over each term, since the terms of the tactics may be big
+    fn into_term_under(self, intros: &mut Vec<String>) -> Option<TermRef> {
+        Some(match self {
+            Hole(_) => return None,
+            Term(t) => abstract_intros(t, intros),
+            App(func, op) => app_ref!(func.into_term_under(intros)?, op.into_term_under(intros)?),
+            Intro { name, ty, body } => {
+                let var_ty = abstract_intros(ty, intros);
+                intros.push(name);
+                let body = body.into_term_under(intros);
+                let name = intros.pop().expect("the name is pushed above");
+                TermRef::new(Term::Fun(Abstraction {
+                    var_ty,
+                    hint_name: Some(name),
+                    body: body?,
+                }))
+            }
+        })
+    }
+}
+
+/// Replaces the hypotheses of `intros` with their variables, where the last one is
+/// the innermost
+fn abstract_intros(t: TermRef, intros: &[String]) -> TermRef {
+    if intros.is_empty() {
+        return t;
+    }
+    fill_axiom(t, |x, ty, depth| {
+        match intros.iter().rposition(|name| name == x) {
+            Some(i) => term_ref!(v depth + intros.len() - 1 - i),
+            None => term_ref!(axiom x, ty),
+        }
+    })
+}
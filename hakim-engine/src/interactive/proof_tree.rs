@@ -37,6 +37,41 @@ impl ProofTree {
         dfs(self, i, &mut v);
         v
     }
+
+    /// Writes the tactics of the subtree `i` as sentences of a proof. The goals of a
+    /// tactic which generates more than one goal get bullets, which their kind
+    /// depends on `depth`. The first line starts with `first` and the others with
+    /// `indent`.
+    pub(crate) fn write_script(
+        &self,
+        mut i: usize,
+        mut first: String,
+        indent: &str,
+        depth: usize,
+        r: &mut String,
+    ) {
+        const BULLETS: [&str; 3] = ["-", "+", "*"];
+        while let Tactic {
+            tactic, children, ..
+        } = &self.0[i]
+        {
+            r.push_str(&format!("{first}{tactic}.\n"));
+            first = indent.to_string();
+            match children[..] {
+                [] => return,
+                [child] => i = child,
+                _ => {
+                    let bullet = BULLETS[depth % 3].repeat(depth / 3 + 1);
+                    let inner = format!("{indent}{}", " ".repeat(bullet.len() + 1));
+                    for child in children {
+                        let first = format!("{indent}{bullet} ");
+                        self.write_script(*child, first, &inner, depth + 1, r);
+                    }
+                    return;
+                }
+            }
+        }
+    }
 }
 
 impl ProofNode {
@@ -54,48 +89,101 @@ impl ProofNode {
 
 use ProofNode::*;
 
-use super::{Frame, Session};
+use super::{
+    flatten_seq,
+    focus::{split_focus, split_selector, Selector},
+    Frame, Session,
+};
 
 impl From<Session> for ProofTree {
     fn from(session: Session) -> Self {
-        let mut it = session.history.into_iter();
-        let mut r = vec![RemainingGoal(
-            it.next()
-                .unwrap()
-                .snapshot
-                .frames
-                .into_iter()
-                .next()
-                .unwrap(),
-        )];
+        let history: Vec<_> = session.history().cloned().collect();
+        let mut it = history.into_iter();
+        let mut prev = it.next().unwrap().snapshot;
+        let mut r = vec![RemainingGoal(prev.frames[0].clone())];
+        // nodes of the goals of the previous snapshot
         let mut going = vec![0];
-        let mut pf = 1;
         for h in it {
-            if let Some(x) = h.tactic.strip_prefix("Switch ") {
+            let (_, tactic) = split_focus(&h.tactic);
+            if let Some(x) = tactic.strip_prefix("Switch ") {
                 let x: usize = x.parse().unwrap();
                 let len = going.len();
                 going.swap(len - 1, len - 1 - x);
+                prev = h.snapshot;
+                continue;
+            }
+            if tactic.is_empty() {
+                prev = h.snapshot;
                 continue;
             }
-            let now = going.pop().unwrap();
-            let frames = h.snapshot.frames;
-            let cnt = frames.len() + 1 - pf;
-            pf = frames.len();
-            let children: Vec<_> = frames
-                .into_iter()
-                .rev()
-                .take(cnt)
-                .map(|x| {
-                    r.push(RemainingGoal(x));
-                    r.len() - 1
-                })
-                .collect();
-            going.extend(children.iter().rev());
-            r[now] = Tactic {
-                frame: r[now].frame().clone(),
-                tactic: h.tactic,
-                children,
+            if !h.seq_frames.is_empty() {
+                // the tactics of a `Seq` are run one after another on the focused goal
+                let tactics = flatten_seq(tactic);
+                let mut added = vec![];
+                for ((tactic, count), frame) in
+                    tactics.into_iter().zip(h.goal_counts).zip(h.seq_frames)
+                {
+                    let now = going.pop().unwrap();
+                    let children: Vec<_> = (0..count)
+                        .map(|_| {
+                            r.push(RemainingGoal(frame.clone()));
+                            r.len() - 1
+                        })
+                        .collect();
+                    going.extend(children.iter().rev());
+                    added.extend_from_slice(&children);
+                    r[now] = Tactic {
+                        frame,
+                        tactic,
+                        children,
+                    };
+                }
+                for (j, x) in going.iter().enumerate() {
+                    if added.contains(x) {
+                        r[*x] = RemainingGoal(h.snapshot.frames[j].clone());
+                    }
+                }
+                prev = h.snapshot;
+                continue;
+            }
+            let (goals, tactic) = match split_selector(tactic) {
+                Some((Selector::Goal(n), x)) => (vec![going.len() - n], x.trim()),
+                Some((Selector::All, x)) => {
+                    ((prev.hidden_goals()..going.len()).collect(), x.trim())
+                }
+                None => (vec![going.len() - 1], tactic),
+            };
+            let frames = &h.snapshot.frames;
+            // number of the goals which each goal is replaced with
+            let counts = if h.goal_counts.is_empty() {
+                vec![frames.len() + 1 - going.len()]
+            } else {
+                h.goal_counts
             };
+            let mut next_going = vec![];
+            let mut last = 0;
+            for (i, count) in goals.into_iter().zip(counts) {
+                next_going.extend_from_slice(&going[last..i]);
+                let start = next_going.len();
+                let children: Vec<_> = (start..start + count)
+                    .rev()
+                    .map(|j| {
+                        r.push(RemainingGoal(frames[j].clone()));
+                        r.len() - 1
+                    })
+                    .collect();
+                next_going.extend(children.iter().rev());
+                let now = going[i];
+                r[now] = Tactic {
+                    frame: r[now].frame().clone(),
+                    tactic: tactic.to_string(),
+                    children,
+                };
+                last = i + 1;
+            }
+            next_going.extend_from_slice(&going[last..]);
+            going = next_going;
+            prev = h.snapshot;
         }
         Self(r)
     }
@@ -0,0 +1,599 @@
+//! Tactic scripts, which compose tactics with combinators:
+//!
+//! * `t1; t2` runs `t2` on every goal which `t1` generates
+//! * `t1 || t2` runs `t2` if `t1` fails
+//! * `try t`, `repeat t`, `progress t` and `do n t`
+//! * `first [t1 | t2 | ...]` runs the first alternative which succeeds
+//!
+//! Parentheses group a script, like `intros; (apply H || lia)`. A script runs on
+//! the focused goal, and leaves the other goals as they are.
+//!
+//! Libraries can name a script with `Tactic name params := script.`, which is then
+//! run like a built-in tactic with the arguments in place of the parameters.
+
+use serde::{Deserialize, Serialize};
+
+use super::{smart_split, tactic, Frame, ProofTerm, Snapshot};
+use crate::parser::is_valid_ident;
+
+use tactic::{Error::*, Result};
+
+/// The words which start a combinator, so they are not the name of a tactic
+pub(crate) const COMBINATORS: &[&str] = &["try", "repeat", "progress", "do", "first"];
+
+/// How deep `repeat` goes on the goals which its tactic generates
+const REPEAT_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Script {
+    /// A tactic without combinators, like `apply H`
+    Atom(String),
+    Try(Box<Script>),
+    Repeat(Box<Script>),
+    Progress(Box<Script>),
+    Do(usize, Box<Script>),
+    First(Vec<Script>),
+    Then(Box<Script>, Box<Script>),
+    OrElse(Box<Script>, Box<Script>),
+}
+
+use Script::*;
+
+/// The characters of the text with their byte index, and whether they are inside a
+/// string like `"ab"` or a char like `'('`, which are read the way the term
+/// tokenizer reads them.
+fn quoted_chars(text: &str) -> Vec<(usize, char, bool)> {
+    let mut in_string = false;
+    let mut in_char = 0;
+    text.char_indices()
+        .map(|(i, c)| {
+            let quoted = if in_char > 0 {
+                in_char -= 1;
+                true
+            } else if in_string {
+                in_string = c != '"';
+                true
+            } else {
+                match c {
+                    '"' => in_string = true,
+                    '\'' => in_char = 2,
+                    _ => return (i, c, false),
+                }
+                true
+            };
+            (i, c, quoted)
+        })
+        .collect()
+}
+
+/// Splits the text into words, and the separators `;` and `||`. Brackets, strings
+/// and chars are kept in a single word.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut r = vec![];
+    let mut s = String::new();
+    let mut depth = 0;
+    let mut chars = quoted_chars(text).into_iter().peekable();
+    while let Some((_, c, quoted)) = chars.next() {
+        if !quoted {
+            match c {
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                _ => (),
+            }
+        }
+        if quoted || depth != 0 {
+            s.push(c);
+            continue;
+        }
+        let separator = match c {
+            ';' => Some(";"),
+            '|' if chars.peek().map(|x| x.1) == Some('|') => {
+                chars.next();
+                Some("||")
+            }
+            _ => None,
+        };
+        if separator.is_some() || c.is_whitespace() {
+            if !s.is_empty() {
+                r.push(std::mem::take(&mut s));
+            }
+            r.extend(separator.map(str::to_string));
+            continue;
+        }
+        s.push(c);
+    }
+    if !s.is_empty() {
+        r.push(s);
+    }
+    r
+}
+
+/// Splits the inside of `first [...]` on the `|` signs which are not nested
+fn alternatives(text: &str) -> Vec<&str> {
+    let mut r = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    let bytes = text.as_bytes();
+    for (i, c, quoted) in quoted_chars(text) {
+        if quoted {
+            continue;
+        }
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            '|' if depth == 0
+                && bytes.get(i + 1) != Some(&b'|')
+                && (i == 0 || bytes[i - 1] != b'|') =>
+            {
+                r.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    r.push(&text[start..]);
+    r
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|x| x.as_str())
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let r = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        r
+    }
+
+    fn then(&mut self) -> Result<Script> {
+        let mut r = self.or_else()?;
+        while self.peek() == Some(";") {
+            self.pos += 1;
+            r = Then(Box::new(r), Box::new(self.or_else()?));
+        }
+        Ok(r)
+    }
+
+    fn or_else(&mut self) -> Result<Script> {
+        let r = self.unary()?;
+        if self.peek() == Some("||") {
+            self.pos += 1;
+            return Ok(OrElse(Box::new(r), Box::new(self.or_else()?)));
+        }
+        Ok(r)
+    }
+
+    fn unary(&mut self) -> Result<Script> {
+        let bad_arg = |tactic_name: &str, arg: String| BadArg {
+            tactic_name: tactic_name.to_string(),
+            arg,
+        };
+        match self.peek() {
+            None | Some(";" | "||") => return Err(EmptyTactic),
+            Some("try") => {
+                self.pos += 1;
+                return Ok(Try(Box::new(self.unary()?)));
+            }
+            Some("repeat") => {
+                self.pos += 1;
+                return Ok(Repeat(Box::new(self.unary()?)));
+            }
+            Some("progress") => {
+                self.pos += 1;
+                return Ok(Progress(Box::new(self.unary()?)));
+            }
+            Some("do") => {
+                self.pos += 1;
+                let n = self.next().ok_or(BadArgCount {
+                    tactic_name: "do".to_string(),
+                })?;
+                let n = n.parse().map_err(|_| bad_arg("do", n))?;
+                return Ok(Do(n, Box::new(self.unary()?)));
+            }
+            Some("first") => {
+                self.pos += 1;
+                let arg = self.next().ok_or(BadArgCount {
+                    tactic_name: "first".to_string(),
+                })?;
+                let Some(inner) = arg.strip_prefix('[').and_then(|x| x.strip_suffix(']')) else {
+                    return Err(bad_arg("first", arg));
+                };
+                let alternatives = alternatives(inner)
+                    .into_iter()
+                    .map(Script::parse)
+                    .collect::<Result<_>>()?;
+                return Ok(First(alternatives));
+            }
+            Some(x) if x.starts_with('(') => {
+                let x = self.next().unwrap();
+                if let Some(inner) = x.strip_prefix('(').and_then(|x| x.strip_suffix(')')) {
+                    return Script::parse(inner);
+                }
+                return Err(UnknownTactic(x));
+            }
+            Some(_) => (),
+        }
+        let mut words = vec![];
+        while let Some(x) = self.peek() {
+            if x == ";" || x == "||" {
+                break;
+            }
+            words.push(self.next().unwrap());
+        }
+        Ok(Atom(words.join(" ")))
+    }
+}
+
+impl Script {
+    pub(crate) fn parse(text: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(text),
+            pos: 0,
+        };
+        let r = parser.then()?;
+        if let Some(x) = parser.next() {
+            return Err(UnknownTactic(x));
+        }
+        Ok(r)
+    }
+}
+
+/// A tactic which is declared in a library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TacticMacro {
+    params: Vec<String>,
+    body: String,
+}
+
+impl Script {
+    /// Runs `f` on each tactic without combinators, and keeps the combinators
+    fn map_atoms(&self, f: &impl Fn(&str) -> String) -> Self {
+        let map = |t: &Script| Box::new(t.map_atoms(f));
+        match self {
+            Atom(line) => Atom(f(line)),
+            Try(t) => Try(map(t)),
+            Repeat(t) => Repeat(map(t)),
+            Progress(t) => Progress(map(t)),
+            Do(n, t) => Do(*n, map(t)),
+            First(ts) => First(ts.iter().map(|t| t.map_atoms(f)).collect()),
+            Then(a, b) => Then(map(a), map(b)),
+            OrElse(a, b) => OrElse(map(a), map(b)),
+        }
+    }
+
+    fn atoms(&self) -> Vec<&str> {
+        match self {
+            Atom(line) => vec![line],
+            Try(t) | Repeat(t) | Progress(t) | Do(_, t) => t.atoms(),
+            First(ts) => ts.iter().flat_map(|t| t.atoms()).collect(),
+            Then(a, b) | OrElse(a, b) => [a.atoms(), b.atoms()].concat(),
+        }
+    }
+}
+
+fn strip_paren(x: &str) -> Option<&str> {
+    x.strip_prefix('(').and_then(|x| x.strip_suffix(')'))
+}
+
+/// Replaces the arguments of the tactic which `f` has a replacement for. An argument
+/// is replaced if it is a name, or a name in a list like `(a b)`, and the arguments of
+/// `chain` are tactics themselves. Names inside terms are kept, so a replacement
+/// never captures a bound variable of the term.
+fn replace_args(line: &str, f: &impl Fn(&str) -> Option<String>) -> String {
+    let parts = smart_split(line);
+    let Some((head, args)) = parts.split_first() else {
+        return line.to_string();
+    };
+    let args = args.iter().map(|arg| {
+        if let Some(r) = f(arg) {
+            return r;
+        }
+        let Some(inner) = strip_paren(arg) else {
+            return arg.clone();
+        };
+        if head == "chain" {
+            return format!("({})", replace_args(inner, f));
+        }
+        let names = inner.split_whitespace().collect::<Vec<_>>();
+        if names.is_empty() || !names.iter().all(|x| is_valid_ident(x)) {
+            return arg.clone();
+        }
+        let names = names
+            .into_iter()
+            .map(|x| f(x).unwrap_or_else(|| x.to_string()))
+            .collect::<Vec<_>>();
+        format!("({})", names.join(" "))
+    });
+    [head.clone()]
+        .into_iter()
+        .chain(args)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Names of the tactics which the line runs, which are its first word and the
+/// tactics in the arguments of `chain`
+fn tactic_heads(line: &str) -> Vec<String> {
+    let parts = smart_split(line);
+    let Some((head, args)) = parts.split_first() else {
+        return vec![];
+    };
+    let mut r = vec![head.clone()];
+    if head == "chain" {
+        r.extend(
+            args.iter()
+                .flat_map(|x| tactic_heads(strip_paren(x).unwrap_or(x))),
+        );
+    }
+    r
+}
+
+impl TacticMacro {
+    pub(crate) fn new(params: Vec<String>, body: String) -> Result<Self> {
+        Script::parse(&body)?;
+        Ok(TacticMacro { params, body })
+    }
+
+    /// Whether the script runs a tactic with this name
+    pub(crate) fn mentions(&self, name: &str) -> bool {
+        let Ok(script) = Script::parse(&self.body) else {
+            return false;
+        };
+        script
+            .atoms()
+            .into_iter()
+            .any(|x| tactic_heads(x).iter().any(|x| x == name))
+    }
+
+    pub(crate) fn run<'a>(
+        &self,
+        name: &str,
+        frame: Frame,
+        args: impl Iterator<Item = &'a str>,
+    ) -> Result<(Vec<Frame>, ProofTerm)> {
+        let args: Vec<_> = args.collect();
+        if args.len() != self.params.len() {
+            return Err(BadArgCount {
+                tactic_name: name.to_string(),
+            });
+        }
+        let script = Script::parse(&self.body)?.map_atoms(&|line| {
+            replace_args(line, &|x| {
+                let i = self.params.iter().position(|p| p == x)?;
+                Some(args[i].to_string())
+            })
+        });
+        let snapshot = Snapshot::from(frame).run_script(&script)?;
+        Ok((snapshot.frames.into_iter().collect(), snapshot.proof))
+    }
+}
+
+impl Snapshot {
+    /// Runs `f` on a snapshot of only the goal `i`, and puts the goals which it
+    /// leaves in the place of that goal.
+    pub(super) fn on_goal(
+        &self,
+        i: usize,
+        f: impl FnOnce(Snapshot) -> Result<Snapshot>,
+    ) -> Result<Self> {
+        let mut before = self.frames.clone();
+        let after = before.split_off(i + 1);
+        let frame = before.pop_back().unwrap();
+        let sub = f(Snapshot::from(frame))?;
+        let n = sub.frames.len();
+        let sub_proof = sub.proof.fill_holes(&|j| ProofTerm::Hole(j + i));
+        let proof = self.proof.clone().fill_holes(&|j| {
+            if j < i {
+                ProofTerm::Hole(j)
+            } else if j == i {
+                sub_proof.clone()
+            } else {
+                ProofTerm::Hole(j + n - 1)
+            }
+        });
+        before.append(sub.frames);
+        before.append(after);
+        Ok(Snapshot {
+            frames: before,
+            proof,
+            focus: self.focus.clone(),
+        })
+    }
+
+    /// Runs the script on each goal of `next` which is generated from the focused
+    /// goal of `self`
+    fn on_generated(
+        &self,
+        mut next: Snapshot,
+        script: impl Fn(Snapshot) -> Result<Snapshot>,
+    ) -> Result<Self> {
+        let start = self.frames.len().saturating_sub(1);
+        for i in (start..next.frames.len()).rev() {
+            next = next.on_goal(i, &script)?;
+        }
+        Ok(next)
+    }
+
+    /// Whether both snapshots have the same goals with the same hypotheses
+    fn same_goals(&self, other: &Snapshot) -> bool {
+        self.frames.len() == other.frames.len()
+            && self
+                .frames
+                .iter()
+                .zip(&other.frames)
+                .all(|(a, b)| a.goal == b.goal && a.hyps == b.hyps)
+    }
+
+    fn repeat(&self, script: &Script, limit: usize) -> Result<Self> {
+        let next = match self.run_script(script) {
+            Ok(next) if !next.same_goals(self) => next,
+            _ => return Ok(self.clone()),
+        };
+        if limit == 0 {
+            return Ok(next);
+        }
+        self.on_generated(next, |x| x.repeat(script, limit - 1))
+    }
+
+    pub(crate) fn run_script(&self, script: &Script) -> Result<Self> {
+        match script {
+            Atom(line) => self.run_atom(line),
+            Try(t) => Ok(self.run_script(t).unwrap_or_else(|_| self.clone())),
+            Repeat(t) => self.repeat(t, REPEAT_LIMIT),
+            Progress(t) => {
+                let next = self.run_script(t)?;
+                if next.same_goals(self) {
+                    return Err(NoProgress);
+                }
+                Ok(next)
+            }
+            Do(0, _) => Ok(self.clone()),
+            Do(n, t) => {
+                let next = self.run_script(t)?;
+                self.on_generated(next, |x| x.run_script(&Do(n - 1, t.clone())))
+            }
+            First(ts) => {
+                let mut error = EmptyTactic;
+                for t in ts {
+                    match self.run_script(t) {
+                        Ok(next) => return Ok(next),
+                        Err(e) => error = e,
+                    }
+                }
+                Err(error)
+            }
+            Then(a, b) => {
+                let next = self.run_script(a)?;
+                self.on_generated(next, |x| x.run_script(b))
+            }
+            OrElse(a, b) => self.run_script(a).or_else(|_| self.run_script(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        replace_args, tactic_heads,
+        Script::{self, *},
+    };
+    use crate::interactive::tests::{run_interactive_to_end, run_interactive_to_fail};
+
+    fn atom(x: &str) -> Box<Script> {
+        Box::new(Atom(x.to_string()))
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Script::parse("intros; try (apply (f x)) || lia").unwrap(),
+            Then(
+                atom("intros"),
+                Box::new(OrElse(Box::new(Try(atom("apply (f x)"))), atom("lia")))
+            )
+        );
+        assert_eq!(
+            Script::parse("first [lia | apply (a || b)]; do 2 intros").unwrap(),
+            Then(
+                Box::new(First(vec![
+                    Atom("lia".to_string()),
+                    Atom("apply (a || b)".to_string())
+                ])),
+                Box::new(Do(2, atom("intros")))
+            )
+        );
+        assert_eq!(
+            Script::parse(r#"rewrite (f ')') || apply (g "a;(")"#).unwrap(),
+            OrElse(atom("rewrite (f ')')"), atom(r#"apply (g "a;(")"#))
+        );
+        assert_eq!(
+            Script::parse("first [apply (f '|') | lia]").unwrap(),
+            First(vec![
+                Atom("apply (f '|')".to_string()),
+                Atom("lia".to_string())
+            ])
+        );
+        assert!(Script::parse("intros;").is_err());
+        assert!(Script::parse("first lia").is_err());
+        assert!(Script::parse("do x lia").is_err());
+    }
+
+    #[test]
+    fn macro_args() {
+        let f = |x: &str| match x {
+            "x" => Some("y".to_string()),
+            "H" => Some("G".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            replace_args("apply (λ x: ℤ, x = z) x", &f),
+            "apply (λ x: ℤ, x = z) y"
+        );
+        assert_eq!(
+            replace_args("destruct H with (and_ind ? ?) to (x r)", &f),
+            "destruct G with (and_ind ? ?) to (y r)"
+        );
+        assert_eq!(
+            replace_args("chain (apply H) (intros x)", &f),
+            "chain (apply G) (intros y)"
+        );
+        assert_eq!(
+            tactic_heads("chain (apply foo) (bar x)"),
+            ["chain", "apply", "bar"]
+        );
+    }
+
+    #[test]
+    fn then_all_goals() {
+        run_interactive_to_end(
+            "∀ a b: ℤ, a < b -> a + 1 ≤ b ∧ a < b + 1",
+            "intros; apply and_intro; lia",
+        );
+        run_interactive_to_fail(
+            "∀ P: U, P -> P ∧ 2 < 3",
+            "intros P p",
+            "apply and_intro; lia",
+        );
+        run_interactive_to_end(
+            "∀ P: U, P -> P ∧ 2 < 3",
+            "intros P p; apply and_intro; first [assumption | lia]",
+        );
+    }
+
+    #[test]
+    fn repeat_and_try() {
+        run_interactive_to_end(
+            "∀ A B C: U, A -> B -> C -> (A ∧ B) ∧ C",
+            "intros; repeat apply and_intro; assumption",
+        );
+        run_interactive_to_end(
+            "∀ A B C: U, A -> B -> C -> (A ∧ B) ∧ C",
+            "intros\nrepeat (apply and_intro || assumption)",
+        );
+        run_interactive_to_end(
+            "∀ A: U, A -> A ∧ A",
+            "intros; try lia; apply and_intro; try lia; assumption",
+        );
+    }
+
+    #[test]
+    fn progress_and_do() {
+        run_interactive_to_fail("∀ A: U, A -> A", "", "progress (try lia)");
+        run_interactive_to_end("∀ A: U, A -> A", "progress (try intros)\nassumption");
+        run_interactive_to_end(
+            "∀ A B: U, A -> B -> (A ∧ B) ∧ (A ∧ B)",
+            "intros\ndo 2 apply and_intro; assumption",
+        );
+        run_interactive_to_fail(
+            "∀ A B: U, A -> B -> A ∧ (A ∧ B)",
+            "intros",
+            "do 3 apply and_intro",
+        );
+    }
+}
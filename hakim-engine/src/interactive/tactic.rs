@@ -1,4 +1,7 @@
-use crate::brain::{self, TermRef};
+use crate::{
+    brain::{self, TermRef},
+    report::{Message, Report, TermPrinter},
+};
 
 mod rewrite;
 pub(crate) use rewrite::{replace, rewrite};
@@ -6,9 +9,21 @@ pub(crate) use rewrite::{replace, rewrite};
 mod unfold;
 pub(crate) use unfold::unfold;
 
+mod compute;
+pub(crate) use compute::{compute, simpl};
+
 mod lia;
 pub(crate) use lia::lia;
 
+mod lra;
+pub(crate) use lra::lra;
+
+mod nia;
+pub(crate) use nia::nia;
+
+mod ring;
+pub(crate) use ring::{ring, ring_simplify};
+
 mod intros;
 pub(crate) use intros::intros;
 
@@ -35,21 +50,37 @@ pub enum Error {
     UnknownTactic(String),
     DisabledTactic(String),
     UnknownHyp(String),
+    /// the key of the message, and the hypothesis
     BadHyp(&'static str, TermRef),
+    /// the key of the message
     BadGoal(&'static str),
-    BadArgCount { tactic_name: String },
-    BadArg { tactic_name: String, arg: String },
+    BadArgCount {
+        tactic_name: String,
+    },
+    BadArg {
+        tactic_name: String,
+        arg: String,
+    },
     BrainError(brain::Error),
     CanNotSolve(&'static str),
     CanNotUndo,
     CanNotRedo,
     EmptyTactic,
-    InvalidGoalNumber { i: usize, n: usize },
+    InvalidGoalNumber {
+        i: usize,
+        n: usize,
+    },
     HypIsFromLib(String),
     EngineError(super::Error),
     CanNotFindInstance(Box<FindInstance>),
     ContextDependOnHyp(String, TermRef),
     TermIsNotType(TermRef),
+    NoProgress,
+    NoFocusedGoal,
+    UnfinishedFocus,
+    NoOpenBrace,
+    UnknownHistoryNode(usize),
+    UnfinishedProof,
 }
 
 impl Error {
@@ -60,6 +91,88 @@ impl Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+impl Report for Error {
+    fn code(&self) -> &'static str {
+        match self {
+            UnknownTactic(_) => "E0401",
+            DisabledTactic(_) => "E0402",
+            UnknownHyp(_) => "E0403",
+            BadHyp(..) => "E0404",
+            BadGoal(_) => "E0405",
+            BadArgCount { .. } => "E0406",
+            BadArg { .. } => "E0407",
+            CanNotSolve(_) => "E0408",
+            CanNotUndo => "E0409",
+            CanNotRedo => "E0410",
+            EmptyTactic => "E0411",
+            InvalidGoalNumber { .. } => "E0412",
+            HypIsFromLib(_) => "E0413",
+            CanNotFindInstance(_) => "E0414",
+            ContextDependOnHyp(..) => "E0415",
+            TermIsNotType(_) => "E0416",
+            NoProgress => "E0417",
+            NoFocusedGoal => "E0418",
+            UnfinishedFocus => "E0419",
+            NoOpenBrace => "E0420",
+            UnknownHistoryNode(_) => "E0421",
+            UnfinishedProof => "E0422",
+            BrainError(e) => e.code(),
+            EngineError(e) => e.code(),
+        }
+    }
+
+    fn message(&self, print: TermPrinter) -> Message {
+        let msg = |key, x: &str| Message::new(key, vec![x.to_string()]);
+        match self {
+            UnknownTactic(x) => msg("unknown_tactic", x),
+            DisabledTactic(x) => msg("disabled_tactic", x),
+            UnknownHyp(x) => msg("unknown_hyp", x),
+            BadHyp(key, t) => Message::new(key, vec![print(t)]),
+            BadGoal(key) => Message::new(key, vec![]),
+            BadArgCount { tactic_name } => msg("bad_arg_count", tactic_name),
+            BadArg { tactic_name, arg } => {
+                Message::new("bad_arg", vec![tactic_name.clone(), arg.clone()])
+            }
+            CanNotSolve(x) => msg("can_not_solve", x),
+            CanNotUndo => Message::new("can_not_undo", vec![]),
+            CanNotRedo => Message::new("can_not_redo", vec![]),
+            EmptyTactic => Message::new("empty_tactic", vec![]),
+            InvalidGoalNumber { i, n } => {
+                Message::new("invalid_goal_number", vec![i.to_string(), n.to_string()])
+            }
+            HypIsFromLib(x) => msg("hyp_is_from_lib", x),
+            CanNotFindInstance(e) => Message::new("can_not_find_instance", vec![print(&e.exp())]),
+            ContextDependOnHyp(x, t) => {
+                Message::new("context_depend_on_hyp", vec![x.clone(), print(t)])
+            }
+            TermIsNotType(t) => Message::new("term_is_not_type", vec![print(t)]),
+            NoProgress => Message::new("no_progress", vec![]),
+            NoFocusedGoal => Message::new("no_focused_goal", vec![]),
+            UnfinishedFocus => Message::new("unfinished_focus", vec![]),
+            NoOpenBrace => Message::new("no_open_brace", vec![]),
+            UnknownHistoryNode(x) => Message::new("unknown_history_node", vec![x.to_string()]),
+            UnfinishedProof => Message::new("unfinished_proof", vec![]),
+            BrainError(e) => e.message(print),
+            EngineError(e) => e.message(print),
+        }
+    }
+
+    fn span(&self) -> Option<(usize, usize)> {
+        match self {
+            EngineError(e) => e.span(),
+            _ => None,
+        }
+    }
+
+    fn context(&self, print: TermPrinter) -> Vec<Message> {
+        match self {
+            BrainError(e) => e.context(print),
+            EngineError(e) => e.context(print),
+            _ => vec![],
+        }
+    }
+}
+
 impl From<super::Error> for Error {
     fn from(e: super::Error) -> Self {
         EngineError(e)
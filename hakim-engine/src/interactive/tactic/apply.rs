@@ -1,12 +1,12 @@
 use crate::{
     app_ref,
     brain::{
-        contains_wild, fill_wild, for_each_wild, get_forall_depth,
+        coerce, contains_wild, fill_wild, for_each_wild, get_forall_depth,
         infer::{type_of_and_infer, InferResults, VarCategory},
-        map_reduce_wild, normalize, predict_axiom, subtype_and_infer, Term, TermRef,
+        map_reduce_wild, normalize, predict_axiom, subtype_and_infer, TermRef,
     },
     engine::Engine,
-    interactive::Frame,
+    interactive::{Frame, ProofTerm},
     term_ref,
 };
 
@@ -21,6 +21,11 @@ pub struct FindInstance {
 }
 
 impl FindInstance {
+    /// The applied term, with the known wilds filled
+    pub(crate) fn exp(&self) -> TermRef {
+        self.infer.fill(self.exp.clone())
+    }
+
     pub(crate) fn first_needed_wild(&self) -> usize {
         if let Some(x) = self.infer.unresolved_obligations.get(0) {
             return x.var;
@@ -98,20 +103,23 @@ impl FindInstance {
     }
 }
 
+/// Returns the applied term, alongside with its type and inference results
 fn find_args_in_apply_hyp(
+    engine: &Engine,
     mut func: TermRef,
     op: TermRef,
     base_ic: usize,
     name: &str,
-) -> Option<(TermRef, InferResults)> {
-    let mut global_infers = InferResults::new(base_ic);
+) -> Option<(TermRef, TermRef, InferResults)> {
+    let mut global_infers = engine.infer_results(base_ic);
     let fd = {
         let ty = type_of_and_infer(func.clone(), &mut global_infers).ok()?;
         get_forall_depth(&ty)
     };
     for _ in base_ic..base_ic + fd {
         let mut infers = global_infers.clone();
-        let ty = match type_of_and_infer(app_ref!(func, op), &mut infers) {
+        let term = app_ref!(func, op);
+        let ty = match type_of_and_infer(term.clone(), &mut infers) {
             Ok(x) => x,
             Err(_) => {
                 func = app_ref!(func, global_infers.add_var());
@@ -123,40 +131,56 @@ fn find_args_in_apply_hyp(
             func = app_ref!(func, global_infers.add_var());
             continue;
         }
-        return Some((ty, infers));
+        return Some((term, ty, infers));
     }
     None
 }
 
-fn apply_for_hyp(mut frame: Frame, exp: &str, name: &str) -> Result<Vec<Frame>> {
+fn apply_for_hyp(mut frame: Frame, exp: &str, name: &str) -> Result<(Vec<Frame>, ProofTerm)> {
     let orig_frame = frame.clone();
     let (term, ic) = frame.engine.parse_text_with_wild(exp)?;
     let prev_hyp = frame.remove_hyp_with_name(name)?.ty;
     let op = term_ref!(axiom name, prev_hyp);
-    let (ty, infers) = match find_args_in_apply_hyp(term, op, ic, name) {
+    let (term, ty, mut infers) = match find_args_in_apply_hyp(&frame.engine, term, op, ic, name) {
         Some(x) => x,
         None => return Err(CanNotSolve("apply")),
     };
-    frame.add_hyp_with_name(name, normalize(ty))?;
+    infers.solve_levels()?;
+    let ty = normalize(infers.fill(ty));
+    frame.add_hyp_with_name(name, ty.clone())?;
     let mut fs = vec![frame];
+    let mut holes = vec![];
     for i in 0..infers.n {
         let mut frame = orig_frame.clone();
         if !contains_wild(&infers.terms[i]) {
+            holes.push(None);
             continue;
         }
         if !contains_wild(&infers.tys[i]) {
             frame.goal = normalize(infers.tys[i].clone());
+            holes.push(Some(fs.len()));
             fs.push(frame);
         } else {
             return Err(CanNotSolve("apply_hyp"));
         }
     }
-    Ok(fs)
+    let arg = proof_of_applied_term(infers.fill(term), &holes)?;
+    let proof = ProofTerm::intro(name, ty, ProofTerm::Hole(0)).app(arg);
+    Ok((fs, proof))
 }
 
-fn apply_for_goal(frame: Frame, exp: &str) -> Result<Vec<Frame>> {
+/// Builds the proof of an applied term, which its unresolved term wild `i` is proved
+/// in the goal `holes[i]`.
+fn proof_of_applied_term(term: TermRef, holes: &[Option<usize>]) -> Result<ProofTerm> {
+    ProofTerm::from_term_with_wild(term, &|index| match VarCategory::from(index) {
+        VarCategory::Term(i) => holes.get(i).copied().flatten(),
+        VarCategory::Ty(_) => None,
+    })
+}
+
+fn apply_for_goal(frame: Frame, exp: &str) -> Result<(Vec<Frame>, ProofTerm)> {
     let (term, inf_num) = frame.engine.parse_text_with_wild(exp)?;
-    let ty = type_of_and_infer(term.clone(), &mut InferResults::new(inf_num))?;
+    let ty = type_of_and_infer(term.clone(), &mut frame.engine.infer_results(inf_num))?;
     let goal = frame.goal.clone();
     let d_forall = get_forall_depth(&ty);
     for i in 0..=d_forall {
@@ -173,18 +197,19 @@ fn apply_for_goal(frame: Frame, exp: &str) -> Result<Vec<Frame>> {
 }
 
 fn try_argument_count_for_goal(
-    mut term: std::rc::Rc<Term>,
+    mut term: TermRef,
     d_forall: usize,
     inf_num: usize,
-    goal: std::rc::Rc<Term>,
+    goal: TermRef,
     frame: Frame,
-) -> Result<Vec<Frame>> {
-    let mut infers = InferResults::new(inf_num);
+) -> Result<(Vec<Frame>, ProofTerm)> {
+    let mut infers = frame.engine.infer_results(inf_num);
     for _ in 0..d_forall {
         term = app_ref!(term, infers.add_var());
     }
     let twa_ty = type_of_and_infer(term.clone(), &mut infers)?;
-    subtype_and_infer(twa_ty.clone(), goal, &mut infers)?;
+    let lemma = |name: &str| frame.engine.parse_text(name).ok();
+    let coercion = subtype_and_infer(twa_ty.clone(), goal, &mut infers, &lemma)?;
     if !infers.unresolved_obligations.is_empty() {
         return Err(CanNotFindInstance(Box::new(FindInstance {
             engine: frame.engine,
@@ -193,14 +218,18 @@ fn try_argument_count_for_goal(
             ty: twa_ty,
         })));
     }
+    infers.solve_levels()?;
     let mut v = vec![];
+    let mut holes = vec![];
     for i in 0..infers.n {
         let mut frame = frame.clone();
         if !contains_wild(&infers.terms[i]) {
+            holes.push(None);
             continue;
         }
         if !contains_wild(&infers.tys[i]) {
             frame.goal = normalize(infers.tys[i].clone());
+            holes.push(Some(v.len()));
             v.push(frame);
         } else {
             return Err(CanNotFindInstance(Box::new(FindInstance {
@@ -211,13 +240,14 @@ fn try_argument_count_for_goal(
             })));
         }
     }
-    Ok(v)
+    let proof = proof_of_applied_term(infers.fill(coerce(coercion, term)), &holes)?;
+    Ok((v, proof))
 }
 
 pub(crate) fn apply<'a>(
     frame: Frame,
     mut args: impl Iterator<Item = &'a str>,
-) -> Result<Vec<Frame>> {
+) -> Result<(Vec<Frame>, ProofTerm)> {
     let exp = &next_arg(&mut args, "apply")?;
     if let Some(in_kw) = args.next() {
         if in_kw != "in" {
@@ -361,6 +391,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn infer_two_argument_motive() {
+        run_interactive_to_end(
+            "(∀ P: ℤ → ℤ → U, (∀ a: ℤ, P a a) → ∀ a b: ℤ, P a b ∨ P b a) → ∀ x y: ℤ, x ≤ y ∨ y ≤ x",
+            r#"
+            intros H
+            apply H
+            intros
+            lia
+        "#,
+        );
+    }
+
     #[test]
     fn infer_induction_p() {
         run_interactive(
@@ -427,4 +470,15 @@ mod tests {
             "λ n pf, n * pf",
         );
     }
+
+    #[test]
+    fn apply_stronger_lemma() {
+        run_interactive_to_end("∀ A B: U, A -> A ∨ B", "intros A B H\napply H");
+        run_interactive_to_end("∀ A B: U, B -> A ∨ B", "intros A B H\napply H");
+        run_interactive_to_end("∀ x: ℤ, False -> x = 2", "intros x H\napply H");
+        run_interactive_to_end(
+            "∀ P: ℤ -> U, (∀ x: ℤ, P x) -> P 2 ∨ 2 = 3",
+            "intros P H\napply H",
+        );
+    }
 }
@@ -5,7 +5,7 @@ use super::Result;
 use crate::{
     analysis::logic::{LogicArena, LogicBuilder, LogicValue},
     brain::TermRef,
-    interactive::Frame,
+    interactive::{Frame, ProofTerm},
 };
 #[derive(Debug, Clone)]
 enum PropStatement {
@@ -53,7 +53,7 @@ fn negator(x: PropStatement) -> PropStatement {
         Not(t) => Atom(t),
     }
 }
-pub fn assumption(frame: Frame) -> Result<Vec<Frame>> {
+pub fn assumption(frame: Frame) -> Result<(Vec<Frame>, ProofTerm)> {
     LogicBuilder::build_tactic("assumption", frame, convert, check_contradiction, negator)
 }
 
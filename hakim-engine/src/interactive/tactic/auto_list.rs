@@ -4,7 +4,7 @@ use super::Result;
 use crate::{
     analysis::logic::{LogicArena, LogicBuilder, LogicTree, LogicValue},
     brain::{Term, TermRef},
-    interactive::Frame,
+    interactive::{Frame, ProofTerm},
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -134,7 +134,7 @@ fn negator(x: ListStatement) -> ListStatement {
     }
 }
 
-pub fn auto_list(frame: Frame) -> Result<Vec<Frame>> {
+pub fn auto_list(frame: Frame) -> Result<(Vec<Frame>, ProofTerm)> {
     LogicBuilder::build_tactic("auto_list", frame, convert, check_contradiction, negator)
 }
 
@@ -3,7 +3,7 @@ use crate::{
     analysis::logic::{LogicArena, LogicBuilder, LogicValue},
     app_ref,
     brain::{Term, TermRef},
-    interactive::Frame,
+    interactive::{Frame, ProofTerm},
     library::prelude::set,
     term_ref,
 };
@@ -396,35 +396,45 @@ fn negator(x: EnsembleStatement) -> EnsembleStatement {
     }
 }
 
-fn pre_process_frame(frame: Frame) -> Frame {
+/// Returns the processed frame, alongside with a proof of the original goal
+/// which its hole is the proof of the processed one.
+fn pre_process_frame(frame: Frame) -> (Frame, ProofTerm) {
     let mut intros_flag = false;
+    let mut proof = ProofTerm::Hole(0);
     let frame = match apply(frame.clone(), vec!["included_fold"].into_iter()) {
-        Ok(x) if x.len() == 1 => {
+        Ok((x, p)) if x.len() == 1 => {
             intros_flag = true;
+            proof = proof.fill_last_hole(0, p);
             x.into_iter().next().unwrap()
         }
         _ => frame,
     };
     let frame = match apply(frame.clone(), vec!["set_equality_forall"].into_iter()) {
-        Ok(x) if x.len() == 1 => {
+        Ok((x, p)) if x.len() == 1 => {
             intros_flag = true;
+            proof = proof.fill_last_hole(0, p);
             x.into_iter().next().unwrap()
         }
         _ => frame,
     };
     if intros_flag {
         match intros(frame.clone(), vec![].into_iter()) {
-            Ok(x) if x.len() == 1 => x.into_iter().next().unwrap(),
-            _ => frame,
+            Ok((x, p)) if x.len() == 1 => {
+                proof = proof.fill_last_hole(0, p);
+                (x.into_iter().next().unwrap(), proof)
+            }
+            _ => (frame, proof),
         }
     } else {
-        frame
+        (frame, proof)
     }
 }
 
-pub fn auto_set(frame: Frame) -> Result<Vec<Frame>> {
-    let frame = pre_process_frame(frame);
-    LogicBuilder::build_tactic("auto_set", frame, convert, check_contradiction, negator)
+pub fn auto_set(frame: Frame) -> Result<(Vec<Frame>, ProofTerm)> {
+    let (frame, proof) = pre_process_frame(frame);
+    let (frames, oracle) =
+        LogicBuilder::build_tactic("auto_set", frame, convert, check_contradiction, negator)?;
+    Ok((frames, proof.fill_last_hole(0, oracle)))
 }
 
 #[cfg(test)]
@@ -1,5 +1,5 @@
 use super::{next_arg, next_arg_constant, Result};
-use crate::interactive::Frame;
+use crate::interactive::{Frame, ProofTerm};
 
 fn eat_paren(mut x: &str) -> &str {
     if let Some(a) = x.strip_prefix('(') {
@@ -10,25 +10,34 @@ fn eat_paren(mut x: &str) -> &str {
     x
 }
 
-pub(crate) fn chain<'a>(frame: Frame, args: impl Iterator<Item = &'a str>) -> Result<Vec<Frame>> {
+pub(crate) fn chain<'a>(
+    frame: Frame,
+    args: impl Iterator<Item = &'a str>,
+) -> Result<(Vec<Frame>, ProofTerm)> {
     let mut frames = vec![frame];
+    let mut proof = ProofTerm::Hole(0);
     for arg in args {
         let arg = eat_paren(arg);
-        frames = frames
+        let results = frames
             .into_iter()
-            .map(|x| x.run_tactic(arg))
-            .collect::<Result<Vec<Vec<_>>>>()?
-            .into_iter()
-            .flat_map(|x| x.into_iter())
-            .collect();
+            .map(|x| x.run_tactic_with_proof(arg))
+            .collect::<Result<Vec<_>>>()?;
+        let mut subproofs = vec![];
+        frames = vec![];
+        for (new_frames, subproof) in results {
+            let offset = frames.len();
+            subproofs.push(subproof.fill_holes(&|i| ProofTerm::Hole(i + offset)));
+            frames.extend(new_frames);
+        }
+        proof = proof.fill_holes(&|i| subproofs[i].clone());
     }
-    Ok(frames)
+    Ok((frames, proof))
 }
 
 pub(crate) fn destruct<'a>(
     frame: Frame,
     args: impl Iterator<Item = &'a str>,
-) -> Result<Vec<Frame>> {
+) -> Result<(Vec<Frame>, ProofTerm)> {
     let args = &mut args.peekable();
     let tactic_name = "destruct";
     let hyp = next_arg(args, tactic_name)?;
@@ -40,7 +49,7 @@ pub(crate) fn destruct<'a>(
         next_arg_constant(args, tactic_name, "to")?;
         eat_paren(next_arg(args, tactic_name)?)
     };
-    frame.run_tactic(&format!(
+    frame.run_tactic_with_proof(&format!(
         "chain (apply ({} {})) (remove_hyp {}) (intros {})",
         lem, hyp, hyp, var
     ))
@@ -0,0 +1,118 @@
+use super::{get_one_arg, Error::BadArg, Result};
+use crate::{
+    brain::{compute::compute as evaluate, TermRef},
+    interactive::{Frame, ProofTerm},
+    term_ref,
+};
+
+/// Evaluates the builtin functions on literals in the goal, or in a hypothesis
+/// with `simpl in H`.
+pub(crate) fn simpl<'a>(
+    frame: Frame,
+    args: impl Iterator<Item = &'a str>,
+) -> Result<(Vec<Frame>, ProofTerm)> {
+    convert(frame, args, "simpl", |_, t| evaluate(t))
+}
+
+/// Like `simpl`, but also expands every definition.
+pub(crate) fn compute<'a>(
+    frame: Frame,
+    args: impl Iterator<Item = &'a str>,
+) -> Result<(Vec<Frame>, ProofTerm)> {
+    convert(frame, args, "compute", |frame, t| {
+        evaluate(frame.engine.expand_definitions(t))
+    })
+}
+
+/// The kernel expands definitions and evaluates literals when it checks a proof,
+/// so the converted goal has the same proofs as the original one.
+fn convert<'a>(
+    mut frame: Frame,
+    mut args: impl Iterator<Item = &'a str>,
+    tactic_name: &'static str,
+    f: impl Fn(&Frame, TermRef) -> TermRef,
+) -> Result<(Vec<Frame>, ProofTerm)> {
+    match args.next() {
+        None => {
+            frame.goal = f(&frame, frame.goal.clone());
+            Ok((vec![frame], ProofTerm::Hole(0)))
+        }
+        Some("in") => {
+            let name = get_one_arg(args, tactic_name)?;
+            let hyp = frame.remove_hyp_with_name(name)?.ty;
+            let ty = f(&frame, hyp.clone());
+            frame.add_hyp_with_name(name, ty.clone())?;
+            let proof = ProofTerm::intro(name, ty, ProofTerm::Hole(0))
+                .app(ProofTerm::Term(term_ref!(axiom name, hyp)));
+            Ok((vec![frame], proof))
+        }
+        Some(arg) => Err(BadArg {
+            tactic_name: tactic_name.to_string(),
+            arg: arg.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interactive::tests::{run_interactive_to_end, run_interactive_to_fail};
+
+    #[test]
+    fn literals() {
+        run_interactive_to_end("2 ^ 10 = 1024", "simpl\napply eq_refl");
+        run_interactive_to_end("17 mod 5 = 2 ∧ -7 mod 5 = 3", "simpl\nlia");
+        run_interactive_to_end("|[1, 2] ++ [3]| = 3", "simpl\napply eq_refl");
+        run_interactive_to_end("cnt 2 [1, 2, 2] = |-2|", "simpl\napply eq_refl");
+        run_interactive_to_end(
+            "∀ a b: ℤ, proj2 (proj1 (a, b), a) = a",
+            "intros\nsimpl\napply eq_refl",
+        );
+    }
+
+    #[test]
+    fn characters() {
+        // nothing says that the codes of characters wrap around
+        run_interactive_to_fail("chr 353 = chr 97", "simpl", "apply eq_refl");
+        run_interactive_to_end(r#"cnt 'a' "salam" = 2"#, "simpl\napply eq_refl");
+    }
+
+    #[test]
+    fn negative_power() {
+        // `lia` and the kernel must not disagree on what `1 ^ (-1)` is
+        run_interactive_to_fail(
+            "False",
+            r#"
+            add_hyp (1 ^ (-1) = 1)
+            lia
+            compute in H
+            "#,
+            "lia",
+        );
+    }
+
+    #[test]
+    fn in_hyp() {
+        run_interactive_to_end(
+            "∀ x: ℤ, x = 2 * 3 + 1 -> x = 7",
+            r#"
+            intros x H
+            simpl in H
+            assumption
+        "#,
+        );
+        run_interactive_to_fail("∀ x: ℤ, x = 7 -> x = 7", "intros x H", "simpl in H2");
+        run_interactive_to_fail("2 = 2", "", "simpl on");
+    }
+
+    #[test]
+    fn unfold_definitions() {
+        run_interactive_to_end(
+            "∀ x: ℤ, prime x -> 1 < x ∧ (∀ y: ℤ, 0 < y -> y | x -> y = 1 ∨ y = x)",
+            r#"
+            compute
+            intros
+            assumption
+        "#,
+        );
+    }
+}
@@ -1,14 +1,14 @@
 use super::{get_one_arg, next_arg, next_arg_constant, Error, Result};
 use crate::{
     brain::{fill_axiom, type_of, Abstraction, Term, TermRef},
-    interactive::Frame,
+    interactive::{Frame, ProofTerm},
     term_ref,
 };
 
 pub fn add_from_lib<'a>(
     mut frame: Frame,
     args: impl Iterator<Item = &'a str>,
-) -> Result<Vec<Frame>> {
+) -> Result<(Vec<Frame>, ProofTerm)> {
     let name = get_one_arg(args, "add_hyp")?;
     let ty = frame.engine.type_of_name(name)?;
     frame.hyps.push_back(crate::interactive::Hyp {
@@ -16,10 +16,13 @@ pub fn add_from_lib<'a>(
         name: name.to_string(),
         from_lib: true,
     });
-    Ok(vec![frame])
+    Ok((vec![frame], ProofTerm::Hole(0)))
 }
 
-pub fn add_hyp<'a>(mut frame: Frame, args: impl Iterator<Item = &'a str>) -> Result<Vec<Frame>> {
+pub fn add_hyp<'a>(
+    mut frame: Frame,
+    args: impl Iterator<Item = &'a str>,
+) -> Result<(Vec<Frame>, ProofTerm)> {
     let mut args = args.peekable();
     let exp = next_arg(&mut args, "add_hyp")?;
     if args.peek().is_some() {
@@ -27,9 +30,10 @@ pub fn add_hyp<'a>(mut frame: Frame, args: impl Iterator<Item = &'a str>) -> Res
         let name = exp;
         let term = next_arg(&mut args, "add_hyp")?;
         let term = frame.engine.parse_text(term)?;
-        let ty = type_of(term)?;
-        frame.add_hyp_with_name(name, ty)?;
-        return Ok(vec![frame]);
+        let ty = type_of(term.clone())?;
+        frame.add_hyp_with_name(name, ty.clone())?;
+        let proof = ProofTerm::intro(name, ty, ProofTerm::Hole(0)).app(ProofTerm::Term(term));
+        return Ok((vec![frame], proof));
     }
     let term = frame.engine.parse_text(exp)?;
     let ty = type_of(term.clone())?;
@@ -37,18 +41,26 @@ pub fn add_hyp<'a>(mut frame: Frame, args: impl Iterator<Item = &'a str>) -> Res
         return Err(Error::TermIsNotType(term));
     }
     let mut frame2 = frame.clone();
-    frame.add_hyp_with_name(&frame.engine.generate_name("H"), term.clone())?;
-    frame2.goal = term;
-    Ok(vec![frame, frame2])
+    let name = frame.engine.generate_name("H");
+    frame.add_hyp_with_name(&name, term.clone())?;
+    frame2.goal = term.clone();
+    let proof = ProofTerm::intro(&name, term, ProofTerm::Hole(0)).app(ProofTerm::Hole(1));
+    Ok((vec![frame, frame2], proof))
 }
 
-pub fn remove_hyp<'a>(mut frame: Frame, args: impl Iterator<Item = &'a str>) -> Result<Vec<Frame>> {
+pub fn remove_hyp<'a>(
+    mut frame: Frame,
+    args: impl Iterator<Item = &'a str>,
+) -> Result<(Vec<Frame>, ProofTerm)> {
     let exp = get_one_arg(args, "remove_hyp")?;
     frame.remove_hyp_with_name(exp)?;
-    Ok(vec![frame])
+    Ok((vec![frame], ProofTerm::Hole(0)))
 }
 
-pub fn revert_hyp<'a>(mut frame: Frame, args: impl Iterator<Item = &'a str>) -> Result<Vec<Frame>> {
+pub fn revert_hyp<'a>(
+    mut frame: Frame,
+    args: impl Iterator<Item = &'a str>,
+) -> Result<(Vec<Frame>, ProofTerm)> {
     let exp = get_one_arg(args, "revert")?;
     let ty = frame
         .get_hyp_by_name(exp)
@@ -65,11 +77,12 @@ pub fn revert_hyp<'a>(mut frame: Frame, args: impl Iterator<Item = &'a str>) ->
     });
     frame.goal = TermRef::new(Term::Forall(Abstraction {
         body: goal,
-        var_ty: ty,
+        var_ty: ty.clone(),
         hint_name: Some(exp.to_string()),
     }));
     frame.remove_hyp_with_name(exp)?;
-    Ok(vec![frame])
+    let proof = ProofTerm::Hole(0).app(ProofTerm::Term(term_ref!(axiom exp, ty)));
+    Ok((vec![frame], proof))
 }
 
 #[cfg(test)]
@@ -1,12 +1,12 @@
 use crate::{
-    brain::{subst, Term},
-    interactive::Frame,
+    brain::{subst, Term, TermRef},
+    interactive::{Frame, ProofTerm},
     term_ref, Abstraction,
 };
 
 use super::{Error::*, Result};
 
-pub fn intros_one(frame: &mut Frame, name: &str) -> Result<()> {
+pub fn intros_one(frame: &mut Frame, name: &str) -> Result<(String, TermRef)> {
     let goal = frame.goal.clone();
     match goal.as_ref() {
         Term::Forall(Abstraction {
@@ -16,14 +16,18 @@ pub fn intros_one(frame: &mut Frame, name: &str) -> Result<()> {
         }) => {
             frame.add_hyp_with_name(name, var_ty.clone())?;
             frame.goal = subst(body.clone(), term_ref!(axiom name, var_ty));
-            Ok(())
+            Ok((name.to_string(), var_ty.clone()))
         }
-        _ => Err(BadGoal("intros expects forall")),
+        _ => Err(BadGoal("intros_expects_forall")),
     }
 }
 
-pub fn intros<'a>(mut frame: Frame, args: impl Iterator<Item = &'a str>) -> Result<Vec<Frame>> {
+pub fn intros<'a>(
+    mut frame: Frame,
+    args: impl Iterator<Item = &'a str>,
+) -> Result<(Vec<Frame>, ProofTerm)> {
     let mut args = args.peekable();
+    let mut introduced = vec![];
     if args.peek().is_none() {
         while let Term::Forall(Abstraction {
             var_ty,
@@ -38,13 +42,20 @@ pub fn intros<'a>(mut frame: Frame, args: impl Iterator<Item = &'a str>) -> Resu
             };
             frame.add_hyp_with_name(&name, var_ty.clone())?;
             frame.goal = subst(body.clone(), term_ref!(axiom name, var_ty));
+            introduced.push((name, var_ty.clone()));
         }
     } else {
         for name in args {
-            intros_one(&mut frame, name)?;
+            introduced.push(intros_one(&mut frame, name)?);
         }
     }
-    Ok(vec![frame])
+    let proof = introduced
+        .into_iter()
+        .rev()
+        .fold(ProofTerm::Hole(0), |body, (name, ty)| {
+            ProofTerm::intro(&name, ty, body)
+        });
+    Ok((vec![frame], proof))
 }
 
 #[cfg(test)]
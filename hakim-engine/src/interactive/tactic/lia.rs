@@ -1,35 +1,24 @@
-use num_bigint::Sign;
+use num_bigint::{BigInt, Sign};
 
-use super::Result;
+use super::{Error::CanNotSolve, Result};
 use crate::{
     analysis::{
         arith::{LinearPoly, Poly},
-        logic::{LogicArena, LogicBuilder, LogicValue},
+        farkas::{find_certificate, Step},
+        logic::{LogicArena, LogicValue},
+        proof::{le_z, lt_z, spine, Eqn, Prover},
+        refute::{as_not, Assumption, Refuter, Theory},
+        ring::{Normal, Ring},
     },
-    brain::{detect_len, Term, TermRef},
-    interactive::Frame,
+    app_ref,
+    brain::{definitely_inequal, detect_len, normalize, Term, TermRef},
+    interactive::{Frame, ProofTerm},
+    library::prelude::{eq, false_ty, minus, mult, or, plus, z},
     parser::BinOp,
     term_ref,
 };
 
-use minilp::{ComparisonOp, OptimizationDirection, Problem};
-
-fn convert_calculator_mode(term: TermRef, arena: LogicArena<'_, Poly>) -> LogicValue<'_, Poly> {
-    let r = convert(term, arena);
-    fn is_good(x: &LogicValue<'_, Poly>) -> bool {
-        match x {
-            LogicValue::Exp(_) => false,
-            LogicValue::True | LogicValue::False => true,
-        }
-    }
-    if is_good(&r) {
-        r
-    } else {
-        LogicValue::unknown()
-    }
-}
-
-fn convert(term: TermRef, arena: LogicArena<'_, Poly>) -> LogicValue<'_, Poly> {
+pub(super) fn convert(term: TermRef, arena: LogicArena<'_, Poly>) -> LogicValue<'_, Poly> {
     if let Term::App { func, op: op2 } = term.as_ref() {
         if let Term::App { func, op: op1 } = func.as_ref() {
             if let Term::App { func, op: _ } = func.as_ref() {
@@ -69,7 +58,7 @@ fn convert(term: TermRef, arena: LogicArena<'_, Poly>) -> LogicValue<'_, Poly> {
     LogicValue::unknown()
 }
 
-fn inject_conditions(polies: Vec<Poly>) -> Vec<Poly> {
+pub(super) fn inject_conditions(polies: Vec<Poly>) -> Vec<Poly> {
     let m1 = -1;
     let m1 = term_ref!(n m1);
     let div_mods = polies
@@ -89,6 +78,12 @@ fn inject_conditions(polies: Vec<Poly>) -> Vec<Poly> {
                 }
                 .into_iter();
             }
+            if let Term::App { func, .. } = x.as_ref() {
+                if matches!(func.as_ref(), Term::Axiom { unique_name, .. } if unique_name == "z_of_n")
+                {
+                    return vec![Poly::from_subtract(x.clone(), m1.clone())].into_iter();
+                }
+            }
             if let Some((_, BinOp::ModOf, b)) = BinOp::detect(x) {
                 if let Term::Number { value: bval } = b.as_ref() {
                     if bval.sign() == Sign::Plus {
@@ -106,83 +101,388 @@ fn inject_conditions(polies: Vec<Poly>) -> Vec<Poly> {
     [div_mods, polies].concat()
 }
 
-fn check_contradiction_lp(var_cnt: usize, linear_polies: &[LinearPoly]) -> bool {
-    let bounded = |v, minmax| {
-        let mut problem = Problem::new(OptimizationDirection::Maximize);
-        let vars = (0..var_cnt)
-            .map(|x| {
-                problem.add_var(
-                    if x == v { minmax } else { 0. },
-                    (-f64::INFINITY, f64::INFINITY),
-                )
-            })
-            .collect::<Vec<_>>();
-        for poly in linear_polies {
-            let x = poly
-                .variables()
-                .iter()
-                .map(|(x, c)| {
-                    let t: i32 = x.try_into().ok()?;
-                    Some((vars[*c], f64::from(t)))
-                })
-                .collect::<Option<Vec<_>>>();
-            let x = match x {
-                Some(x) => x,
-                None => continue,
-            };
-            let c: i32 = match poly.constant().try_into() {
-                Ok(x) => x,
-                Err(_) => continue,
-            };
-            let c = -c + 1;
-            problem.add_constraint(&x, ComparisonOp::Ge, f64::from(c))
+pub(super) fn negator(mut poly: Poly) -> Poly {
+    poly.negate();
+    poly.add(1.into());
+    poly
+}
+
+/// The literals which `lia` understands
+enum Literal {
+    Lt(TermRef, TermRef),
+    /// `a ≤ b`, which is `a < b ∨ a = b`
+    Le(TermRef, TermRef),
+    Eq(TermRef, TermRef, TermRef),
+}
+
+impl Literal {
+    fn detect(prop: &TermRef) -> Option<Self> {
+        match spine(prop)? {
+            ("lt", args) if args.len() == 2 => Some(Literal::Lt(args[0].clone(), args[1].clone())),
+            ("eq", args) if args.len() == 3 => Some(Literal::Eq(
+                args[0].clone(),
+                args[1].clone(),
+                args[2].clone(),
+            )),
+            ("or", args) if args.len() == 2 => match (spine(&args[0])?, spine(&args[1])?) {
+                (("lt", lt), ("eq", eq)) if eq[0] == z() && lt[..] == eq[1..] => {
+                    Some(Literal::Le(lt[0].clone(), lt[1].clone()))
+                }
+                _ => None,
+            },
+            _ => None,
         }
-        match problem.solve() {
-            Ok(x) => Some(x[vars[v]]),
-            Err(minilp::Error::Infeasible) => None,
-            Err(_) => Some(minmax * f64::INFINITY),
+    }
+
+    /// The sides of the integer literals
+    fn sides(&self) -> Option<(&TermRef, &TermRef)> {
+        match self {
+            Literal::Lt(a, b) | Literal::Le(a, b) => Some((a, b)),
+            Literal::Eq(ty, a, b) => (*ty == z()).then_some((a, b)),
         }
-    };
-    for i in 0..var_cnt {
-        let lb = bounded(i, -1.);
-        let ub = bounded(i, 1.);
-        match (lb, ub) {
-            (Some(lb), Some(ub)) => {
-                if lb.ceil() > ub.floor() {
-                    return true;
+    }
+}
+
+/// The bound `0 ≤ p` in the normal form, with its proof
+#[derive(Clone)]
+struct Bound {
+    normal: Normal,
+    proof: TermRef,
+}
+
+impl Bound {
+    fn prop(&self) -> TermRef {
+        le_z(term_ref!(n 0), self.normal.term())
+    }
+}
+
+/// The fact `0 ≤ t`, with its proof, which is normalized only if a certificate uses it
+fn fact(t: TermRef, proof: TermRef) -> Assumption<TermRef> {
+    Assumption::Fact(t.clone(), le_z(term_ref!(n 0), t), proof)
+}
+
+fn sub(a: TermRef, b: TermRef) -> TermRef {
+    app_ref!(minus(), a, b)
+}
+
+fn num(n: &BigInt) -> TermRef {
+    term_ref!(n n.clone())
+}
+
+/// Linear arithmetic over integers, which its literals are the bounds `0 ≤ t` and
+/// refutes them with the certificates of `find_certificate`
+struct Lia<'a> {
+    prover: &'a Prover<'a>,
+    ring: Ring<'a>,
+    /// only the literals without variable are understood in the calculator mode
+    calculator: bool,
+}
+
+impl<'a> Lia<'a> {
+    /// The bound `0 ≤ t` from its proof
+    fn bound(&self, t: &TermRef, proof: TermRef) -> Bound {
+        let (normal, e) = self.ring.normalize(t);
+        let proof = self.transport(&e, proof);
+        Bound { normal, proof }
+    }
+
+    /// The proof of `0 ≤ rhs` from the proof of `0 ≤ lhs`
+    fn transport(&self, e: &Eqn, proof: TermRef) -> TermRef {
+        self.prover.rewrite(e, proof, |w| le_z(term_ref!(n 0), w))
+    }
+
+    /// The proof of `0 ≤ a - a`
+    fn zero_le_diff(&self, a: &TermRef) -> TermRef {
+        let e = self.ring.identity([a.clone()], |[x]| {
+            (sub(x.clone(), x.clone()), term_ref!(n 0))
+        });
+        self.transport(&self.prover.sym(e), self.prover.nonneg(&0.into()))
+    }
+
+    /// The proof of `0 ≤ b - a` from the proof of `a = b`
+    fn eq_bound(&self, ty: &TermRef, a: &TermRef, b: &TermRef, proof: TermRef) -> TermRef {
+        let e = Eqn::new(ty.clone(), a.clone(), b.clone(), proof);
+        self.prover.rewrite(&e, self.zero_le_diff(a), |w| {
+            le_z(term_ref!(n 0), sub(w, a.clone()))
+        })
+    }
+
+    /// The fact `0 ≤ b - a - 1` from the proof of `a < b`
+    fn lt_fact(&self, a: &TermRef, b: &TermRef, proof: TermRef) -> (TermRef, TermRef) {
+        let d = sub(b.clone(), a.clone());
+        let proof = self.prover.apply("ℤ_lt_sub", [a.clone(), b.clone(), proof]);
+        let proof = self.prover.apply("ℤ_lt_le", [d.clone(), proof]);
+        (sub(d, term_ref!(n 1)), proof)
+    }
+
+    /// The fact `0 ≤ b - a` from the proof of `a ≤ b`
+    fn le_fact(&self, a: &TermRef, b: &TermRef, proof: TermRef) -> (TermRef, TermRef) {
+        let p = self.prover;
+        let d = sub(b.clone(), a.clone());
+        let from_lt = p.abstract_over(lt_z(a.clone(), b.clone()), |h| {
+            let (d_minus_one, le) = self.lt_fact(a, b, h);
+            let one = term_ref!(n 1);
+            let le = p.apply("ℤ_le_plus", [d_minus_one, one, le, p.nonneg(&1.into())]);
+            let e = self.ring.identity([d.clone()], |[x]| {
+                (
+                    app_ref!(plus(), sub(x.clone(), term_ref!(n 1)), term_ref!(n 1)),
+                    x.clone(),
+                )
+            });
+            self.transport(&e, le)
+        });
+        let from_eq = p.abstract_over(app_ref!(eq(), z(), a, b), |h| self.eq_bound(&z(), a, b, h));
+        let lt = lt_z(a.clone(), b.clone());
+        let eq = app_ref!(eq(), z(), a, b);
+        let le = le_z(term_ref!(n 0), d.clone());
+        let proof = p.apply("or_elim", [lt, eq, proof, le, from_lt, from_eq]);
+        (d, proof)
+    }
+
+    /// `a = b`, if the sides are the same after canonicalizing their integer arguments
+    fn same(&self, ty: &TermRef, a: &TermRef, b: &TermRef) -> Option<Eqn> {
+        let ea = self.ring.canonical_term(ty, a);
+        let eb = self.ring.canonical_term(ty, b);
+        (ea.rhs == eb.rhs).then(|| self.prover.trans(ea, self.prover.sym(eb)))
+    }
+
+    /// The facts which hold for the atoms, like `0 ≤ a mod 2 < 2`
+    fn conditions(&self, atoms: Vec<TermRef>) -> Vec<(TermRef, TermRef)> {
+        let p = self.prover;
+        let mut r = vec![];
+        for x in atoms {
+            match spine(&x) {
+                Some(("len1", args)) if args.len() == 2 => {
+                    if let Some(("list", ty)) = spine(&args[0]) {
+                        let proof = p.apply("ℤ_len_nonneg", [ty[0].clone(), args[1].clone()]);
+                        r.push((x, proof));
+                    }
+                }
+                Some(("z_of_n", args)) if args.len() == 1 => {
+                    r.push((x, p.apply("ℤ_of_n_nonneg", args)));
                 }
+                Some(("mod_of", args)) if args.len() == 2 => {
+                    let Term::Number { value } = args[1].as_ref().clone() else {
+                        continue;
+                    };
+                    if value.sign() != Sign::Plus {
+                        continue;
+                    }
+                    let [a, b]: [TermRef; 2] = args.try_into().unwrap();
+                    let nonneg = p.apply("ℤ_mod_nonneg", [a.clone(), b.clone(), p.pos(&value)]);
+                    let lt = p.apply("ℤ_mod_lt", [a, b.clone(), p.pos(&value)]);
+                    r.push((x.clone(), nonneg));
+                    r.push(self.lt_fact(&x, &b, lt));
+                }
+                _ => (),
             }
-            _ => return true,
         }
+        r
+    }
+
+    /// `0 ≤ a * x + b * y` of two bounds and non negative `a` and `b`
+    fn combine(&self, a: &BigInt, x: &Bound, b: &BigInt, y: &Bound) -> Bound {
+        let p = self.prover;
+        let (xt, yt) = (x.normal.term(), y.normal.term());
+        let (ax, by) = (app_ref!(mult(), num(a), xt), app_ref!(mult(), num(b), yt));
+        let px = p.apply("ℤ_le_mult", [num(a), xt, p.nonneg(a), x.proof.clone()]);
+        let py = p.apply("ℤ_le_mult", [num(b), yt, p.nonneg(b), y.proof.clone()]);
+        let proof = p.apply("ℤ_le_plus", [ax.clone(), by.clone(), px, py]);
+        self.bound(&app_ref!(plus(), ax, by), proof)
+    }
+
+    /// The bound divided by the gcd `g` of its coefficients. Its negation `0 ≤ -d - 1`
+    /// times `g` plus the bound is a negative constant.
+    fn tighten(&self, x: &Bound) -> Option<Bound> {
+        let p = self.prover;
+        let (g, d) = x.normal.tightened()?;
+        let zero = term_ref!(n 0);
+        let dt = d.term();
+        let goal = le_z(zero.clone(), dt.clone());
+        let absurd = p.abstract_over(implies_false(goal.clone()), |h| {
+            let neg = sub(zero.clone(), dt.clone());
+            let lt = p.apply("ℤ_not_le", [zero.clone(), dt.clone(), h]);
+            let le = p.apply("ℤ_lt_le", [neg.clone(), lt]);
+            let neg = Bound {
+                normal: d.clone(),
+                proof: le,
+            };
+            let neg_t = sub(sub(zero.clone(), dt.clone()), term_ref!(n 1));
+            let scaled = app_ref!(mult(), num(&g), neg_t.clone());
+            let scaled_proof = p.apply("ℤ_le_mult", [num(&g), neg_t, p.nonneg(&g), neg.proof]);
+            let xt = x.normal.term();
+            let sum = p.apply(
+                "ℤ_le_plus",
+                [xt.clone(), scaled.clone(), x.proof.clone(), scaled_proof],
+            );
+            let sum = self.bound(&app_ref!(plus(), xt, scaled), sum);
+            let c = sum.normal.as_constant().expect("the sum is a constant");
+            p.negative_nonneg(c, sum.proof)
+        });
+        Some(Bound {
+            normal: d,
+            proof: p.apply("by_contra", [goal, absurd]),
+        })
     }
-    false
 }
 
-fn check_contradiction(polies: &[Poly]) -> bool {
-    let polies = &inject_conditions(polies.to_vec());
-    let (var_cnt, linear_polies) = LinearPoly::from_slice(polies);
-    check_contradiction_lp(var_cnt, &linear_polies)
+fn implies_false(t: TermRef) -> TermRef {
+    crate::analysis::proof::not(t)
 }
 
-fn negator(mut poly: Poly) -> Poly {
-    poly.negate();
-    poly.add(1.into());
-    poly
+impl Theory for Lia<'_> {
+    type Fact = TermRef;
+
+    fn understands(&self, prop: &TermRef) -> bool {
+        let literal = Literal::detect(prop).or_else(|| Literal::detect(&as_not(prop)?));
+        let Some(literal) = literal else {
+            return false;
+        };
+        match literal.sides() {
+            Some((a, b)) => {
+                !self.calculator
+                    || Poly::from_subtract(b.clone(), a.clone())
+                        .variables()
+                        .is_empty()
+            }
+            None => match literal {
+                Literal::Eq(ty, a, b) => {
+                    self.same(&ty, &a, &b).is_some() || definitely_inequal(&a, &b)
+                }
+                _ => false,
+            },
+        }
+    }
+
+    fn assume(&self, prop: &TermRef, proof: TermRef) -> Option<Vec<Assumption<TermRef>>> {
+        if !self.understands(prop) {
+            return None;
+        }
+        let p = self.prover;
+        if let Some(literal) = Literal::detect(prop) {
+            return Some(match literal {
+                Literal::Lt(a, b) => {
+                    let (t, proof) = self.lt_fact(&a, &b, proof);
+                    vec![fact(t, proof)]
+                }
+                Literal::Le(a, b) => {
+                    let (t, proof) = self.le_fact(&a, &b, proof);
+                    vec![fact(t, proof)]
+                }
+                Literal::Eq(ty, a, b) if ty == z() => {
+                    let e = Eqn::new(z(), a.clone(), b.clone(), proof.clone());
+                    let ab = p.rewrite(&e, self.zero_le_diff(&a), |w| {
+                        le_z(term_ref!(n 0), sub(a.clone(), w))
+                    });
+                    vec![
+                        fact(sub(b.clone(), a.clone()), self.eq_bound(&ty, &a, &b, proof)),
+                        fact(sub(a, b), ab),
+                    ]
+                }
+                Literal::Eq(ty, a, b) if self.same(&ty, &a, &b).is_none() => {
+                    let absurd = app_ref!(self.ring.literal_neq(&ty, &a, &b), proof);
+                    vec![Assumption::Prop(false_ty(), absurd)]
+                }
+                Literal::Eq(..) => vec![],
+            });
+        }
+        let literal = Literal::detect(&as_not(prop)?)?;
+        Some(match literal {
+            Literal::Lt(a, b) => {
+                let proof = p.apply("ℤ_not_lt", [a.clone(), b.clone(), proof]);
+                vec![fact(sub(a, b), proof)]
+            }
+            Literal::Le(a, b) => {
+                let lt = p.apply("ℤ_not_le", [a.clone(), b.clone(), proof]);
+                let d = sub(a, b);
+                let proof = p.apply("ℤ_lt_le", [d.clone(), lt]);
+                vec![fact(sub(d, term_ref!(n 1)), proof)]
+            }
+            Literal::Eq(ty, a, b) => match self.same(&ty, &a, &b) {
+                Some(e) => vec![Assumption::Prop(false_ty(), app_ref!(proof, p.proof(&e)))],
+                None if ty == z() => {
+                    let prop =
+                        app_ref!(or(), lt_z(a.clone(), b.clone()), lt_z(b.clone(), a.clone()));
+                    vec![Assumption::Prop(prop, p.apply("ℤ_lt_total", [a, b, proof]))]
+                }
+                None => vec![],
+            },
+        })
+    }
+
+    fn refute(&self, facts: &[(TermRef, TermRef)]) -> Option<TermRef> {
+        let polies: Vec<Poly> = facts.iter().map(|x| Poly::from(x.0.clone())).collect();
+        let mut atoms: Vec<TermRef> = polies
+            .iter()
+            .flat_map(|x| x.variables().iter().flat_map(|x| x.1.iter().cloned()))
+            .collect();
+        atoms.sort();
+        atoms.dedup();
+        let conditions = self.conditions(atoms);
+        let polies: Vec<Poly> = conditions
+            .iter()
+            .map(|x| Poly::from(x.0.clone()))
+            .chain(polies)
+            .map(|mut poly| {
+                // the fact `0 ≤ p` is the hyp `0 < p + 1` of the certificate
+                poly.add(1.into());
+                poly
+            })
+            .collect();
+        let facts: Vec<&(TermRef, TermRef)> = conditions.iter().chain(facts).collect();
+        let (_, linear_polies) = LinearPoly::from_slice(&polies);
+        let certificate = find_certificate(&linear_polies).filter(|x| x.check(&linear_polies))?;
+        // the derived bounds are bound once, since the next steps may use them many times
+        let mut lets = vec![];
+        let mut bind = |x: Bound| {
+            let local = self.prover.local(x.prop());
+            lets.push((local.clone(), x.proof));
+            Bound {
+                normal: x.normal,
+                proof: local,
+            }
+        };
+        let mut derived: Vec<Bound> = vec![];
+        for step in certificate.steps() {
+            let bound = match step {
+                Step::Hyp(i) => self.bound(&facts[*i].0, facts[*i].1.clone()),
+                Step::Combine {
+                    a,
+                    first,
+                    b,
+                    second,
+                } => bind(self.combine(a, &derived[*first], b, &derived[*second])),
+                Step::Tighten(i) => bind(self.tighten(&derived[*i])?),
+            };
+            derived.push(bound);
+        }
+        let last = derived.pop()?;
+        let c = last.normal.as_constant()?;
+        let proof =
+            (c.sign() == Sign::Minus).then(|| self.prover.negative_nonneg(c, last.proof))?;
+        Some(Prover::let_in(lets, proof))
+    }
 }
 
-pub fn lia(frame: Frame) -> Result<Vec<Frame>> {
-    let is_calculator = frame.engine.params.get("lia") == Some(&"calculator".to_string());
-    LogicBuilder::build_tactic(
-        "lia",
-        frame,
-        if is_calculator {
-            convert_calculator_mode
-        } else {
-            convert
-        },
-        check_contradiction,
-        negator,
-    )
+pub fn lia(frame: Frame) -> Result<(Vec<Frame>, ProofTerm)> {
+    let prover = Prover::new(&frame.engine);
+    let theory = Lia {
+        prover: &prover,
+        ring: Ring::new(&prover),
+        calculator: frame.engine.params.get("lia") == Some(&"calculator".to_string()),
+    };
+    let hyps = frame
+        .hyps
+        .iter()
+        .map(|hyp| {
+            let proof = term_ref!(axiom hyp.name.clone(), hyp.ty.clone());
+            (normalize(hyp.ty.clone()), proof)
+        })
+        .collect();
+    let proof = Refuter::new(&prover, &theory)
+        .prove(normalize(frame.goal.clone()), hyps)
+        .ok_or(CanNotSolve("lia"))?;
+    Ok((vec![], ProofTerm::Term(proof)))
 }
 
 #[cfg(test)]
@@ -202,6 +502,14 @@ mod tests {
         run_interactive_to_end("forall x: ℤ, x < x + 1", "intros\nlia");
     }
 
+    #[test]
+    fn certificates() {
+        success("forall x: ℤ, x < 5 ∨ x < 10 -> x < 20");
+        // needs the tightening of `2 < x` to `3 ≤ x`
+        success("forall x: ℤ, 4 < 2 * x -> 5 < 2 * x");
+        fail("forall x: ℤ, x < 5 ∨ x < 100 -> x < 20");
+    }
+
     #[test]
     fn success_lia_one_var() {
         success("forall x: ℤ, 2 * x < 5 -> 6 * x < 10 + 2 * x");
@@ -246,6 +554,13 @@ mod tests {
         fail("∀ x: ℤ, 10000000000000000000000000000000000000000001 * x = 10000000000000000000000000000000000000000000");
     }
 
+    #[test]
+    fn big_constants() {
+        success("∀ x: ℤ, 100000000000000000000000 < x -> 50000000000000000000000 < 2 * x - x");
+        fail("∀ x: ℤ, 100000000000000000000000 < x -> 100000000000000000000002 < x");
+        success("∀ x: ℤ, 3 * x = 100000000000000000000000 -> False");
+    }
+
     #[test]
     fn logic_unknown() {
         success("∀ P: U, 2 = 2 ∨ P");
@@ -380,6 +695,16 @@ mod tests {
         success(r#"|[1, 2, 3]| = 3"#);
     }
 
+    #[test]
+    fn naturals() {
+        success("∀ n: ℕ, 0 ≤ n");
+        success("∀ n m: ℕ, n + m < 0 -> False");
+        fail("∀ n: ℕ, 0 < n");
+        success("∀ n: ℕ, ↑ (succ n) = n + 1");
+        success("↑ (succ (succ zero)) = 2");
+        success("∀ n: ℕ, 0 < succ n");
+    }
+
     #[test]
     fn sets() {
         success(r#"|set_empty ℤ| = 0"#);
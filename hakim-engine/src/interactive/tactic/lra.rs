@@ -0,0 +1,411 @@
+use num_bigint::{BigInt, Sign};
+
+use super::{Error::CanNotSolve, Result};
+use crate::{
+    analysis::{
+        arith::LinearPoly,
+        farkas::{find_rational_certificate, Step},
+        proof::{le_z, spine, Eqn, Prover},
+        refute::{as_not, Assumption, Refuter, Theory},
+        ring::{Carrier, Normal, Ring},
+    },
+    app_ref,
+    brain::{normalize, TermRef},
+    interactive::{Frame, ProofTerm},
+    library::prelude::{eq, lt, minus, or, q, q_of_z, qlt, z},
+    term_ref,
+};
+
+/// `a < b` over rationals
+fn lt_q(a: TermRef, b: TermRef) -> TermRef {
+    app_ref!(qlt(), a, b)
+}
+
+/// `a ≤ b` over rationals, which is `a < b ∨ a = b`
+fn le_q(a: TermRef, b: TermRef) -> TermRef {
+    app_ref!(or(), lt_q(a.clone(), b.clone()), app_ref!(eq(), q(), a, b))
+}
+
+fn num(n: &BigInt) -> TermRef {
+    Carrier::Rational.num(n)
+}
+
+fn zero() -> TermRef {
+    num(&0.into())
+}
+
+fn sub(a: TermRef, b: TermRef) -> TermRef {
+    Carrier::Rational.sub(a, b)
+}
+
+/// `0 < t` if it is strict, otherwise `0 ≤ t`
+fn bound_prop(t: TermRef, strict: bool) -> TermRef {
+    if strict {
+        lt_q(zero(), t)
+    } else {
+        le_q(zero(), t)
+    }
+}
+
+/// The literals which `lra` understands, over integers or rationals
+enum Literal {
+    Lt(TermRef, TermRef, TermRef),
+    Eq(TermRef, TermRef, TermRef),
+}
+
+impl Literal {
+    fn detect(prop: &TermRef) -> Option<Self> {
+        match spine(prop)? {
+            ("lt", args) if args.len() == 2 => {
+                Some(Literal::Lt(z(), args[0].clone(), args[1].clone()))
+            }
+            ("qlt", args) if args.len() == 2 => {
+                Some(Literal::Lt(q(), args[0].clone(), args[1].clone()))
+            }
+            ("eq", args) if args.len() == 3 && (args[0] == z() || args[0] == q()) => Some(
+                Literal::Eq(args[0].clone(), args[1].clone(), args[2].clone()),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// The bound `0 < p` or `0 ≤ p` in the normal form, with its proof
+#[derive(Clone)]
+struct Bound {
+    normal: Normal,
+    strict: bool,
+    proof: TermRef,
+}
+
+impl Bound {
+    fn prop(&self) -> TermRef {
+        bound_prop(self.normal.term(), self.strict)
+    }
+}
+
+/// Linear arithmetic over rationals, which its facts are the bounds `0 < t` and
+/// `0 ≤ t`, and refutes them with the certificates of `find_rational_certificate`.
+/// The integer literals are lifted to rationals, so they are not tightened.
+struct Lra<'a> {
+    prover: &'a Prover<'a>,
+    field: Ring<'a>,
+}
+
+impl<'a> Lra<'a> {
+    fn bound(&self, t: &TermRef, strict: bool, proof: TermRef) -> Bound {
+        let (normal, e) = self.field.normalize(t);
+        let proof = self.prover.rewrite(&e, proof, |w| bound_prop(w, strict));
+        Bound {
+            normal,
+            strict,
+            proof,
+        }
+    }
+
+    /// The proof of `0 < ↑n` of a positive `n`
+    fn pos(&self, n: &BigInt) -> TermRef {
+        let p = self.prover;
+        p.apply(
+            "ℚ_of_z_lt",
+            [term_ref!(n 0), term_ref!(n n.clone()), p.pos(n)],
+        )
+    }
+
+    /// The proof of `0 ≤ t` from the proof of `0 < t`
+    fn weaken(&self, t: TermRef, proof: TermRef) -> TermRef {
+        let eq = app_ref!(eq(), q(), zero(), t);
+        self.prover.apply("inl", [lt_q(zero(), t), eq, proof])
+    }
+
+    /// The proofs of `0 ≤ b - a` and `0 ≤ a - b` from the proof of `a = b`
+    fn eq_facts(&self, a: &TermRef, b: &TermRef, proof: TermRef) -> [(TermRef, TermRef); 2] {
+        let p = self.prover;
+        let e = self
+            .field
+            .identity([a.clone()], |[x]| (sub(x.clone(), x.clone()), zero()));
+        let eq = app_ref!(eq(), q(), zero(), zero());
+        let zero_le_zero = p.apply("inr", [lt_q(zero(), zero()), eq, p.refl(q(), zero())]);
+        let zero_le_diff = p.rewrite(&p.sym(e), zero_le_zero, |w| le_q(zero(), w));
+        let e = Eqn::new(q(), a.clone(), b.clone(), proof);
+        let ba = p.rewrite(&e, zero_le_diff.clone(), |w| {
+            le_q(zero(), sub(w, a.clone()))
+        });
+        let ab = p.rewrite(&e, zero_le_diff, |w| le_q(zero(), sub(a.clone(), w)));
+        [
+            (sub(b.clone(), a.clone()), ba),
+            (sub(a.clone(), b.clone()), ab),
+        ]
+    }
+
+    /// The bounds of `b * x = a` for the atom `x = a / b` which `b` is a nonzero
+    /// constant, so the atoms of the divisions are linear in their numerators
+    fn division(&self, x: &TermRef) -> Vec<Bound> {
+        let p = self.prover;
+        let Some(("qdiv", args)) = spine(x) else {
+            return vec![];
+        };
+        let [a, b]: [TermRef; 2] = args.try_into().expect("qdiv has two arguments");
+        let (n, e) = self.field.normalize(&b);
+        let Some(c) = n.as_constant().filter(|c| c.sign() != Sign::NoSign) else {
+            return vec![];
+        };
+        let (ct, zt) = (term_ref!(n c.clone()), term_ref!(n 0));
+        let neq = p.abstract_over(app_ref!(eq(), q(), b, zero()), |h| {
+            let e = p.trans(p.sym(e), Eqn::new(q(), b.clone(), zero(), h));
+            let inj = p.apply("ℚ_of_z_inj", [ct.clone(), zt.clone(), p.proof(&e)]);
+            app_ref!(self.field.literal_neq(&z(), &ct, &zt), inj)
+        });
+        let proof = p.apply("ℚ_mult_div", [a.clone(), b.clone(), neq]);
+        let bx = Carrier::Rational.mul(b, x.clone());
+        self.eq_facts(&bx, &a, proof)
+            .into_iter()
+            .map(|(t, proof)| self.bound(&t, false, proof))
+            .collect()
+    }
+
+    /// `0 < k * t` or `0 ≤ k * t` of the bound and a positive `k`
+    fn scale(&self, k: &BigInt, x: &Bound) -> (TermRef, TermRef) {
+        let p = self.prover;
+        let (kt, xt) = (num(k), x.normal.term());
+        let proof = if x.strict {
+            p.apply(
+                "ℚ_lt_mult",
+                [kt.clone(), xt.clone(), self.pos(k), x.proof.clone()],
+            )
+        } else {
+            let nonneg = self.weaken(kt.clone(), self.pos(k));
+            p.apply(
+                "ℚ_le_mult",
+                [kt.clone(), xt.clone(), nonneg, x.proof.clone()],
+            )
+        };
+        (Carrier::Rational.mul(kt, xt), proof)
+    }
+
+    /// `a * x + b * y` of two bounds and positive `a` and `b`
+    fn combine(&self, a: &BigInt, x: &Bound, b: &BigInt, y: &Bound) -> Bound {
+        let p = self.prover;
+        let (mut x, mut y) = ((x.strict, self.scale(a, x)), (y.strict, self.scale(b, y)));
+        if !x.0 && y.0 {
+            std::mem::swap(&mut x, &mut y);
+        }
+        let ((sx, (xt, px)), (sy, (yt, py))) = (x, y);
+        let name = match (sx, sy) {
+            (true, true) => "ℚ_lt_plus",
+            (true, false) => "ℚ_lt_le_plus",
+            _ => "ℚ_le_plus",
+        };
+        let proof = p.apply(name, [xt.clone(), yt.clone(), px, py]);
+        self.bound(&Carrier::Rational.add(xt, yt), sx, proof)
+    }
+
+    /// The proof of `False` from a bound of a constant which is a contradiction
+    fn absurd(&self, x: &Bound) -> Option<TermRef> {
+        let p = self.prover;
+        let c = x.normal.as_constant()?;
+        let lt_zero = match c.sign() {
+            Sign::NoSign if x.strict => x.proof.clone(),
+            Sign::Minus => {
+                let k = -c;
+                let (kt, ct) = (num(&k), x.normal.term());
+                let le = if x.strict {
+                    self.weaken(ct.clone(), x.proof.clone())
+                } else {
+                    x.proof.clone()
+                };
+                let sum = p.apply("ℚ_lt_le_plus", [kt.clone(), ct.clone(), self.pos(&k), le]);
+                self.bound(&Carrier::Rational.add(kt, ct), true, sum).proof
+            }
+            _ => return None,
+        };
+        Some(p.apply("ℚ_lt_irrefl", [zero(), lt_zero]))
+    }
+}
+
+impl Theory for Lra<'_> {
+    type Fact = (TermRef, bool);
+
+    fn understands(&self, prop: &TermRef) -> bool {
+        Literal::detect(prop)
+            .or_else(|| Literal::detect(&as_not(prop)?))
+            .is_some()
+    }
+
+    fn assume(&self, prop: &TermRef, proof: TermRef) -> Option<Vec<Assumption<Self::Fact>>> {
+        let p = self.prover;
+        let fact = |t: TermRef, strict, proof| {
+            Assumption::Fact((t.clone(), strict), bound_prop(t, strict), proof)
+        };
+        let lift = |x: &TermRef| app_ref!(q_of_z(), x);
+        if let Some(literal) = Literal::detect(prop) {
+            return Some(match literal {
+                Literal::Lt(ty, a, b) if ty == z() => {
+                    let proof = p.apply("ℚ_of_z_lt", [a.clone(), b.clone(), proof]);
+                    vec![Assumption::Prop(lt_q(lift(&a), lift(&b)), proof)]
+                }
+                Literal::Eq(ty, a, b) if ty == z() => {
+                    let e = Eqn::new(z(), a, b, proof);
+                    let e = p.cong(e, q(), |x| lift(&x));
+                    let prop = app_ref!(eq(), q(), e.lhs, e.rhs);
+                    vec![Assumption::Prop(prop, p.proof(&e))]
+                }
+                Literal::Lt(_, a, b) => {
+                    let proof = p.apply("ℚ_lt_sub", [a.clone(), b.clone(), proof]);
+                    vec![fact(sub(b, a), true, proof)]
+                }
+                Literal::Eq(_, a, b) => self
+                    .eq_facts(&a, &b, proof)
+                    .into_iter()
+                    .map(|(t, proof)| fact(t, false, proof))
+                    .collect(),
+            });
+        }
+        Some(match Literal::detect(&as_not(prop)?)? {
+            Literal::Lt(ty, a, b) if ty == z() => {
+                let d = app_ref!(minus(), a, b);
+                let proof = p.apply("ℤ_not_lt", [a, b, proof]);
+                vec![Assumption::Prop(le_z(term_ref!(n 0), d), proof)]
+            }
+            Literal::Lt(_, a, b) => {
+                let proof = p.apply("ℚ_not_lt", [a.clone(), b.clone(), proof]);
+                vec![fact(sub(a, b), false, proof)]
+            }
+            Literal::Eq(ty, a, b) => {
+                let (name, lt) = if ty == z() {
+                    ("ℤ_lt_total", lt())
+                } else {
+                    ("ℚ_lt_total", qlt())
+                };
+                let prop = app_ref!(or(), app_ref!(lt, a, b), app_ref!(lt, b, a));
+                vec![Assumption::Prop(prop, p.apply(name, [a, b, proof]))]
+            }
+        })
+    }
+
+    fn refute(&self, facts: &[(Self::Fact, TermRef)]) -> Option<TermRef> {
+        let mut bounds: Vec<Bound> = facts
+            .iter()
+            .map(|((t, strict), proof)| self.bound(t, *strict, proof.clone()))
+            .collect();
+        // the bounds of the divisions have the atoms of their numerators, which may be
+        // divisions again
+        let mut seen: Vec<TermRef> = vec![];
+        let mut i = 0;
+        while i < bounds.len() {
+            let poly = bounds[i].normal.poly();
+            for x in poly.variables().iter().flat_map(|x| x.1.iter()) {
+                if !seen.contains(x) {
+                    seen.push(x.clone());
+                    bounds.extend(self.division(x));
+                }
+            }
+            i += 1;
+        }
+        let polies: Vec<_> = bounds.iter().map(|x| x.normal.poly()).collect();
+        let (_, linear_polies) = LinearPoly::from_slice(&polies);
+        let hyps: Vec<_> = linear_polies
+            .into_iter()
+            .zip(&bounds)
+            .map(|(p, b)| (p, b.strict))
+            .collect();
+        let certificate = find_rational_certificate(&hyps).filter(|x| x.check_rational(&hyps))?;
+        // the derived bounds are bound once, since the next steps may use them many times
+        let mut lets = vec![];
+        let mut bind = |x: Bound| {
+            let local = self.prover.local(x.prop());
+            lets.push((local.clone(), x.proof));
+            Bound { proof: local, ..x }
+        };
+        let mut derived: Vec<Bound> = vec![];
+        for step in certificate.steps() {
+            let bound = match step {
+                Step::Hyp(i) => bounds[*i].clone(),
+                Step::Combine {
+                    a,
+                    first,
+                    b,
+                    second,
+                } => bind(self.combine(a, &derived[*first], b, &derived[*second])),
+                Step::Tighten(_) => return None,
+            };
+            derived.push(bound);
+        }
+        let proof = self.absurd(derived.last()?)?;
+        Some(Prover::let_in(lets, proof))
+    }
+}
+
+pub fn lra(frame: Frame) -> Result<(Vec<Frame>, ProofTerm)> {
+    let prover = Prover::new(&frame.engine);
+    let ring = Ring::new(&prover);
+    let theory = Lra {
+        prover: &prover,
+        field: Ring::rational(&prover, &ring),
+    };
+    let hyps = frame
+        .hyps
+        .iter()
+        .map(|hyp| {
+            let proof = term_ref!(axiom hyp.name.clone(), hyp.ty.clone());
+            (normalize(hyp.ty.clone()), proof)
+        })
+        .collect();
+    let proof = Refuter::new(&prover, &theory)
+        .prove(normalize(frame.goal.clone()), hyps)
+        .ok_or(CanNotSolve("lra"))?;
+    Ok((vec![], ProofTerm::Term(proof)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interactive::tests::{run_interactive_to_end, run_interactive_to_fail};
+
+    fn success(goal: &str) {
+        run_interactive_to_end(goal, "intros\nlra");
+    }
+
+    fn fail(goal: &str) {
+        run_interactive_to_fail(goal, "intros", "lra");
+    }
+
+    #[test]
+    fn fractions() {
+        success("↑ 1 / 2 + ↑ 1 / 3 = ↑ 5 / 6");
+        success("↑ 1 / 3 < ↑ 1 / 2");
+        fail("↑ 1 / 2 < ↑ 1 / 3");
+        success("∀ x: ℚ, x / 2 + x / 2 = x");
+        success("∀ x: ℚ, 3 * (x / 3) = x");
+    }
+
+    #[test]
+    fn dense_order() {
+        success("∀ a b: ℚ, a < b -> a < (a + b) / 2 ∧ (a + b) / 2 < b");
+        fail("∀ a b: ℚ, a < b -> a + 1 ≤ b");
+        success("∀ x: ℚ, 2 < 2 * x -> 2 * x < 6 -> 1 < x");
+        fail("∀ x: ℚ, 4 < 2 * x -> 5 < 2 * x");
+    }
+
+    #[test]
+    fn logic() {
+        success("∀ x: ℚ, x < 0 ∨ x = 0 ∨ 0 < x");
+        fail("∀ x: ℚ, x < 0 ∨ 0 < x");
+        success("∀ x y: ℚ, (x < 1 -> y = 2) -> x = ↑ 1 / 2 -> y = 2");
+        success("∀ a b c: ℚ, a ≤ b -> b < c -> a < c");
+        fail("∀ a b c: ℚ, a ≤ b -> b ≤ c -> a < c");
+    }
+
+    #[test]
+    fn with_integers() {
+        success("∀ n: ℤ, ∀ x: ℚ, x = ↑ n -> 2 * x = ↑ (2 * n)");
+        success("∀ n: ℤ, 0 < n -> 0 < ↑ n / 2");
+        success("∀ a b: ℤ, a < b -> ↑ a < ↑ b");
+    }
+
+    #[test]
+    fn non_linear() {
+        success("∀ x y: ℚ, x * y < 1 -> 2 * (y * x) < 2");
+        fail("∀ x: ℚ, 0 ≤ x * x");
+        fail("∀ x y: ℚ, 0 < y -> x / y * y = x");
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::BTreeSet;
+
+use super::{
+    lia::{convert, inject_conditions, negator},
+    Result,
+};
+use crate::{
+    analysis::{
+        arith::{LinearPoly, Poly},
+        farkas::find_certificate,
+        logic::LogicBuilder,
+    },
+    brain::TermRef,
+    interactive::{Frame, ProofTerm},
+};
+
+fn is_square(monomial: &[TermRef]) -> bool {
+    // monomials are sorted, so equal terms are adjacent
+    monomial.chunks(2).all(|x| x.len() == 2 && x[0] == x[1])
+}
+
+/// Adds products of each pair of hypotheses and non-negativity of squares, which are
+/// valid nonlinear facts, so the linear back end can use them. For `0 < p` and `0 < q`
+/// over integers, `0 ≤ (p - 1) * (q - 1)` is added.
+fn add_products(polies: Vec<Poly>) -> Vec<Poly> {
+    let mut r = vec![];
+    let mut atoms = BTreeSet::new();
+    let mut squares = BTreeSet::new();
+    for p in &polies {
+        for (_, m) in p.variables() {
+            atoms.extend(m.iter().cloned());
+            if is_square(m) {
+                squares.insert(m.clone());
+            }
+        }
+    }
+    for atom in atoms {
+        squares.insert(vec![atom.clone(), atom]);
+    }
+    for m in squares {
+        let mut sq = Poly::monomial(m);
+        sq.add(1.into());
+        r.push(sq);
+    }
+    let shifted = polies
+        .iter()
+        .map(|p| {
+            let mut p = p.clone();
+            p.add((-1).into());
+            p
+        })
+        .collect::<Vec<_>>();
+    for (i, p) in shifted.iter().enumerate() {
+        for q in &shifted[i..] {
+            let mut pq = p.clone() * q.clone();
+            pq.add(1.into());
+            r.push(pq);
+        }
+    }
+    [polies, r].concat()
+}
+
+fn check_contradiction(polies: &[Poly]) -> bool {
+    let polies = &add_products(inject_conditions(polies.to_vec()));
+    let (_, linear_polies) = LinearPoly::from_slice(polies);
+    match find_certificate(&linear_polies) {
+        Some(certificate) => certificate.check(&linear_polies),
+        None => false,
+    }
+}
+
+pub fn nia(frame: Frame) -> Result<(Vec<Frame>, ProofTerm)> {
+    LogicBuilder::build_tactic("nia", frame, convert, check_contradiction, negator)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interactive::tests::{run_interactive_to_end, run_interactive_to_fail};
+
+    fn success(goal: &str) {
+        run_interactive_to_end(goal, "intros\nnia");
+    }
+
+    fn fail(goal: &str) {
+        run_interactive_to_fail(goal, "intros", "nia");
+    }
+
+    #[test]
+    fn linear() {
+        success("∀ x: ℤ, 2 * x < 6 -> 2 * x < 5");
+        fail("∀ x: ℤ, 5 < 2 * x -> 6 < 2 * x");
+    }
+
+    #[test]
+    fn product_sign() {
+        success("∀ a b: ℤ, 0 < a -> 0 < b -> 0 < a * b");
+        success("∀ a b: ℤ, a < 0 -> b < 0 -> 0 < a * b");
+        success("∀ a b: ℤ, 0 ≤ a -> 0 ≤ b -> 0 ≤ a * b");
+        success("∀ a b: ℤ, a ≤ 0 -> b ≤ 0 -> 0 ≤ a * b");
+        fail("∀ a b: ℤ, a < 0 -> 0 < a * b");
+    }
+
+    #[test]
+    fn squares() {
+        success("∀ a: ℤ, 0 ≤ a * a");
+        success("∀ a b: ℤ, 0 ≤ a * a + b * b");
+        fail("∀ a: ℤ, 0 < a * a");
+    }
+
+    #[test]
+    fn multiply_inequality() {
+        success("∀ a b c: ℤ, 0 < c -> a < b -> c * a < c * b");
+        success("∀ a b c: ℤ, ~ c = 0 -> a * c = b * c -> a = b");
+        fail("∀ a b c: ℤ, a < b -> c * a < c * b");
+    }
+}
@@ -1,16 +1,19 @@
 use crate::{
+    app_ref,
     brain::{
+        fill_axiom,
         infer::{type_of_and_infer, InferResults},
-        Term, TermRef,
+        normalize, type_of, Term, TermRef,
     },
-    interactive::Frame,
+    interactive::{Frame, ProofTerm},
+    library::prelude::eq_ind,
     parser::{fix_wild_scope, BinOp, InferGenerator},
-    Abstraction,
+    term_ref, Abstraction,
 };
 
 use super::{deny_arg, get_one_arg, next_arg, next_arg_constant, Error::*, Result};
 
-fn replace_term(
+pub(super) fn replace_term(
     exp: TermRef,
     find: TermRef,
     replace: TermRef,
@@ -77,6 +80,77 @@ fn replace_term(
     }
 }
 
+const MARKER: &str = "$marker";
+
+/// Builds `λ z: ty, exp'` where `exp'` is `exp` with the selected occurrences
+/// of `find` replaced by `z`, so it can be used as the motive of `eq_ind`.
+/// If `then` is given, the motive will be `λ z: ty, exp' → then` instead.
+fn motive(
+    exp: TermRef,
+    find: TermRef,
+    ty: TermRef,
+    mut which: Option<isize>,
+    then: Option<TermRef>,
+) -> TermRef {
+    let marker = term_ref!(axiom MARKER, ty);
+    let mut body = replace_term(exp, find, marker, &mut which);
+    if let Some(then) = then {
+        body = TermRef::new(Term::Forall(Abstraction {
+            var_ty: body,
+            hint_name: None,
+            body: then,
+        }));
+    }
+    let body = fill_axiom(body, |name, ty, depth| {
+        if name == MARKER {
+            term_ref!(v depth)
+        } else {
+            term_ref!(axiom name, ty)
+        }
+    });
+    TermRef::new(Term::Fun(Abstraction {
+        var_ty: ty,
+        hint_name: None,
+        body,
+    }))
+}
+
+/// A proof of `exp` from a proof of `exp` with `a` replaced by `b`, given
+/// `eq_proof: a = b`. The proof of replaced goal is the hole 0.
+pub(super) fn proof_of_forward_replace(
+    exp: TermRef,
+    [a, b, ty]: [TermRef; 3],
+    which: Option<isize>,
+    eq_proof: ProofTerm,
+) -> ProofTerm {
+    let motive = motive(exp.clone(), a.clone(), ty.clone(), which, Some(exp.clone()));
+    let id = TermRef::new(Term::Fun(Abstraction {
+        var_ty: exp,
+        hint_name: None,
+        body: term_ref!(v 0),
+    }));
+    let term = app_ref!(app_ref!(eq_ind(), ty, a, motive), id, b);
+    ProofTerm::Term(term).app(eq_proof).app(ProofTerm::Hole(0))
+}
+
+/// A proof of the goal from a proof of the goal in which hypothesis `hyp_name` is
+/// replaced by `new_hyp`, which is `hyp` with `a` replaced by `b`, given
+/// `eq_proof: a = b`. The proof of replaced goal is the hole 0.
+pub(super) fn proof_of_replace_in_hyp(
+    hyp_name: &str,
+    [hyp, new_hyp]: [TermRef; 2],
+    [a, b, ty]: [TermRef; 3],
+    which: Option<isize>,
+    eq_proof: ProofTerm,
+) -> ProofTerm {
+    let motive = motive(hyp.clone(), a.clone(), ty.clone(), which, None);
+    let new_hyp_proof = ProofTerm::Term(app_ref!(eq_ind(), ty, a, motive))
+        .app(ProofTerm::Term(term_ref!(axiom hyp_name, hyp)))
+        .app(ProofTerm::Term(b))
+        .app(eq_proof);
+    ProofTerm::intro(hyp_name, new_hyp, ProofTerm::Hole(0)).app(new_hyp_proof)
+}
+
 pub fn get_eq_params(term: &Term) -> Option<[TermRef; 3]> {
     if let Term::App { func, op: op2 } = term {
         if let Term::App { func, op: op1 } = func.as_ref() {
@@ -92,25 +166,39 @@ pub fn get_eq_params(term: &Term) -> Option<[TermRef; 3]> {
     None
 }
 
-pub fn rewrite<'a>(mut frame: Frame, args: impl Iterator<Item = &'a str>) -> Result<Vec<Frame>> {
+pub fn rewrite<'a>(
+    mut frame: Frame,
+    args: impl Iterator<Item = &'a str>,
+) -> Result<(Vec<Frame>, ProofTerm)> {
     let mut args = args.peekable();
     let is_reverse = args.peek() == Some(&"<-");
     if is_reverse {
         args.next();
     }
     let exp = &get_one_arg(args, "rewrite")?;
-    let term = frame.engine.calc_type(exp)?;
-    let [op1, op2, _] = get_eq_params(&term).ok_or(BadHyp("rewrite expect eq but got", term))?;
+    let eq_proof = frame.engine.parse_text(exp)?;
+    let term = normalize(type_of(eq_proof.clone())?);
+    let [op1, op2, ty] = get_eq_params(&term).ok_or(BadHyp("rewrite_expects_eq", term))?;
     let goal = frame.goal.clone();
-    frame.goal = if is_reverse {
-        replace_term(goal, op2, op1, &mut None)
+    let eq_proof = ProofTerm::Term(eq_proof);
+    let proof = if is_reverse {
+        frame.goal = replace_term(goal.clone(), op2.clone(), op1.clone(), &mut None);
+        let motive = motive(goal, op2.clone(), ty.clone(), None, None);
+        ProofTerm::Term(app_ref!(eq_ind(), ty, op1, motive))
+            .app(ProofTerm::Hole(0))
+            .app(ProofTerm::Term(op2))
+            .app(eq_proof)
     } else {
-        replace_term(goal, op1, op2, &mut None)
+        frame.goal = replace_term(goal.clone(), op1.clone(), op2.clone(), &mut None);
+        proof_of_forward_replace(goal, [op1, op2, ty], None, eq_proof)
     };
-    Ok(vec![frame])
+    Ok((vec![frame], proof))
 }
 
-pub fn replace<'a>(frame: Frame, args: impl Iterator<Item = &'a str>) -> Result<Vec<Frame>> {
+pub fn replace<'a>(
+    frame: Frame,
+    args: impl Iterator<Item = &'a str>,
+) -> Result<(Vec<Frame>, ProofTerm)> {
     let mut args = args.peekable();
     let mut which = None;
     if let Some(x) = args.peek() {
@@ -132,29 +220,37 @@ pub fn replace<'a>(frame: Frame, args: impl Iterator<Item = &'a str>) -> Result<
     proof_eq.goal = eq.clone();
     let mut after_replace = frame;
 
-    if let Term::App { func, op: replace } = eq.as_ref() {
-        if let Term::App { func: _, op: find } = func.as_ref() {
-            if args.peek().is_some() {
-                next_arg_constant(&mut args, "replace", "in")?;
-                let hyp_name = next_arg(&mut args, "replace")?;
-                let hyp = after_replace.remove_hyp_with_name(hyp_name)?.ty;
-                after_replace.add_hyp_with_name(
-                    hyp_name,
-                    replace_term(hyp, find.clone(), replace.clone(), &mut which),
-                )?;
-                deny_arg(args, "replace")?;
-            } else {
-                after_replace.goal = replace_term(
-                    after_replace.goal,
-                    find.clone(),
-                    replace.clone(),
-                    &mut which,
-                );
-            }
-            return Ok(vec![after_replace, proof_eq]);
-        }
-    }
-    unreachable!();
+    let [find, replace, ty] = get_eq_params(&eq).unwrap();
+    let proof = if args.peek().is_some() {
+        next_arg_constant(&mut args, "replace", "in")?;
+        let hyp_name = next_arg(&mut args, "replace")?;
+        let hyp = after_replace.remove_hyp_with_name(hyp_name)?.ty;
+        let new_hyp = replace_term(
+            hyp.clone(),
+            find.clone(),
+            replace.clone(),
+            &mut which.clone(),
+        );
+        after_replace.add_hyp_with_name(hyp_name, new_hyp.clone())?;
+        deny_arg(args, "replace")?;
+        proof_of_replace_in_hyp(
+            hyp_name,
+            [hyp, new_hyp],
+            [find, replace, ty],
+            which,
+            ProofTerm::Hole(1),
+        )
+    } else {
+        let goal = after_replace.goal.clone();
+        after_replace.goal = replace_term(
+            goal.clone(),
+            find.clone(),
+            replace.clone(),
+            &mut which.clone(),
+        );
+        proof_of_forward_replace(goal, [find, replace, ty], which, ProofTerm::Hole(1))
+    };
+    Ok((vec![after_replace, proof_eq], proof))
 }
 /*
 fn build_eq_term(t1: TermRef, t2: TermRef) -> Result<TermRef> {
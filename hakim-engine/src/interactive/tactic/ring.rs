@@ -0,0 +1,161 @@
+use super::{
+    deny_arg, next_arg, next_arg_constant,
+    rewrite::{get_eq_params, proof_of_forward_replace, proof_of_replace_in_hyp, replace_term},
+    Error::*,
+    Result,
+};
+use crate::{
+    analysis::{
+        proof::{Eqn, Prover},
+        ring::Ring,
+    },
+    brain::{normalize, type_of, Term, TermRef},
+    interactive::{Frame, ProofTerm},
+    library::prelude::z,
+    parser::BinOp,
+};
+
+pub fn ring<'a>(
+    frame: Frame,
+    args: impl Iterator<Item = &'a str>,
+) -> Result<(Vec<Frame>, ProofTerm)> {
+    deny_arg(args, "ring")?;
+    let goal = normalize(frame.goal.clone());
+    let [a, b, ty] = get_eq_params(&goal).ok_or(BadGoal("ring_expects_eq"))?;
+    if ty != z() {
+        return Err(BadGoal("ring_expects_integers"));
+    }
+    let prover = Prover::new(&frame.engine);
+    let e = Ring::new(&prover)
+        .prove_eq(&a, &b)
+        .ok_or(CanNotSolve("ring"))?;
+    Ok((vec![], ProofTerm::Term(prover.proof(&e))))
+}
+
+fn is_arith(t: &TermRef) -> bool {
+    matches!(
+        BinOp::detect(t),
+        Some((_, BinOp::Plus | BinOp::Minus | BinOp::Mult | BinOp::Pow, _))
+    ) && type_of(t.clone()) == Ok(z())
+}
+
+/// Finds the maximal arithmetic subterms which are not under a binder.
+fn arith_subterms(t: &TermRef, r: &mut Vec<TermRef>) {
+    if is_arith(t) {
+        r.push(t.clone());
+        return;
+    }
+    if let Term::App { func, op } = t.as_ref() {
+        arith_subterms(func, r);
+        arith_subterms(op, r);
+    }
+}
+
+/// Equations of arithmetic subterms of `t` and their canonical form, which are different
+fn simplifications(ring: &Ring, t: &TermRef) -> Vec<Eqn> {
+    let mut subterms = vec![];
+    arith_subterms(t, &mut subterms);
+    subterms
+        .into_iter()
+        .map(|x| ring.canonical(&x).1)
+        .filter(|e| e.lhs != e.rhs)
+        .collect()
+}
+
+pub fn ring_simplify<'a>(
+    mut frame: Frame,
+    args: impl Iterator<Item = &'a str>,
+) -> Result<(Vec<Frame>, ProofTerm)> {
+    let mut args = args.peekable();
+    let engine = frame.engine.clone();
+    let prover = Prover::new(&engine);
+    let ring = Ring::new(&prover);
+    let mut proof = ProofTerm::Hole(0);
+    if args.peek().is_some() {
+        next_arg_constant(&mut args, "ring_simplify", "in")?;
+        let hyp_name = next_arg(&mut args, "ring_simplify")?;
+        deny_arg(args, "ring_simplify")?;
+        let hyp = frame
+            .get_hyp_by_name(hyp_name)
+            .ok_or_else(|| UnknownHyp(hyp_name.to_string()))?
+            .ty
+            .clone();
+        for e in simplifications(&ring, &hyp) {
+            let eq_proof = ProofTerm::Term(prover.proof(&e));
+            let Eqn { lhs: a, rhs: b, .. } = e;
+            let hyp = frame.remove_hyp_with_name(hyp_name)?.ty;
+            let new_hyp = replace_term(hyp.clone(), a.clone(), b.clone(), &mut None);
+            frame.add_hyp_with_name(hyp_name, new_hyp.clone())?;
+            let step =
+                proof_of_replace_in_hyp(hyp_name, [hyp, new_hyp], [a, b, z()], None, eq_proof);
+            proof = proof.fill_last_hole(0, step);
+        }
+    } else {
+        for e in simplifications(&ring, &frame.goal.clone()) {
+            let eq_proof = ProofTerm::Term(prover.proof(&e));
+            let Eqn { lhs: a, rhs: b, .. } = e;
+            let goal = frame.goal.clone();
+            frame.goal = replace_term(goal.clone(), a.clone(), b.clone(), &mut None);
+            let step = proof_of_forward_replace(goal, [a, b, z()], None, eq_proof);
+            proof = proof.fill_last_hole(0, step);
+        }
+    }
+    Ok((vec![frame], proof))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interactive::tests::{
+        run_interactive, run_interactive_to_end, run_interactive_to_fail, EngineLevel,
+    };
+
+    #[test]
+    fn ring_identities() {
+        run_interactive_to_end("∀ a b: ℤ, a * b = (-a) * (-b)", "intros\nring");
+        run_interactive_to_end(
+            "∀ a b: ℤ, (a + b) * (a + b) = a * a + 2 * a * b + b * b",
+            "intros\nring",
+        );
+        run_interactive_to_end(
+            "∀ a b: ℤ, (a - b) ^ 2 = a ^ 2 - 2 * b * a + b ^ 2",
+            "intros\nring",
+        );
+        run_interactive_to_end("∀ a n: ℤ, a ^ n * a = a * a ^ n", "intros\nring");
+    }
+
+    #[test]
+    fn ring_fail() {
+        run_interactive_to_fail("∀ a b: ℤ, a * b = a * a", "intros", "ring");
+        run_interactive_to_fail("∀ a: ℤ, a < a + 1", "intros", "ring");
+        run_interactive_to_fail("∀ a: ℤ, a = 2 -> a * a = 4", "intros", "ring");
+    }
+
+    #[test]
+    fn ring_simplify_goal() {
+        let session = run_interactive(
+            "∀ a b: ℤ, 0 < (a + b) * (a - b) + b * b + 0",
+            "intros\nring_simplify",
+            EngineLevel::Full,
+        );
+        assert_eq!(
+            session.monitor_string().lines().last().unwrap().trim(),
+            "0 < a * a"
+        );
+        run_interactive_to_end(
+            "∀ a b: ℤ, 0 < a * a -> 0 < (a + b) * (a - b) + b * b",
+            "intros\nring_simplify\nassumption",
+        );
+    }
+
+    #[test]
+    fn ring_simplify_hyp() {
+        run_interactive_to_end(
+            "∀ a b: ℤ, (a + b) * (a + b) = a * a + b * b -> 2 * a * b = 0",
+            "intros a b H\nring_simplify in H\nlia",
+        );
+        run_interactive_to_end(
+            "∀ a b: ℤ, b * a + a * b < 2 -> 2 * a * b < 2",
+            "intros a b H\nring_simplify in H\nring_simplify\nassumption",
+        );
+    }
+}
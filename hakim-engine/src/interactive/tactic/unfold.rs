@@ -1,10 +1,16 @@
 use super::{next_arg, Result};
-use crate::{brain::fill_axiom, interactive::Frame, term_ref};
+use crate::{
+    brain::fill_axiom,
+    interactive::{Frame, ProofTerm},
+    term_ref,
+};
 
+/// Definitions are expanded in proof checking, so the proof of the unfolded
+/// goal is also a proof of the original one.
 pub(crate) fn unfold<'a>(
     mut frame: Frame,
     args: impl Iterator<Item = &'a str>,
-) -> Result<Vec<Frame>> {
+) -> Result<(Vec<Frame>, ProofTerm)> {
     let args = &mut args.peekable();
     let tactic_name = "unfold";
     let def = next_arg(args, tactic_name)?;
@@ -16,7 +22,7 @@ pub(crate) fn unfold<'a>(
             term_ref!(axiom x, y)
         }
     });
-    Ok(vec![frame])
+    Ok((vec![frame], ProofTerm::Hole(0)))
 }
 
 #[cfg(test)]
@@ -34,4 +40,25 @@ mod tests {
         "#,
         )
     }
+
+    #[test]
+    fn apply_without_unfold() {
+        const EXPANDED: &str = "1 < x ∧ (∀ y: ℤ, 0 < y -> y | x -> y = 1 ∨ y = x)";
+        run_interactive_to_end(
+            &format!("∀ x: ℤ, {EXPANDED} -> 0 < x"),
+            r#"
+            intros x H
+            apply prime_is_positive
+            apply H
+        "#,
+        );
+        run_interactive_to_end(
+            &format!("∀ x: ℤ, {EXPANDED} -> 0 < x"),
+            r#"
+            intros x H
+            apply prime_is_positive in H
+            assumption
+        "#,
+        );
+    }
 }
@@ -1,6 +1,6 @@
 pub use crate::engine::tests::{with_params, EngineLevel};
 
-use crate::engine::tests::build_engine;
+use crate::{engine::tests::build_engine, term_ref};
 
 use super::Session;
 
@@ -34,6 +34,9 @@ pub fn run_interactive_to_end(goal: &str, tactics: &str) {
     if !session.is_finished() {
         panic!("Goal not solved:\n{}", session.monitor_string());
     }
+    if let Err(e) = session.check_proof() {
+        panic!("Invalid proof term: {:?}", e);
+    }
 }
 
 pub fn run_interactive_to_fail(goal: &str, tactics: &str, fail_tactic: &str) {
@@ -361,3 +364,92 @@ fn auto_sugg() {
     s.run_tactic(&tac).unwrap();
     assert!(s.is_finished());
 }
+
+#[test]
+fn proof_term_of_intros_apply() {
+    let s = run_interactive(
+        "∀ A B: U, A → (A → B) → B",
+        r#"
+        intros A B a f
+        apply (f a)
+    "#,
+        EngineLevel::Empty,
+    );
+    let proof = s.proof_term().expect("proof is finished");
+    let eng = build_engine(EngineLevel::Empty);
+    let expected = eng.parse_text("λ A B: U, λ a: A, λ f: A → B, f a").unwrap();
+    assert_eq!(proof, expected);
+    s.check_proof().unwrap();
+}
+
+#[test]
+fn proof_term_of_switch() {
+    run_interactive_to_end(
+        "∀ A B: U, A → B → A ∧ B",
+        r#"
+        intros A B a b
+        apply and_intro
+        Switch 1
+        apply a
+        apply b
+    "#,
+    );
+}
+
+#[test]
+fn incomplete_proof() {
+    let s = run_interactive("∀ A: U, A → A", "intros A", EngineLevel::Empty);
+    assert!(s.proof_term().is_none());
+    assert!(matches!(
+        s.check_proof(),
+        Err(super::Error::IncompleteProof)
+    ));
+}
+
+#[test]
+fn reject_foreign_axiom_in_proof() {
+    let eng = build_engine(EngineLevel::Empty);
+    let goal = eng.parse_text("False").unwrap();
+    let proof = term_ref!(axiom "fake_proof", goal);
+    assert!(matches!(
+        eng.check_proof(proof, goal),
+        Err(super::Error::UnknownAxiomInProof(x)) if x == "fake_proof"
+    ));
+}
+
+#[test]
+fn export_script() {
+    let s = run_interactive(
+        "∀ A B C: U, A → B → C → C ∧ (B ∧ A)",
+        r#"
+        intros A B C a b c
+        apply and_intro
+        Seq (apply and_intro) (apply a)
+        apply b
+        apply c
+    "#,
+        EngineLevel::Full,
+    );
+    let script = s.to_script("and3").unwrap();
+    assert_eq!(
+        script,
+        r#"Theorem and3: ∀ A B C: Universe, A → B → C → C ∧ B ∧ A.
+Proof.
+    intros A B C a b c.
+    apply and_intro.
+    - apply and_intro.
+      + apply a.
+      + apply b.
+    - apply c.
+Qed.
+"#
+    );
+    let mut eng = build_engine(EngineLevel::Full);
+    let reports = eng.check_library_text("/Test", &script).unwrap();
+    assert!(reports[0].error.is_none());
+    let s = run_interactive("∀ A: U, A → A", "intros A", EngineLevel::Empty);
+    assert!(matches!(
+        s.to_script("id"),
+        Err(super::tactic::Error::UnfinishedProof)
+    ));
+}
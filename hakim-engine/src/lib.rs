@@ -6,7 +6,12 @@ pub mod engine;
 pub mod interactive;
 mod library;
 pub(crate) mod parser;
+pub mod report;
 pub use parser::notation_list;
 pub(crate) mod search;
+pub use brain::delta::Transparency;
 pub(crate) use brain::{Abstraction, Term, TermRef};
-pub use library::all_library_data;
+pub use library::{
+    all_library_data, BundledResolver, FsResolver, LibraryResolver, MemoryResolver, Position, Span,
+    SyntaxError, SyntaxErrorKind,
+};
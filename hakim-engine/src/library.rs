@@ -1,28 +1,47 @@
 use im::HashMap;
 
-use crate::engine::{Engine, Error, Result};
+use crate::engine::{Engine, Error, Result, TheoremCheck};
 
-pub use self::text::all_names;
+pub(crate) use self::resolver::Resolvers;
+pub use self::resolver::{FsResolver, LibraryResolver, MemoryResolver};
+pub use self::syntax::{Position, Span, SyntaxError, SyntaxErrorKind};
+pub use self::text::{all_names, BundledResolver};
 use self::{
     ast::{File, Sentence},
     text::load_text,
 };
 
 pub mod prelude;
+mod resolver;
+mod syntax;
 mod text;
 
 mod ast;
 #[cfg(test)]
 mod tests;
 
-fn load_library_by_text(engine: &mut Engine, text: &str) -> Result<()> {
-    ast::File::parse(text).add_to_engine(engine)?;
+fn parse_library(name: &str, text: &str) -> Result<File> {
+    File::parse(text).map_err(|e| Error::LibrarySyntax(name.to_string(), e))
+}
+
+fn load_library_by_text(engine: &mut Engine, name: &str, text: &str) -> Result<()> {
+    parse_library(name, text)?.add_to_engine(engine, name)?;
     Ok(())
 }
 
+pub(crate) fn check_library_by_text(
+    engine: &mut Engine,
+    name: &str,
+    text: &str,
+) -> Result<Vec<TheoremCheck>> {
+    parse_library(name, text)?.check(engine, name)
+}
+
 pub fn load_library_by_name(engine: &mut Engine, name: &str) -> Result<()> {
-    let text = text_of_name(name)?;
-    load_library_by_text(engine, text)
+    let text = engine
+        .library_text(name)
+        .ok_or_else(|| Error::UnknownLibrary(name.to_string()))?;
+    load_library_by_text(engine, name, &text)
 }
 
 fn text_of_name(name: &str) -> Result<&str> {
@@ -30,7 +49,7 @@ fn text_of_name(name: &str) -> Result<&str> {
 }
 
 pub(crate) fn proof_of_theorem(lib: &str, name: &str) -> Option<Vec<String>> {
-    let lib = File::parse(text_of_name(lib).ok()?);
+    let lib = File::parse(text_of_name(lib).ok()?).ok()?;
     let x = lib.0.into_iter().find(|x| x.name() == Some(name))?;
     if let Sentence::Theorem { proof, .. } = x {
         Some(proof)
@@ -41,7 +60,7 @@ pub(crate) fn proof_of_theorem(lib: &str, name: &str) -> Option<Vec<String>> {
 
 pub(crate) fn engine_from_middle_of_lib(lib: &str, name: &str) -> Option<(Engine, String)> {
     let mut eng = Engine::default();
-    let lib = File::parse(text_of_name(lib).ok()?);
+    let lib = File::parse(text_of_name(lib).ok()?).ok()?;
     for x in lib.0 {
         if x.name() == Some(name) {
             return Some((eng, x.ty()?.to_string()));
@@ -56,7 +75,7 @@ pub fn all_library_data() -> HashMap<String, ast::File> {
         if r.contains_key(&name) {
             return Ok(());
         }
-        let x = ast::File::parse(text_of_name(&name)?);
+        let x = parse_library(&name, text_of_name(&name)?)?;
         r.insert(name, x);
         Ok(())
     }
@@ -1,8 +1,18 @@
 use serde::Serialize;
 
 use crate::{
-    engine::{Engine, Result},
-    interactive::{suggest::Applicablity, SuggClass, SuggRule},
+    engine::{
+        Engine, Error, LibraryProofError,
+        LibraryProofFailure::{self, *},
+        NameKind, Result, TheoremCheck,
+    },
+    interactive::{suggest::Applicablity, SuggClass, SuggRule, TacticMacro},
+    TermRef, Transparency,
+};
+
+use super::syntax::{
+    self, sentences, SentenceTokens, SyntaxError,
+    SyntaxErrorKind::{Expected, MissingQed, UnknownSentence, UnknownSuggClass},
 };
 
 #[derive(Debug, Clone, Serialize)]
@@ -34,113 +44,223 @@ pub(crate) enum Sentence {
     Definition {
         name: String,
         body: String,
+        hidden_args: usize,
     },
     Theorem {
         #[serde(flatten)]
         sig: Signature,
         proof: Vec<String>,
     },
+    Inductive {
+        name: String,
+        /// prefix of the parameters, like `∀ A: U, `
+        params: String,
+        constructors: Vec<(String, String)>,
+    },
+    /// `Transparent name.`, `OnDemand name.` or `Opaque name.`
+    Transparency {
+        name: String,
+        transparency: Transparency,
+    },
+    /// `UniversePolymorphic name.`, which makes an axiom usable at every universe level
+    UniversePolymorphic {
+        name: String,
+    },
+    /// `Tactic name params := script.`
+    Tactic {
+        name: String,
+        params: Vec<String>,
+        script: String,
+    },
 }
 
-fn eat_signature(mut r: &str) -> Signature {
-    let hidden_args = if let Some(x) = r.strip_prefix("#1 ") {
-        r = x;
-        1
-    } else {
-        0
-    };
-    if let Some((name, body)) = r.split_once(':') {
-        return Signature {
-            name: name.trim().to_string(),
-            ty: body.to_string(),
-            hidden_args,
-        };
+/// Eats an optional `#n`, which hides the first `n` arguments of the name.
+fn eat_hidden_count(st: &mut SentenceTokens) -> syntax::Result<usize> {
+    if !st.eat_punct("#") {
+        return Ok(0);
     }
-    panic!("invalid signature {:?}", r);
+    let n = st.expect_word("number of hidden arguments")?;
+    n.parse()
+        .map_err(|_| st.error(Expected("number of hidden arguments")))
+}
+
+/// Eats implicit binders like `{A B: U} {C: U}` and returns them as a prefix of
+/// `sign` abstractions, with the number of their names.
+fn eat_implicit_binders(st: &mut SentenceTokens, sign: &str) -> syntax::Result<(String, usize)> {
+    eat_binders(st, ("{", "}"), sign)
+}
+
+fn eat_binders(
+    st: &mut SentenceTokens,
+    (open, close): (&str, &'static str),
+    sign: &str,
+) -> syntax::Result<(String, usize)> {
+    let mut prefix = String::new();
+    let mut count = 0;
+    while st.eat_punct(open) {
+        let mut names = vec![st.expect_word("name of the argument")?];
+        while !st.eat_punct(":") {
+            names.push(st.expect_word("`:` after the arguments")?);
+        }
+        let ty = st
+            .text_until(close, "type of the arguments")?
+            .ok_or_else(|| st.error(Expected("closing bracket of the arguments")))?;
+        count += names.len();
+        prefix += &format!("{sign} {}: {ty}, ", names.join(" "));
+    }
+    Ok((prefix, count))
+}
+
+fn eat_signature(st: &mut SentenceTokens) -> syntax::Result<Signature> {
+    let hidden_args = eat_hidden_count(st)?;
+    let name = st.expect_word("name")?.to_string();
+    let (binders, implicits) = eat_implicit_binders(st, "∀")?;
+    st.expect_punct(":", "`:` after the name")?;
+    Ok(Signature {
+        name,
+        ty: format!("{binders}{}", st.rest("type")?),
+        hidden_args: implicits + hidden_args,
+    })
+}
+
+fn eat_sugg_class(st: &mut SentenceTokens) -> syntax::Result<SuggClass> {
+    if let Some(l) = st.text_until("=>", "pattern")? {
+        let r = st.rest("pattern")?;
+        return Ok(SuggClass::Pattern(l.to_string(), r.to_string()));
+    }
+    let at_class = st.error(Expected("suggestion class"));
+    let class = st.rest("suggestion class")?;
+    Ok(match class {
+        "Destruct" => SuggClass::Destruct,
+        "Contradiction" => SuggClass::Contradiction,
+        "Rewrite" => SuggClass::Rewrite,
+        "intros" => SuggClass::Intros,
+        "Instantiate" => SuggClass::Instantiate,
+        "Trivial" => SuggClass::Trivial,
+        _ => {
+            return Err(SyntaxError {
+                kind: UnknownSuggClass(class.to_string()),
+                ..at_class
+            })
+        }
+    })
 }
 
 impl Sentence {
-    fn parse<'a, T: Iterator<Item = &'a str> + 'a>(it: &mut T) -> Self {
-        let s = it.next().unwrap();
-        if let Some(mut r) = s.strip_prefix("Suggest ") {
-            let target = if let Some(x) = r.strip_prefix("goal ") {
-                r = x;
-                SuggTarget::Goal
-            } else if let Some(x) = r.strip_prefix("hyp ") {
-                r = x;
-                SuggTarget::Hyp
-            } else {
-                panic!("invalid type for suggestion");
-            };
-            let applicablity = if let Some(x) = r.strip_prefix("default ") {
-                r = x;
-                Applicablity::Default
-            } else if let Some(x) = r.strip_prefix("auto ") {
-                r = x;
-                Applicablity::Auto
-            } else {
-                Applicablity::Normal
-            };
-            let tactic = match r.split_once(';') {
-                Some((t, rest)) => {
-                    r = rest;
-                    t.to_string()
+    fn parse<'a>(
+        mut st: SentenceTokens<'a>,
+        it: &mut impl Iterator<Item = SentenceTokens<'a>>,
+    ) -> syntax::Result<Self> {
+        let keyword = st.expect_word("a sentence")?;
+        let r = match keyword {
+            "Suggest" => {
+                let target = if st.eat_word("goal") {
+                    SuggTarget::Goal
+                } else if st.eat_word("hyp") {
+                    SuggTarget::Hyp
+                } else {
+                    return Err(st.error(Expected("`goal` or `hyp`")));
+                };
+                let applicablity = if st.eat_word("default") {
+                    Applicablity::Default
+                } else if st.eat_word("auto") {
+                    Applicablity::Auto
+                } else {
+                    Applicablity::Normal
+                };
+                let tactic = st
+                    .text_until(";", "tactic")?
+                    .ok_or_else(|| st.error(Expected("`;` after the tactic")))?
+                    .to_string();
+                Sentence::Suggestion {
+                    target,
+                    tactic,
+                    applicablity,
+                    class: eat_sugg_class(&mut st)?,
                 }
-                None => panic!("missing semicolon in suggestion"),
-            };
-            let class = match r.split_once("=>") {
-                Some((l, r)) => SuggClass::Pattern(l.trim().to_string(), r.trim().to_string()),
-                None => match r.trim() {
-                    "Destruct" => SuggClass::Destruct,
-                    "Contradiction" => SuggClass::Contradiction,
-                    "Rewrite" => SuggClass::Rewrite,
-                    "intros" => SuggClass::Intros,
-                    "Instantiate" => SuggClass::Instantiate,
-                    "Trivial" => SuggClass::Trivial,
-                    _ => panic!("unknown sugg class {r}"),
-                },
-            };
-            return Sentence::Suggestion {
-                target,
-                tactic,
-                applicablity,
-                class,
-            };
-        }
-        if let Some(r) = s.strip_prefix("Definition ") {
-            if let Some((name, body)) = r.split_once(":=") {
-                return Sentence::Definition {
-                    name: name.trim().to_string(),
-                    body: body.to_string(),
+            }
+            "Definition" => {
+                let hidden_args = eat_hidden_count(&mut st)?;
+                let name = st.expect_word("name")?.to_string();
+                let (binders, implicits) = eat_implicit_binders(&mut st, "λ")?;
+                st.expect_punct(":=", "`:=` after the name")?;
+                Sentence::Definition {
+                    name,
+                    body: format!("{binders}{}", st.rest("body")?),
+                    hidden_args: implicits + hidden_args,
+                }
+            }
+            "Inductive" => {
+                let name = st.expect_word("name")?.to_string();
+                let (params, _) = eat_binders(&mut st, ("(", ")"), "∀")?;
+                st.expect_punct(":=", "`:=` after the parameters")?;
+                let constructors = st
+                    .cases("type of the constructor")?
+                    .into_iter()
+                    .map(|(x, y)| (x.to_string(), y.to_string()))
+                    .collect();
+                Sentence::Inductive {
+                    name,
+                    params,
+                    constructors,
+                }
+            }
+            "Transparent" | "OnDemand" | "Opaque" => {
+                let transparency = match keyword {
+                    "Transparent" => Transparency::Transparent,
+                    "OnDemand" => Transparency::OnDemand,
+                    _ => Transparency::Opaque,
                 };
+                let name = st.expect_word("name of the definition")?.to_string();
+                if !st.is_empty() {
+                    return Err(st.error(Expected("period after the name")));
+                }
+                Sentence::Transparency { name, transparency }
             }
-        }
-        if let Some(r) = s.strip_prefix("Todo ") {
-            return Sentence::Todo(eat_signature(r));
-        }
-        if let Some(r) = s.strip_prefix("Axiom ") {
-            return Sentence::Axiom(eat_signature(r));
-        }
-        if let Some(r) = s.strip_prefix("Import ") {
-            return Sentence::Import {
-                name: r.to_string(),
-            };
-        }
-        if let Some(r) = s.strip_prefix("Theorem ") {
-            let mut proof = vec![];
-            assert_eq!("Proof", it.next().unwrap());
-            for x in it {
-                if x == "Qed" {
-                    break;
+            "UniversePolymorphic" => {
+                let name = st.expect_word("name of the axiom")?.to_string();
+                if !st.is_empty() {
+                    return Err(st.error(Expected("period after the name")));
                 }
-                proof.push(x.to_string());
+                Sentence::UniversePolymorphic { name }
             }
-            return Sentence::Theorem {
-                sig: eat_signature(r),
-                proof,
-            };
-        }
-        panic!("invalid sentence {:?}", s);
+            "Tactic" => {
+                let name = st.expect_word("name")?.to_string();
+                let mut params = vec![];
+                while !st.eat_punct(":=") {
+                    params.push(st.expect_word("name of the parameter or `:=`")?.to_string());
+                }
+                Sentence::Tactic {
+                    name,
+                    params,
+                    script: st.rest("script")?.to_string(),
+                }
+            }
+            "Todo" => Sentence::Todo(eat_signature(&mut st)?),
+            "Axiom" => Sentence::Axiom(eat_signature(&mut st)?),
+            "Import" => Sentence::Import {
+                name: st.library_name()?.to_string(),
+            },
+            "Theorem" => {
+                let sig = eat_signature(&mut st)?;
+                match it.next() {
+                    Some(x) if x.whole() == "Proof" => (),
+                    Some(x) => return Err(x.sentence_error(Expected("`Proof`"))),
+                    None => return Err(st.sentence_error(Expected("`Proof`"))),
+                }
+                let mut proof = vec![];
+                loop {
+                    match it.next() {
+                        Some(x) if x.whole() == "Qed" => break,
+                        Some(x) => proof.push(x.whole().to_string()),
+                        None => return Err(st.sentence_error(MissingQed)),
+                    }
+                }
+                Sentence::Theorem { sig, proof }
+            }
+            _ => return Err(st.sentence_error(UnknownSentence(keyword.to_string()))),
+        };
+        Ok(r)
     }
 
     pub(crate) fn add_to_engine(&self, engine: &mut Engine) -> Result<()> {
@@ -162,17 +282,88 @@ impl Sentence {
                 }
             }
             Sentence::Import { name } => engine.load_library(&name)?,
-            Sentence::Todo(sig) | Sentence::Axiom(sig) | Sentence::Theorem { sig, .. } => {
-                engine.add_axiom(&sig.name, &sig.ty, sig.hidden_args)?
+            Sentence::Todo(sig) => {
+                engine.add_axiom(&sig.name, &sig.ty, sig.hidden_args)?;
+                engine.set_name_kind(&sig.name, NameKind::Todo);
+            }
+            Sentence::Axiom(sig) => {
+                engine.add_axiom(&sig.name, &sig.ty, sig.hidden_args)?;
+                engine.set_name_kind(&sig.name, NameKind::Axiom);
+            }
+            Sentence::Theorem { sig, .. } => {
+                engine.add_axiom(&sig.name, &sig.ty, sig.hidden_args)?;
+                let kind = if engine.is_verifying_proofs() {
+                    NameKind::CheckedTheorem
+                } else {
+                    NameKind::Theorem
+                };
+                engine.set_name_kind(&sig.name, kind);
+            }
+            Sentence::Definition {
+                name,
+                body,
+                hidden_args,
+            } => {
+                engine.add_definition(&name, &body, hidden_args)?;
+                engine.set_name_kind(&name, NameKind::Definition);
+            }
+            Sentence::Inductive {
+                name,
+                params,
+                constructors,
+            } => engine.add_inductive(&name, &params, &constructors)?,
+            Sentence::Transparency { name, transparency } => {
+                engine.set_transparency(&name, transparency)?
+            }
+            Sentence::UniversePolymorphic { name } => engine.make_universe_polymorphic(&name)?,
+            Sentence::Tactic {
+                name,
+                params,
+                script,
+            } => {
+                let tactic = TacticMacro::new(params, script)
+                    .map_err(|e| Error::InvalidTacticMacro(name.clone(), Box::new(e)))?;
+                engine.add_tactic_macro(&name, tactic)?
             }
-            Sentence::Definition { name, body } => engine.add_definition(&name, &body)?,
         }
         Ok(())
     }
 
+    /// Replays the proof of a theorem and returns its checked proof term. Other
+    /// sentences are always fine.
+    pub(crate) fn check_proof(&self, engine: &Engine, library: &str) -> Result<Option<TermRef>> {
+        let (sig, proof) = match self {
+            Sentence::Theorem { sig, proof } => (sig, proof),
+            _ => return Ok(None),
+        };
+        let error = |tactic: Option<&String>, failure: LibraryProofFailure| {
+            Error::LibraryProof(Box::new(LibraryProofError {
+                library: library.to_string(),
+                theorem: sig.name.clone(),
+                tactic: tactic.cloned(),
+                failure,
+            }))
+        };
+        let mut session = engine.interactive_session(&sig.ty)?;
+        for tactic in proof {
+            if let Err(e) = session.run_tactic(tactic) {
+                let monitor = session.monitor_string();
+                return Err(error(Some(tactic), TacticFailed { error: e, monitor }));
+            }
+        }
+        if !session.is_finished() {
+            let monitor = session.monitor_string();
+            return Err(error(proof.last(), Incomplete { monitor }));
+        }
+        session
+            .check_proof()
+            .map_err(|e| error(proof.last(), InvalidProofTerm(e)))?;
+        Ok(session.proof_term())
+    }
+
     pub(crate) fn name(&self) -> Option<&str> {
         match self {
-            Sentence::Import { name } => Some(name),
+            Sentence::Import { name } | Sentence::Inductive { name, .. } => Some(name),
             Sentence::Todo(sig) | Sentence::Axiom(sig) | Sentence::Theorem { sig, .. } => {
                 Some(&sig.name)
             }
@@ -182,9 +373,13 @@ impl Sentence {
 
     pub(crate) fn ty(&self) -> Option<&str> {
         match self {
-            Sentence::Suggestion { .. } | Sentence::Import { .. } | Sentence::Definition { .. } => {
-                None
-            }
+            Sentence::Suggestion { .. }
+            | Sentence::Import { .. }
+            | Sentence::Definition { .. }
+            | Sentence::Inductive { .. }
+            | Sentence::Transparency { .. }
+            | Sentence::UniversePolymorphic { .. }
+            | Sentence::Tactic { .. } => None,
             Sentence::Todo(sig) | Sentence::Axiom(sig) | Sentence::Theorem { sig, .. } => {
                 Some(&sig.ty)
             }
@@ -195,24 +390,53 @@ impl Sentence {
 #[derive(Debug, Clone, Serialize)]
 pub struct File(pub(crate) Vec<Sentence>);
 
-fn split_by_sentence(text: &str) -> impl Iterator<Item = &str> {
-    text.split('.').map(|x| x.trim()).filter(|x| !x.is_empty())
-}
-
 impl File {
-    pub fn parse(text: &str) -> Self {
-        let mut it = split_by_sentence(text).peekable();
+    pub fn parse(text: &str) -> syntax::Result<Self> {
+        let mut it = sentences(text)?;
         let mut r = vec![];
-        while it.peek().is_some() {
-            r.push(Sentence::parse(&mut it));
+        while let Some(st) = it.next() {
+            r.push(Sentence::parse(st, &mut it)?);
         }
-        Self(r)
+        Ok(Self(r))
     }
 
-    pub fn add_to_engine(&self, engine: &mut Engine) -> Result<()> {
+    pub fn add_to_engine(&self, engine: &mut Engine, name: &str) -> Result<()> {
         for x in self.0.iter() {
+            let proof = if engine.is_verifying_proofs() {
+                x.check_proof(engine, name)?
+            } else {
+                None
+            };
             x.add_to_engine(engine)?;
+            if let (Some(proof), Some(name)) = (proof, x.name()) {
+                engine.add_proof_dependencies(name, &proof);
+            }
         }
         Ok(())
     }
+
+    /// Adds the file to the engine like `add_to_engine`, but checks every theorem and
+    /// reports failed proofs instead of stopping on them.
+    pub(crate) fn check(&self, engine: &mut Engine, name: &str) -> Result<Vec<TheoremCheck>> {
+        let mut r = vec![];
+        for x in self.0.iter() {
+            let (proof, error) = match x.check_proof(engine, name) {
+                Ok(proof) => (proof, None),
+                Err(Error::LibraryProof(e)) => (None, Some(*e)),
+                Err(e) => return Err(e),
+            };
+            x.add_to_engine(engine)?;
+            if let Sentence::Theorem { sig, .. } = x {
+                if let Some(proof) = &proof {
+                    engine.add_proof_dependencies(&sig.name, proof);
+                    engine.set_name_kind(&sig.name, NameKind::CheckedTheorem);
+                }
+                r.push(TheoremCheck {
+                    theorem: sig.name.clone(),
+                    error,
+                });
+            }
+        }
+        Ok(r)
+    }
 }
@@ -1,4 +1,8 @@
-use crate::{app_ref, term_ref, TermRef};
+use crate::{
+    app_ref,
+    engine::{Engine, Result},
+    term_ref, TermRef, Transparency,
+};
 
 pub fn u() -> TermRef {
     term_ref!(universe 0)
@@ -6,6 +10,14 @@ pub fn u() -> TermRef {
 pub fn z() -> TermRef {
     term_ref!(axiom "ℤ" , u())
 }
+/// The natural numbers, which are an inductive with constructors `zero` and `succ`
+/// added by `init_naturals`
+pub fn n() -> TermRef {
+    term_ref!(axiom "ℕ" , u())
+}
+pub fn q() -> TermRef {
+    term_ref!(axiom "ℚ" , u())
+}
 pub fn false_ty() -> TermRef {
     term_ref!(axiom "False" , u())
 }
@@ -27,6 +39,12 @@ fn v3() -> TermRef {
 pub fn eq() -> TermRef {
     term_ref!(axiom "eq" , forall u(), forall v0(), forall v1(), u())
 }
+//∀ A: U, ∀ x: A, ∀ P: A → U, P x → ∀ y: A, x = y → P y
+pub fn eq_ind() -> TermRef {
+    term_ref!(axiom "eq_ind", forall u(), forall v0(), forall term_ref!(forall v1(), u()),
+        forall app_ref!(v0(), v1()), forall v3(), forall app_ref!(eq(), term_ref!(v 4), v3(), v0()),
+        app_ref!(v3(), v1()))
+}
 pub fn lt() -> TermRef {
     term_ref!(axiom "lt" , forall z(), forall z(), u())
 }
@@ -42,6 +60,27 @@ pub fn minus() -> TermRef {
 pub fn mult() -> TermRef {
     term_ref!(axiom "mult", forall z(), forall z(), z())
 }
+pub fn z_of_n() -> TermRef {
+    term_ref!(axiom "z_of_n", forall n(), z())
+}
+pub fn q_of_z() -> TermRef {
+    term_ref!(axiom "q_of_z", forall z(), q())
+}
+pub fn qlt() -> TermRef {
+    term_ref!(axiom "qlt" , forall q(), forall q(), u())
+}
+pub fn qplus() -> TermRef {
+    term_ref!(axiom "qplus", forall q(), forall q(), q())
+}
+pub fn qminus() -> TermRef {
+    term_ref!(axiom "qminus", forall q(), forall q(), q())
+}
+pub fn qmult() -> TermRef {
+    term_ref!(axiom "qmult", forall q(), forall q(), q())
+}
+pub fn qdiv() -> TermRef {
+    term_ref!(axiom "qdiv", forall q(), forall q(), q())
+}
 pub fn ex() -> TermRef {
     term_ref!(axiom "ex", forall u(), forall term_ref!(forall v0(), u()), u())
 }
@@ -54,6 +93,12 @@ pub fn and() -> TermRef {
 pub fn pair() -> TermRef {
     term_ref!(axiom "pair", forall u(), forall u(), forall v1(), forall v1(), app_ref!(and(), v3(), v2()))
 }
+pub fn proj1() -> TermRef {
+    term_ref!(axiom "proj1", forall u(), forall u(), forall app_ref!(and(), v1(), v0()), v2())
+}
+pub fn proj2() -> TermRef {
+    term_ref!(axiom "proj2", forall u(), forall u(), forall app_ref!(and(), v1(), v0()), v1())
+}
 pub fn set() -> TermRef {
     term_ref!(axiom "set", forall u(), u())
 }
@@ -126,14 +171,170 @@ pub fn chr() -> TermRef {
     term_ref!(axiom "chr", forall z(), char_ty())
 }
 
+/// Number of implicit arguments of the builtin names, which are filled with wilds
+/// when the name is parsed and hidden when it is printed.
+pub fn init_hidden_args() -> im::HashMap<String, usize> {
+    let mut hidden_args = im::HashMap::<String, usize>::default();
+    hidden_args.insert("finite".to_string(), 1);
+    hidden_args.insert("cnt".to_string(), 1);
+    hidden_args.insert("proj1".to_string(), 2);
+    hidden_args.insert("proj2".to_string(), 2);
+    hidden_args
+}
+
+/// Builtin axioms which are usable at every universe level, like `set U`.
+pub fn init_universe_polymorphic() -> im::HashSet<String> {
+    [
+        "eq",
+        "eq_ind",
+        "set",
+        "set_from_func",
+        "set_empty",
+        "set_singleton",
+        "inset",
+        "union",
+        "intersection",
+        "setminus",
+        "included",
+        "finite",
+        "list",
+        "nil",
+        "cons",
+        "plus_list",
+    ]
+    .into_iter()
+    .map(|x| x.to_string())
+    .collect()
+}
+
+/// Natural numbers are defined in the engine itself, since their induction principle
+/// and the coercion `z_of_n` to `ℤ` are generated from the declaration.
+pub fn init_naturals(eng: &mut Engine) -> Result<()> {
+    let constructors = [("zero", "ℕ"), ("succ", "ℕ → ℕ")].map(|(c, ty)| (c.into(), ty.into()));
+    eng.add_inductive("ℕ", "", &constructors)?;
+    eng.add_definition("z_of_n", "ℕ_rect (λ n: ℕ, ℤ) 0 (λ n: ℕ, λ r: ℤ, r + 1)", 0)?;
+    eng.set_transparency("z_of_n", Transparency::Opaque)
+}
+
+/// Axioms of the logic, the integers and the rationals which the decision procedures
+/// build their proof terms from. They have their own names, so the libraries can still
+/// declare and prove their usual forms like `eq_refl` and `or_ind`.
+const ARITH_AXIOMS: &[(&str, &str)] = &[
+    ("refl", "∀ A: U, ∀ x: A, x = x"),
+    ("inl", "∀ A B: U, A → A ∨ B"),
+    ("inr", "∀ A B: U, B → A ∨ B"),
+    ("or_elim", "∀ A B: U, A ∨ B → ∀ C: U, (A → C) → (B → C) → C"),
+    ("by_contra", "∀ A: U, ((A → False) → False) → A"),
+    ("ℤ_plus_comm", "∀ a b: ℤ, a + b = b + a"),
+    ("ℤ_plus_assoc", "∀ a b c: ℤ, a + b + c = a + (b + c)"),
+    ("ℤ_plus_zero", "∀ a: ℤ, a + 0 = a"),
+    ("ℤ_mult_comm", "∀ a b: ℤ, a * b = b * a"),
+    ("ℤ_mult_assoc", "∀ a b c: ℤ, a * b * c = a * (b * c)"),
+    ("ℤ_mult_one", "∀ a: ℤ, 1 * a = a"),
+    ("ℤ_mult_zero", "∀ a: ℤ, 0 * a = 0"),
+    ("ℤ_distr", "∀ a b c: ℤ, a * (b + c) = a * b + a * c"),
+    ("ℤ_minus", "∀ a b: ℤ, a - b = a + (-1) * b"),
+    ("ℤ_pow_zero", "∀ a: ℤ, a ^ 0 = 1"),
+    ("ℤ_pow_succ", "∀ a n: ℤ, 0 ≤ n → a ^ (n + 1) = a * a ^ n"),
+    ("ℤ_one_pow", "∀ n: ℤ, 1 ^ n = 1"),
+    ("ℤ_zero_lt_one", "0 < 1"),
+    ("ℤ_lt_irrefl", "∀ a: ℤ, a < a → False"),
+    ("ℤ_lt_plus", "∀ a b: ℤ, 0 < a → 0 < b → 0 < a + b"),
+    ("ℤ_lt_mult", "∀ a b: ℤ, 0 < a → 0 < b → 0 < a * b"),
+    ("ℤ_lt_sub", "∀ a b: ℤ, a < b → 0 < b - a"),
+    ("ℤ_lt_total", "∀ a b: ℤ, (a = b → False) → a < b ∨ b < a"),
+    ("ℤ_not_lt", "∀ a b: ℤ, (a < b → False) → 0 ≤ a - b"),
+    ("ℤ_not_le", "∀ a b: ℤ, (a ≤ b → False) → 0 < a - b"),
+    ("ℤ_lt_le", "∀ a: ℤ, 0 < a → 0 ≤ a - 1"),
+    ("ℤ_le_lt", "∀ a: ℤ, 0 ≤ a → 0 < a + 1"),
+    ("ℤ_le_plus", "∀ a b: ℤ, 0 ≤ a → 0 ≤ b → 0 ≤ a + b"),
+    ("ℤ_le_mult", "∀ a b: ℤ, 0 ≤ a → 0 ≤ b → 0 ≤ a * b"),
+    ("ℤ_mod_nonneg", "∀ a b: ℤ, 0 < b → 0 ≤ a mod b"),
+    ("ℤ_mod_lt", "∀ a b: ℤ, 0 < b → a mod b < b"),
+    ("ℤ_of_n_nonneg", "∀ n: ℕ, 0 ≤ z_of_n n"),
+    ("ℤ_sigma_split", "∀ a b: ℤ, ∀ f: ℤ → ℤ, sigma a b f = sigma 0 b f - sigma 0 a f"),
+    ("ℤ_sigma_succ", "∀ b: ℤ, ∀ f: ℤ → ℤ, sigma 0 (b + 1) f = sigma 0 b f + f b"),
+    ("ℤ_sigma_zero", "∀ f: ℤ → ℤ, sigma 0 0 f = 0"),
+    ("ℤ_sigma_const", "∀ b c: ℤ, sigma 0 b (λ i: ℤ, c) = c * b"),
+    (
+        "ℤ_sigma_plus",
+        "∀ b: ℤ, ∀ f g: ℤ → ℤ, sigma 0 b (λ i: ℤ, f i + g i) = sigma 0 b f + sigma 0 b g",
+    ),
+    (
+        "ℤ_sigma_mult",
+        "∀ b c: ℤ, ∀ f: ℤ → ℤ, sigma 0 b (λ i: ℤ, c * f i) = c * sigma 0 b f",
+    ),
+    (
+        "ℤ_sigma_ext",
+        "∀ a b: ℤ, ∀ f g: ℤ → ℤ, (∀ i: ℤ, f i = g i) → sigma a b f = sigma a b g",
+    ),
+    ("ℤ_len_nil", "∀ A: U, len1 (list A) (nil A) = 0"),
+    (
+        "ℤ_len_cons",
+        "∀ A: U, ∀ x: A, ∀ l: list A, len1 (list A) (cons A x l) = 1 + len1 (list A) l",
+    ),
+    (
+        "ℤ_len_plus_list",
+        "∀ A: U, ∀ a b: list A, len1 (list A) (plus_list A a b) = len1 (list A) a + len1 (list A) b",
+    ),
+    ("ℤ_len_set_empty", "∀ A: U, len1 (set A) (set_empty A) = 0"),
+    ("ℤ_len_nonneg", "∀ A: U, ∀ l: list A, 0 ≤ len1 (list A) l"),
+    ("ℤ_cnt_nil", "∀ A: U, ∀ x: A, cnt x (nil A) = 0"),
+    (
+        "ℤ_cnt_cons_eq",
+        "∀ A: U, ∀ x: A, ∀ l: list A, cnt x (cons A x l) = 1 + cnt x l",
+    ),
+    (
+        "ℤ_cnt_cons_neq",
+        "∀ A: U, ∀ x y: A, ∀ l: list A, (x = y → False) → cnt x (cons A y l) = cnt x l",
+    ),
+    (
+        "ℤ_cnt_plus_list",
+        "∀ A: U, ∀ x: A, ∀ a b: list A, cnt x (plus_list A a b) = cnt x a + cnt x b",
+    ),
+    ("ℚ_plus_comm", "∀ a b: ℚ, a + b = b + a"),
+    ("ℚ_plus_assoc", "∀ a b c: ℚ, a + b + c = a + (b + c)"),
+    ("ℚ_plus_zero", "∀ a: ℚ, a + 0 = a"),
+    ("ℚ_mult_comm", "∀ a b: ℚ, a * b = b * a"),
+    ("ℚ_mult_assoc", "∀ a b c: ℚ, a * b * c = a * (b * c)"),
+    ("ℚ_mult_one", "∀ a: ℚ, 1 * a = a"),
+    ("ℚ_mult_zero", "∀ a: ℚ, 0 * a = 0"),
+    ("ℚ_distr", "∀ a b c: ℚ, a * (b + c) = a * b + a * c"),
+    ("ℚ_minus", "∀ a b: ℚ, a - b = a + (-1) * b"),
+    ("ℚ_mult_div", "∀ a b: ℚ, (b = 0 → False) → b * (a / b) = a"),
+    ("ℚ_of_z_plus", "∀ a b: ℤ, q_of_z (a + b) = q_of_z a + q_of_z b"),
+    ("ℚ_of_z_mult", "∀ a b: ℤ, q_of_z (a * b) = q_of_z a * q_of_z b"),
+    ("ℚ_of_z_lt", "∀ a b: ℤ, a < b → q_of_z a < q_of_z b"),
+    ("ℚ_of_z_inj", "∀ a b: ℤ, q_of_z a = q_of_z b → a = b"),
+    ("ℚ_lt_irrefl", "∀ a: ℚ, a < a → False"),
+    ("ℚ_lt_plus", "∀ a b: ℚ, 0 < a → 0 < b → 0 < a + b"),
+    ("ℚ_lt_le_plus", "∀ a b: ℚ, 0 < a → 0 ≤ b → 0 < a + b"),
+    ("ℚ_le_plus", "∀ a b: ℚ, 0 ≤ a → 0 ≤ b → 0 ≤ a + b"),
+    ("ℚ_lt_mult", "∀ a b: ℚ, 0 < a → 0 < b → 0 < a * b"),
+    ("ℚ_le_mult", "∀ a b: ℚ, 0 ≤ a → 0 ≤ b → 0 ≤ a * b"),
+    ("ℚ_lt_sub", "∀ a b: ℚ, a < b → 0 < b - a"),
+    ("ℚ_lt_total", "∀ a b: ℚ, (a = b → False) → a < b ∨ b < a"),
+    ("ℚ_not_lt", "∀ a b: ℚ, (a < b → False) → 0 ≤ a - b"),
+];
+
+/// Adds the axioms which proofs of `lia` and `ring` are built from.
+pub fn init_arith(eng: &mut Engine) -> Result<()> {
+    for (name, ty) in ARITH_AXIOMS {
+        eng.add_axiom(name, ty, 0)?;
+    }
+    Ok(())
+}
+
 pub fn init_dict() -> im::HashMap<String, TermRef> {
     let mut name_dict = im::HashMap::<String, TermRef>::default();
     name_dict.insert("U".to_string(), u());
     name_dict.insert("ℤ".to_string(), z());
+    name_dict.insert("ℚ".to_string(), q());
     name_dict.insert("False".to_string(), false_ty());
     name_dict.insert("True".to_string(), true_ty());
     name_dict.insert("divide".to_string(), divide());
     name_dict.insert("eq".to_string(), eq());
+    name_dict.insert("eq_ind".to_string(), eq_ind());
     name_dict.insert("ex".to_string(), ex());
     name_dict.insert("plus".to_string(), plus());
     name_dict.insert("pow".to_string(), pow());
@@ -141,9 +342,17 @@ pub fn init_dict() -> im::HashMap<String, TermRef> {
     name_dict.insert("mod_of".to_string(), mod_of());
     name_dict.insert("mult".to_string(), mult());
     name_dict.insert("or".to_string(), or());
+    name_dict.insert("q_of_z".to_string(), q_of_z());
+    name_dict.insert("qlt".to_string(), qlt());
+    name_dict.insert("qplus".to_string(), qplus());
+    name_dict.insert("qminus".to_string(), qminus());
+    name_dict.insert("qmult".to_string(), qmult());
+    name_dict.insert("qdiv".to_string(), qdiv());
     name_dict.insert("lt".to_string(), lt());
     name_dict.insert("and".to_string(), and());
     name_dict.insert("pair".to_string(), pair());
+    name_dict.insert("proj1".to_string(), proj1());
+    name_dict.insert("proj2".to_string(), proj2());
     name_dict.insert("set".to_string(), set());
     name_dict.insert("set_from_func".to_string(), set_from_func());
     name_dict.insert("set_empty".to_string(), set_empty());
@@ -0,0 +1,141 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use super::text::BundledResolver;
+
+/// A source of library texts. Library names are absolute and separated by `/`, like
+/// `/Arith` or `/myteam/Algebra`.
+pub trait LibraryResolver: Debug {
+    /// Text of the library with the given name, if this resolver has it
+    fn load(&self, name: &str) -> Option<String>;
+
+    /// All of the names that this resolver has, which is used for loading every library
+    /// under a prefix. Resolvers which can not list their libraries return nothing, and
+    /// their libraries can be loaded only by their full name.
+    fn names(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+/// Serves libraries from `.v` files under a directory. By default `<root>/a/b.v` is
+/// served as `/a/b`, and with a namespace `/ns` it is served as `/ns/a/b`.
+#[derive(Debug, Clone)]
+pub struct FsResolver {
+    root: PathBuf,
+    namespace: String,
+}
+
+impl FsResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self::with_namespace(root, "")
+    }
+
+    pub fn with_namespace(root: impl Into<PathBuf>, namespace: &str) -> Self {
+        FsResolver {
+            root: root.into(),
+            namespace: namespace.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn path_of_name(&self, name: &str) -> Option<PathBuf> {
+        let relative = name.strip_prefix(&self.namespace)?.strip_prefix('/')?;
+        let mut path = self.root.clone();
+        for part in relative.split('/') {
+            if part.is_empty() || part == "." || part == ".." {
+                return None;
+            }
+            path.push(part);
+        }
+        path.set_extension("v");
+        Some(path)
+    }
+
+    fn collect_names(&self, dir: &Path, prefix: &str, r: &mut Vec<String>) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_name = match path.file_name().and_then(|x| x.to_str()) {
+                Some(x) => x.to_string(),
+                None => continue,
+            };
+            if path.is_dir() {
+                self.collect_names(&path, &format!("{prefix}/{file_name}"), r);
+            } else if let Some(name) = file_name.strip_suffix(".v") {
+                r.push(format!("{prefix}/{name}"));
+            }
+        }
+    }
+}
+
+impl LibraryResolver for FsResolver {
+    fn load(&self, name: &str) -> Option<String> {
+        std::fs::read_to_string(self.path_of_name(name)?).ok()
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut r = vec![];
+        self.collect_names(&self.root, &self.namespace, &mut r);
+        r
+    }
+}
+
+/// Serves libraries from texts in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemoryResolver(HashMap<String, String>);
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: &str, text: &str) {
+        self.0.insert(name.to_string(), text.to_string());
+    }
+}
+
+impl LibraryResolver for MemoryResolver {
+    fn load(&self, name: &str) -> Option<String> {
+        self.0.get(name).cloned()
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.0.keys().cloned().collect()
+    }
+}
+
+/// Resolvers of an engine, in the order of priority. They are not serialized, and a
+/// deserialized engine only sees the bundled libraries.
+#[derive(Debug, Clone)]
+pub(crate) struct Resolvers(im::Vector<Rc<dyn LibraryResolver>>);
+
+impl Default for Resolvers {
+    fn default() -> Self {
+        Resolvers(im::vector![
+            Rc::new(BundledResolver) as Rc<dyn LibraryResolver>
+        ])
+    }
+}
+
+impl Resolvers {
+    pub(crate) fn push(&mut self, resolver: Rc<dyn LibraryResolver>) {
+        self.0.push_back(resolver);
+    }
+
+    pub(crate) fn load(&self, name: &str) -> Option<String> {
+        self.0.iter().find_map(|x| x.load(name))
+    }
+
+    pub(crate) fn names(&self) -> Vec<String> {
+        let mut r = self.0.iter().flat_map(|x| x.names()).collect::<Vec<_>>();
+        r.sort();
+        r.dedup();
+        r
+    }
+}
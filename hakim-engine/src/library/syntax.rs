@@ -0,0 +1,375 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::parser::{is_valid_ident_char, is_whity_char};
+
+/// Line and column of a character in a library file, both starting from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    fn of_offset(text: &str, offset: usize) -> Self {
+        let before = &text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|x| x + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+        Position { line, column }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    fn of_offsets(text: &str, start: usize, end: usize) -> Self {
+        Span {
+            start: Position::of_offset(text, start),
+            end: Position::of_offset(text, end),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum SyntaxErrorKind {
+    UnterminatedString,
+    InvalidChar,
+    UnbalancedBracket(char),
+    /// The last sentence doesn't end with a period
+    MissingDot,
+    UnknownSentence(String),
+    Expected(&'static str),
+    UnknownSuggClass(String),
+    /// A theorem whose proof doesn't end with `Qed`
+    MissingQed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SyntaxError {
+    pub span: Span,
+    pub kind: SyntaxErrorKind,
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use SyntaxErrorKind::*;
+        let Position { line, column } = self.span.start;
+        write!(f, "{line}:{column}: ")?;
+        match &self.kind {
+            UnterminatedString => write!(f, "unterminated string"),
+            InvalidChar => write!(f, "invalid char literal"),
+            UnbalancedBracket(c) => write!(f, "unbalanced `{c}`"),
+            MissingDot => write!(f, "sentence doesn't end with a period"),
+            UnknownSentence(x) => write!(f, "unknown sentence `{x}`"),
+            Expected(x) => write!(f, "expected {x}"),
+            UnknownSuggClass(x) => write!(f, "unknown suggestion class `{x}`"),
+            MissingQed => write!(f, "proof doesn't end with `Qed`"),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, SyntaxError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Punct,
+    Str,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start: usize,
+    end: usize,
+    /// number of open brackets before the token
+    depth: usize,
+}
+
+/// Splits the text into sentences. A sentence ends with a period which is followed by a
+/// white space or the end of the text and is not inside brackets, so terms and tactics
+/// can contain periods. Brackets are only counted and not matched by kind, since
+/// intervals like `[0, n)` are valid terms. A brace at the start of a sentence is a
+/// sentence by itself, like the braces which focus on a goal in proofs.
+fn tokenize(text: &str) -> Result<Vec<Vec<Token<'_>>>> {
+    let error = |start: usize, end: usize, kind| SyntaxError {
+        span: Span::of_offsets(text, start, end),
+        kind,
+    };
+    let mut sentences = vec![];
+    let mut current = vec![];
+    // positions of the open brackets
+    let mut brackets: Vec<usize> = vec![];
+    let mut it = text.char_indices().peekable();
+    while let Some((start, c)) = it.next() {
+        if is_whity_char(c) {
+            continue;
+        }
+        let depth = brackets.len();
+        if current.is_empty() && depth == 0 && matches!(c, '{' | '}') {
+            let end = start + c.len_utf8();
+            sentences.push(vec![Token {
+                kind: TokenKind::Punct,
+                text: &text[start..end],
+                start,
+                end,
+                depth,
+            }]);
+            continue;
+        }
+        let (kind, end) = if is_valid_ident_char(c) {
+            let mut end = start + c.len_utf8();
+            while let Some((i, c)) = it.next_if(|(_, c)| is_valid_ident_char(*c)) {
+                end = i + c.len_utf8();
+            }
+            (TokenKind::Word, end)
+        } else if c == '"' {
+            match it.find(|(_, c)| *c == '"') {
+                Some((i, _)) => (TokenKind::Str, i + 1),
+                None => {
+                    return Err(error(
+                        start,
+                        text.len(),
+                        SyntaxErrorKind::UnterminatedString,
+                    ))
+                }
+            }
+        } else if c == '\'' {
+            // a char literal, which can be a bracket or a period
+            match (it.next(), it.next()) {
+                (Some(_), Some((i, '\''))) => (TokenKind::Str, i + 1),
+                _ => return Err(error(start, start + 1, SyntaxErrorKind::InvalidChar)),
+            }
+        } else if c == '.' && depth == 0 && it.peek().is_none_or(|(_, c)| is_whity_char(*c)) {
+            sentences.push(std::mem::take(&mut current));
+            continue;
+        } else {
+            if matches!(c, '(' | '[' | '{') {
+                brackets.push(start);
+            } else if matches!(c, ')' | ']' | '}') && brackets.pop().is_none() {
+                let kind = SyntaxErrorKind::UnbalancedBracket(c);
+                return Err(error(start, start + 1, kind));
+            }
+            let mut end = start + c.len_utf8();
+            if let Some((i, _)) =
+                it.next_if(|(_, next)| matches!((c, next), (':', '=') | ('=', '>')))
+            {
+                end = i + 1;
+            }
+            (TokenKind::Punct, end)
+        };
+        current.push(Token {
+            kind,
+            text: &text[start..end],
+            start,
+            end,
+            depth,
+        });
+    }
+    if let Some(start) = brackets.pop() {
+        let open = text[start..].chars().next().unwrap_or('(');
+        return Err(error(
+            start,
+            start + 1,
+            SyntaxErrorKind::UnbalancedBracket(open),
+        ));
+    }
+    if let (Some(first), Some(last)) = (current.first(), current.last()) {
+        return Err(error(first.start, last.end, SyntaxErrorKind::MissingDot));
+    }
+    Ok(sentences)
+}
+
+/// Tokens of a single sentence, without its period
+pub(crate) struct SentenceTokens<'a> {
+    text: &'a str,
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> SentenceTokens<'a> {
+    pub(crate) fn error(&self, kind: SyntaxErrorKind) -> SyntaxError {
+        let (start, end) = match (self.tokens.get(self.pos), self.tokens.last()) {
+            (Some(x), _) => (x.start, x.end),
+            (None, Some(x)) => (x.end, x.end),
+            (None, None) => (0, 0),
+        };
+        SyntaxError {
+            span: Span::of_offsets(self.text, start, end),
+            kind,
+        }
+    }
+
+    /// Error which covers the whole sentence
+    pub(crate) fn sentence_error(&self, kind: SyntaxErrorKind) -> SyntaxError {
+        let start = self.tokens.first().map_or(0, |x| x.start);
+        let end = self.tokens.last().map_or(0, |x| x.end);
+        SyntaxError {
+            span: Span::of_offsets(self.text, start, end),
+            kind,
+        }
+    }
+
+    /// Eats the next token if it is the word `word`
+    pub(crate) fn eat_word(&mut self, word: &str) -> bool {
+        self.eat_if(TokenKind::Word, word)
+    }
+
+    /// Eats the next token if it is the punctuation `punct`
+    pub(crate) fn eat_punct(&mut self, punct: &str) -> bool {
+        self.eat_if(TokenKind::Punct, punct)
+    }
+
+    fn eat_if(&mut self, kind: TokenKind, text: &str) -> bool {
+        match self.tokens.get(self.pos) {
+            Some(x) if x.kind == kind && x.text == text => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn expect_word(&mut self, what: &'static str) -> Result<&'a str> {
+        match self.tokens.get(self.pos) {
+            Some(x) if x.kind == TokenKind::Word => {
+                self.pos += 1;
+                Ok(x.text)
+            }
+            _ => Err(self.error(SyntaxErrorKind::Expected(what))),
+        }
+    }
+
+    pub(crate) fn expect_punct(&mut self, punct: &str, what: &'static str) -> Result<()> {
+        if self.eat_punct(punct) {
+            Ok(())
+        } else {
+            Err(self.error(SyntaxErrorKind::Expected(what)))
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pos == self.tokens.len()
+    }
+
+    /// Source text from the current token to the first `punct` outside of brackets,
+    /// which is eaten as well. Returns `None` if there is no such `punct`.
+    pub(crate) fn text_until(
+        &mut self,
+        punct: &str,
+        what: &'static str,
+    ) -> Result<Option<&'a str>> {
+        let depth = match self.tokens.get(self.pos) {
+            Some(x) => x.depth,
+            None => return Ok(None),
+        };
+        let found = self.tokens[self.pos..]
+            .iter()
+            .position(|x| x.kind == TokenKind::Punct && x.text == punct && x.depth == depth);
+        let Some(len) = found else {
+            return Ok(None);
+        };
+        if len == 0 {
+            return Err(self.error(SyntaxErrorKind::Expected(what)));
+        }
+        let r = self.slice(self.pos, self.pos + len);
+        self.pos += len + 1;
+        Ok(Some(r))
+    }
+
+    /// Source text of the remaining tokens, which should not be empty
+    pub(crate) fn rest(&mut self, what: &'static str) -> Result<&'a str> {
+        if self.is_empty() {
+            return Err(self.error(SyntaxErrorKind::Expected(what)));
+        }
+        let r = self.slice(self.pos, self.tokens.len());
+        self.pos = self.tokens.len();
+        Ok(r)
+    }
+
+    /// Eats `| name: text | name: text ...`, where the first `|` is optional. A `|`
+    /// starts a new case only outside of brackets and before `name :`, so the texts can
+    /// contain `|` as well.
+    pub(crate) fn cases(&mut self, what: &'static str) -> Result<Vec<(&'a str, &'a str)>> {
+        let depth = self.tokens.get(self.pos).map_or(0, |x| x.depth);
+        if self.starts_case(self.pos, depth) {
+            self.pos += 1;
+        }
+        let mut r = vec![];
+        loop {
+            let name = self.expect_word("name of the case")?;
+            self.expect_punct(":", "`:` after the name")?;
+            let start = self.pos;
+            let end = (start..self.tokens.len())
+                .find(|i| self.starts_case(*i, depth))
+                .unwrap_or(self.tokens.len());
+            if start == end {
+                return Err(self.error(SyntaxErrorKind::Expected(what)));
+            }
+            r.push((name, self.slice(start, end)));
+            if end == self.tokens.len() {
+                self.pos = end;
+                return Ok(r);
+            }
+            self.pos = end + 1;
+        }
+    }
+
+    /// Whether the `i`-th token is a `|` at `depth` followed by `name :`
+    fn starts_case(&self, i: usize, depth: usize) -> bool {
+        let is = |j: usize, kind: TokenKind, text: Option<&str>| {
+            self.tokens.get(j).is_some_and(|x| {
+                x.kind == kind && x.depth == depth && text.is_none_or(|t| t == x.text)
+            })
+        };
+        is(i, TokenKind::Punct, Some("|"))
+            && is(i + 1, TokenKind::Word, None)
+            && is(i + 2, TokenKind::Punct, Some(":"))
+    }
+
+    /// Source text of a library name like `/a/b`, which should be the rest of the sentence
+    pub(crate) fn library_name(&mut self) -> Result<&'a str> {
+        let start = self.pos;
+        loop {
+            self.expect_punct("/", "`/` in the library name")?;
+            self.expect_word("library name")?;
+            if self.is_empty() {
+                return Ok(self.slice(start, self.pos));
+            }
+        }
+    }
+
+    /// Source text of the whole sentence
+    pub(crate) fn whole(&self) -> &'a str {
+        self.slice(0, self.tokens.len())
+    }
+
+    fn slice(&self, from: usize, to: usize) -> &'a str {
+        match (
+            self.tokens.get(from),
+            to.checked_sub(1).and_then(|x| self.tokens.get(x)),
+        ) {
+            (Some(a), Some(b)) if from < to => &self.text[a.start..b.end],
+            _ => "",
+        }
+    }
+}
+
+/// Splits the text of a library file into its sentences.
+pub(crate) fn sentences(text: &str) -> Result<impl Iterator<Item = SentenceTokens<'_>>> {
+    Ok(tokenize(text)?
+        .into_iter()
+        .filter(|x| !x.is_empty())
+        .map(move |tokens| SentenceTokens {
+            text,
+            tokens,
+            pos: 0,
+        }))
+}
@@ -1,11 +1,14 @@
 use std::panic::catch_unwind;
 
-use crate::{engine::Engine, library::ast::Signature};
+use crate::{
+    brain::inductive::InductiveError,
+    engine::{Engine, Error, LibraryProofFailure, NameKind},
+};
 
 use super::{
     ast::{File, Sentence},
     text::all_names,
-    text_of_name,
+    FsResolver, LibraryResolver, MemoryResolver, SyntaxErrorKind,
 };
 
 #[test]
@@ -31,28 +34,501 @@ fn any() {
 
 #[test]
 fn check_library_proofs() {
-    for lib_name in all_names() {
-        let mut eng = Engine::default();
-        let lib = File::parse(text_of_name(lib_name).unwrap());
-        for st in lib.0 {
-            if let Sentence::Theorem { sig, proof } = &st {
-                let Signature { ty, name, .. } = sig;
-                let mut session = eng.interactive_session(ty).unwrap();
-                for tactic in proof {
-                    match session.run_tactic(tactic) {
-                        Ok(_) => (),
-                        Err(e) => panic!(
-                            "In library {lib_name}\nIn theorem {name}: {ty}\nIn tactic: {tactic}\nProof failed with error: {e:?}",
-                        ),
-                    }
-                }
-                assert!(
-                    session.is_finished(),
-                    "Incomplete proof for {name} in library {lib_name}\nMonitor: {}",
-                    session.monitor_string()
-                );
-            }
-            st.add_to_engine(&mut eng).unwrap();
+    let mut eng = Engine::default();
+    if let Err(e) = eng.load_library_verified("/") {
+        panic!("Library verification failed: {e:?}");
+    }
+    assert_eq!(eng.names_of_kind(NameKind::Theorem).count(), 0);
+}
+
+#[test]
+fn name_kinds() {
+    let mut eng = Engine::default();
+    eng.load_library("/Arith").unwrap();
+    assert_eq!(eng.kind_of_name("eq_refl"), Some(NameKind::Axiom));
+    assert_eq!(eng.kind_of_name("lt_trans"), Some(NameKind::Theorem));
+    assert_eq!(eng.kind_of_name("plus"), None);
+    let mut eng = Engine::default();
+    eng.load_library_verified("/Arith").unwrap();
+    assert_eq!(eng.kind_of_name("lt_trans"), Some(NameKind::CheckedTheorem));
+    eng.load_library_verified("/NumberTheory").unwrap();
+    assert_eq!(eng.kind_of_name("prime"), Some(NameKind::Definition));
+}
+
+fn check_text(text: &str) -> Result<(), Error> {
+    let mut eng = Engine::default();
+    eng.load_library("/Arith").unwrap();
+    for st in File::parse(text).unwrap().0 {
+        st.check_proof(&eng, "/Test")?;
+        st.add_to_engine(&mut eng)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn report_failed_tactic() {
+    let e = check_text("Theorem foo: ∀ x: ℤ, x < x + 1. Proof. intros x. apply bar. Qed.");
+    match e {
+        Err(Error::LibraryProof(e)) => {
+            assert_eq!(e.library, "/Test");
+            assert_eq!(e.theorem, "foo");
+            assert_eq!(e.tactic.as_deref(), Some("apply bar"));
+            assert!(matches!(
+                e.failure,
+                LibraryProofFailure::TacticFailed { .. }
+            ));
+        }
+        _ => panic!("Expected failed proof but got {e:?}"),
+    }
+}
+
+#[test]
+fn report_incomplete_proof() {
+    let e = check_text("Theorem foo: ∀ x: ℤ, x < x + 1. Proof. intros x. Qed.");
+    match e {
+        Err(Error::LibraryProof(e)) => {
+            assert_eq!(e.theorem, "foo");
+            assert_eq!(e.tactic.as_deref(), Some("intros x"));
+            assert!(matches!(e.failure, LibraryProofFailure::Incomplete { .. }));
+        }
+        _ => panic!("Expected incomplete proof but got {e:?}"),
+    }
+    check_text("Theorem foo: ∀ x: ℤ, x < x + 1. Proof. intros x. lia. Qed.").unwrap();
+}
+
+#[test]
+fn check_reports_each_theorem() {
+    let mut eng = Engine::default();
+    let r = eng
+        .check_library_text(
+            "/Test",
+            r#"
+            Import /Arith.
+            Theorem good: ∀ x: ℤ, x < x + 1. Proof. intros. lia. Qed.
+            Theorem bad: ∀ x: ℤ, x < x. Proof. intros x. lia. Qed.
+            Theorem uses_bad: ∀ y: ℤ, y < y. Proof. apply bad. Qed.
+            "#,
+        )
+        .unwrap();
+    let names = r.iter().map(|x| x.theorem.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["good", "bad", "uses_bad"]);
+    assert!(r[0].error.is_none());
+    assert!(r[2].error.is_none());
+    let e = r[1].error.as_ref().unwrap();
+    assert_eq!(e.tactic.as_deref(), Some("lia"));
+    match &e.failure {
+        LibraryProofFailure::TacticFailed { monitor, .. } => assert!(monitor.contains("x < x")),
+        _ => panic!("Expected failed tactic but got {e:?}"),
+    }
+    assert_eq!(eng.kind_of_name("good"), Some(NameKind::CheckedTheorem));
+    assert_eq!(eng.kind_of_name("bad"), Some(NameKind::Theorem));
+    assert!(eng.has_library("/Test"));
+}
+
+#[test]
+fn library_paths() {
+    let dir = std::env::temp_dir().join(format!("hakim-library-paths-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("team")).unwrap();
+    std::fs::write(
+        dir.join("team/Extra.v"),
+        "Import /Arith. Theorem extra: ∀ x: ℤ, x < x + 2. Proof. intros. lia. Qed.",
+    )
+    .unwrap();
+    let mut eng = Engine::default();
+    assert!(matches!(
+        eng.load_library("/team/Extra"),
+        Err(Error::UnknownLibrary(_))
+    ));
+    eng.add_library_path(&dir);
+    eng.load_library("/team/Extra").unwrap();
+    assert_eq!(eng.kind_of_name("extra"), Some(NameKind::Theorem));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn memory_resolver() {
+    let mut libs = MemoryResolver::new();
+    libs.insert(
+        "/myteam/Algebra",
+        "Import /Arith. Theorem double_pos: ∀ x: ℤ, 0 < x -> 0 < 2 * x. Proof. intros. lia. Qed.",
+    );
+    libs.insert(
+        "/myteam/Order",
+        "Import /myteam/Algebra. Axiom order_axiom: ∀ x: ℤ, x < x + 1.",
+    );
+    let mut eng = Engine::default();
+    eng.add_library_resolver(libs);
+    eng.load_library("/myteam/Order").unwrap();
+    assert_eq!(eng.kind_of_name("double_pos"), Some(NameKind::Theorem));
+    assert_eq!(eng.kind_of_name("order_axiom"), Some(NameKind::Axiom));
+    assert!(matches!(
+        eng.load_library("/myteam/Geometry"),
+        Err(Error::UnknownLibrary(_))
+    ));
+}
+
+#[test]
+fn namespaced_roots() {
+    let base = std::env::temp_dir().join(format!("hakim-namespaced-{}", std::process::id()));
+    let (first, second) = (base.join("first"), base.join("second"));
+    std::fs::create_dir_all(first.join("sub")).unwrap();
+    std::fs::create_dir_all(&second).unwrap();
+    std::fs::write(
+        first.join("sub/A.v"),
+        "Import /Arith. Axiom first_a: ∀ x: ℤ, x < x + 1.",
+    )
+    .unwrap();
+    std::fs::write(
+        second.join("B.v"),
+        "Import /team/sub/A. Axiom second_b: ∀ x: ℤ, x < x + 3.",
+    )
+    .unwrap();
+    let mut eng = Engine::default();
+    eng.add_library_resolver(FsResolver::with_namespace(&first, "/team"));
+    eng.add_library_resolver(FsResolver::with_namespace(&second, "/other/"));
+    assert!(matches!(
+        eng.load_library("/sub/A"),
+        Err(Error::UnknownLibrary(_))
+    ));
+    eng.load_library("/other/B").unwrap();
+    assert_eq!(eng.kind_of_name("first_a"), Some(NameKind::Axiom));
+    assert_eq!(eng.kind_of_name("second_b"), Some(NameKind::Axiom));
+    let mut eng = Engine::default();
+    eng.add_library_resolver(FsResolver::with_namespace(&first, "/team"));
+    eng.add_library_resolver(FsResolver::with_namespace(&second, "/team"));
+    // loads every library under the prefix, from both roots
+    eng.load_library("/team").unwrap();
+    assert_eq!(eng.kind_of_name("second_b"), Some(NameKind::Axiom));
+    std::fs::remove_dir_all(base).unwrap();
+}
+
+#[test]
+fn resolver_rejects_escaping_names() {
+    let resolver = FsResolver::new(std::env::temp_dir());
+    assert_eq!(resolver.load("/../etc/passwd"), None);
+    assert_eq!(resolver.load("/a//b"), None);
+    assert_eq!(resolver.load("no_slash"), None);
+}
+
+fn syntax_error(text: &str) -> (usize, usize, SyntaxErrorKind) {
+    let e = File::parse(text).unwrap_err();
+    (e.span.start.line, e.span.start.column, e.kind)
+}
+
+#[test]
+fn syntax_errors() {
+    use SyntaxErrorKind::*;
+    assert_eq!(
+        syntax_error("Import /Arith.\nAxim foo: ∀ x: ℤ, x < x + 1."),
+        (2, 1, UnknownSentence("Axim".to_string()))
+    );
+    assert_eq!(
+        syntax_error("Axiom foo ∀ x: ℤ, x < x + 1."),
+        (1, 11, Expected("`:` after the name"))
+    );
+    assert_eq!(
+        syntax_error("Suggest goal apply foo Destruct."),
+        (1, 14, Expected("`;` after the tactic"))
+    );
+    assert_eq!(
+        syntax_error("Suggest goal apply foo; Destroy."),
+        (1, 25, UnknownSuggClass("Destroy".to_string()))
+    );
+    assert_eq!(
+        syntax_error("Theorem foo: ∀ x: ℤ, x < x + 1.\nProof. intros. lia."),
+        (1, 1, MissingQed)
+    );
+    assert_eq!(
+        syntax_error("Theorem foo: ∀ x: ℤ, x < x + 1.\n  intros. lia. Qed."),
+        (2, 3, Expected("`Proof`"))
+    );
+    assert_eq!(
+        syntax_error("Axiom foo: ∀ x: ℤ, (x < x + 1."),
+        (1, 20, UnbalancedBracket('('))
+    );
+    assert_eq!(
+        syntax_error("Axiom foo: ∀ x: ℤ, x < x + 1"),
+        (1, 1, MissingDot)
+    );
+    assert_eq!(
+        syntax_error("Import /Arith\nAxiom x: ℤ."),
+        (2, 1, Expected("`/` in the library name"))
+    );
+    assert!(matches!(
+        Engine::default().check_library_text("/Test", "Import /Arith.\nAxiom x: ℤ"),
+        Err(Error::LibrarySyntax(_, _))
+    ));
+}
+
+#[test]
+fn periods_inside_sentences() {
+    let file = File::parse(
+        "Definition dot := '.'.\nTheorem foo: ∀ x: ℤ, x < x + 1.\nProof. intros. lia. Qed.\n\
+         Axiom bar: P (a.b) -> Q (x. y) [0, n).",
+    )
+    .unwrap();
+    match &file.0[..] {
+        [Sentence::Definition { body, .. }, Sentence::Theorem { proof, .. }, Sentence::Axiom(sig)] =>
+        {
+            assert_eq!(body, "'.'");
+            assert_eq!(proof, &["intros", "lia"]);
+            assert_eq!(sig.ty, "P (a.b) -> Q (x. y) [0, n)");
         }
+        x => panic!("bad parse {x:?}"),
+    }
+}
+
+#[test]
+fn structured_proofs() {
+    let proof = |body: &str| {
+        format!(
+            "Theorem and3: ∀ A B C: U, A -> B -> C -> C ∧ (B ∧ A).
+            Proof. intros A B C a b c. apply and_intro. {body} Qed."
+        )
+    };
+    let check = |body: &str| {
+        let mut eng = Engine::default();
+        eng.load_library("/Arith").unwrap();
+        eng.check_library_text("/Test", &proof(body)).unwrap()
+    };
+    let file = File::parse(&proof("{ apply and_intro. all: assumption. } apply c.")).unwrap();
+    match &file.0[..] {
+        [Sentence::Theorem { proof, .. }] => assert_eq!(
+            proof,
+            &[
+                "intros A B C a b c",
+                "apply and_intro",
+                "{",
+                "apply and_intro",
+                "all: assumption",
+                "}",
+                "apply c"
+            ]
+        ),
+        x => panic!("bad parse {x:?}"),
+    }
+    for body in [
+        "- apply and_intro. + apply a. + apply b. - apply c.",
+        "{ apply and_intro. all: assumption. } apply c.",
+        "2: apply c. apply and_intro; assumption.",
+    ] {
+        let reports = check(body);
+        assert!(reports[0].error.is_none(), "{body}");
+    }
+    let reports = check("- apply and_intro. + apply a. - apply c.");
+    let error = reports[0].error.as_ref().unwrap();
+    assert_eq!(error.tactic.as_deref(), Some("- apply c"));
+}
+
+#[test]
+fn implicit_arguments() {
+    let mut libs = MemoryResolver::new();
+    libs.insert(
+        "/Pairs",
+        "Import /Arith.
+        Axiom fst {A B: U}: A ∧ B -> A.
+        Axiom #2 snd: ∀ A B: U, A ∧ B -> B.
+        Axiom fst_pair {A: U} {B: U}: ∀ a: A, ∀ b: B, fst (a, b) = a.
+        Definition swap {A B: U} := λ p: A ∧ B, (snd p, fst p).
+        Theorem fst_swap: ∀ a b: ℤ, fst (swap (a, b)) = snd (a, b).
+        Proof. intros. unfold swap. apply fst_pair. Qed.",
+    );
+    let mut eng = Engine::default();
+    eng.add_library_resolver(libs);
+    let checks = eng.check_library_text("/Test", "Import /Pairs.").unwrap();
+    assert!(checks.is_empty());
+    let reports = eng
+        .check_library_text(
+            "/Test2",
+            "Theorem fst_swap2: ∀ a b: ℤ, fst (swap (a, b)) = snd (a, b). \
+             Proof. intros. unfold swap. apply fst_pair. Qed.",
+        )
+        .unwrap();
+    assert!(reports[0].error.is_none(), "{:?}", reports[0].error);
+    // hidden arguments are not printed
+    assert_eq!(
+        eng.check("fst_swap").unwrap(),
+        "∀ a b: ℤ, fst (swap (a, b)) = snd (a, b)"
+    );
+    assert!(matches!(
+        File::parse("Axiom foo {A B U}: A.").map(|_| ()),
+        Err(e) if e.kind == SyntaxErrorKind::Expected("`:` after the arguments")
+    ));
+}
+
+#[test]
+fn inductive_declarations() {
+    let mut eng = Engine::default();
+    eng.load_library("/Arith").unwrap();
+    let reports = eng
+        .check_library_text(
+            "/Test",
+            "Inductive nat := | O: nat | S: nat -> nat.
+            Inductive tree (A: U) := leaf: tree A | node: tree A -> A -> tree A -> tree A.
+            Theorem nat_cases: ∀ n: nat, n = O ∨ ∃ m: nat, n = S m.
+            Proof.
+                apply nat_ind. intros. apply or_intror. apply (ex_intro ? ? x). apply eq_refl.
+                apply or_introl. apply eq_refl.
+            Qed.",
+        )
+        .unwrap();
+    assert!(reports[0].error.is_none(), "{:?}", reports[0].error);
+    for name in [
+        "nat",
+        "O",
+        "S",
+        "nat_rect",
+        "nat_ind",
+        "nat_rect_O",
+        "nat_rect_S",
+        "node",
+    ] {
+        assert_eq!(eng.kind_of_name(name), Some(NameKind::Inductive), "{name}");
+    }
+    assert_eq!(
+        eng.check("nat_rect").unwrap(),
+        "∀ P: nat → Universe, P O → (∀ x: nat, P x → P (S x)) → ∀ t: nat, P t"
+    );
+    assert!(eng
+        .check("nat_rect_S")
+        .unwrap()
+        .ends_with("nat_rect P case_O case_S (S x) = case_S x (nat_rect P case_O case_S x)"));
+    // nothing in the declarations is trusted as an axiom
+    let r = eng.assumptions_of("nat_ind").unwrap();
+    assert!(r.axioms.is_empty() && r.unchecked_theorems.is_empty());
+    let r = eng.assumptions_of("nat_cases").unwrap();
+    assert!(r
+        .axioms
+        .iter()
+        .all(|x| !x.starts_with("nat") && x != "O" && x != "S"));
+}
+
+#[test]
+fn inductive_positivity() {
+    let declare = |text: &str| {
+        let mut eng = Engine::default();
+        eng.check_library_text("/Test", text).map(|_| ())
+    };
+    assert!(declare("Inductive rose := node: (ℤ -> rose) -> rose.").is_ok());
+    assert!(matches!(
+        declare("Inductive bad := mk: (bad -> ℤ) -> bad."),
+        Err(Error::Inductive(InductiveError::NotStrictlyPositive(c))) if c == "mk"
+    ));
+    assert!(matches!(
+        declare("Import /List. Inductive rose := node: list rose -> rose."),
+        Err(Error::Inductive(InductiveError::NotStrictlyPositive(_)))
+    ));
+    assert!(matches!(
+        declare("Inductive foo := mk: ℤ -> ℤ."),
+        Err(Error::Inductive(InductiveError::BadConclusion(c))) if c == "mk"
+    ));
+    assert!(matches!(
+        declare("Inductive foo (A: U) := mk: foo ℤ."),
+        Err(Error::Inductive(InductiveError::BadConclusion(_)))
+    ));
+}
+
+#[test]
+fn transparency() {
+    let theorem = "Theorem pos: ∀ x: ℤ, 1 < x ∧ (∀ y: ℤ, 0 < y -> y | x -> y = 1 ∨ y = x) -> 0 < x.
+        Proof. intros x H. apply prime_is_positive. apply H. Qed.";
+    let check = |transparency: &str| {
+        let mut eng = Engine::default();
+        eng.load_library("/NumberTheory").unwrap();
+        let text = format!("{transparency} prime. {theorem}");
+        let reports = eng.check_library_text("/Test", &text).unwrap();
+        reports[0].error.is_none()
+    };
+    assert!(check("Transparent"));
+    assert!(check("OnDemand"));
+    assert!(!check("Opaque"));
+    let mut eng = Engine::default();
+    assert!(matches!(
+        eng.check_library_text("/Test", "Opaque plus."),
+        Err(Error::UnknownDefinition(x)) if x == "plus"
+    ));
+    assert!(matches!(
+        File::parse("Opaque prime plus.").map(|_| ()),
+        Err(e) if e.kind == SyntaxErrorKind::Expected("period after the name")
+    ));
+}
+
+#[test]
+fn universe_polymorphic_axiom() {
+    let text = |poly: &str| {
+        format!(
+            "Axiom box: U -> U. {poly}
+            Theorem box_universe: ∀ P: Universe1 -> U, P (box U) -> P (box U).
+            Proof. intros P H. apply H. Qed."
+        )
+    };
+    let mut eng = Engine::default();
+    let reports = eng
+        .check_library_text("/Test", &text("UniversePolymorphic box."))
+        .unwrap();
+    assert!(reports.iter().all(|x| x.error.is_none()));
+    assert!(Engine::default()
+        .check_library_text("/Test", &text(""))
+        .is_err());
+    assert!(matches!(
+        Engine::default().check_library_text("/Test", "UniversePolymorphic plus_x."),
+        Err(Error::UnknownName(x)) if x == "plus_x"
+    ));
+    // the statement is the same at every level
+    assert!(matches!(
+        Engine::default().check_library_text(
+            "/Test",
+            "Axiom zero_nat: ∀ x: ℤ, 0 ≤ x * x. UniversePolymorphic zero_nat."
+        ),
+        Err(Error::NotUniverseGeneric(x)) if x == "zero_nat"
+    ));
+}
+
+#[test]
+fn tactic_macros() {
+    let text = "Tactic split_hyp H l r := destruct H with (and_ind ? ?) to (l r).
+        Tactic solve := intros; repeat apply and_intro; first [assumption | lia].
+        Theorem and_swap: ∀ A B: U, A ∧ B -> B ∧ A.
+        Proof. intros A B H. split_hyp H a b. apply and_intro; assumption. Qed.
+        Theorem with_number: ∀ A: U, A -> (0 < 1 ∧ A) ∧ A.
+        Proof. solve. Qed.";
+    let mut eng = Engine::default();
+    eng.load_library("/Arith").unwrap();
+    let reports = eng.check_library_text("/Test", text).unwrap();
+    assert!(reports.iter().all(|x| x.error.is_none()));
+    let mut session = eng.interactive_session("∀ A B: U, A ∧ B -> A").unwrap();
+    session.run_tactic("intros A B H").unwrap();
+    assert!(session.run_tactic("split_hyp H a").is_err());
+    session.run_tactic("split_hyp H a b").unwrap();
+    session.run_tactic("solve").unwrap();
+    assert!(session.is_finished());
+}
+
+#[test]
+fn bad_tactic_macros() {
+    let declare = |text: &str| Engine::default().check_library_text("/Test", text);
+    assert!(matches!(
+        declare("Tactic foo := intros;."),
+        Err(Error::InvalidTacticMacro(x, _)) if x == "foo"
+    ));
+    assert!(matches!(
+        declare("Tactic foo := try foo."),
+        Err(Error::RecursiveTacticMacro(x)) if x == "foo"
+    ));
+    assert!(matches!(
+        declare("Tactic foo := bar || lia. Tactic bar := foo."),
+        Err(Error::RecursiveTacticMacro(x)) if x == "bar"
+    ));
+    // only the names of the tactics in the script count, not their arguments
+    assert!(declare("Tactic foo := apply bar. Tactic bar := lia.").is_ok());
+    assert!(matches!(
+        declare("Tactic foo := lia. Tactic foo := ring."),
+        Err(Error::DuplicateName(x)) if x == "foo"
+    ));
+    for name in ["lia", "Switch", "repeat"] {
+        assert!(matches!(
+            declare(&format!("Tactic {name} := intros.")),
+            Err(Error::DuplicateName(x)) if x == name
+        ));
     }
 }
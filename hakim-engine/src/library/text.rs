@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use lazy_static::lazy_static;
 
+use super::LibraryResolver;
+
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
@@ -47,3 +49,17 @@ pub fn load_text(name: &str) -> Option<&str> {
 pub fn all_names() -> impl Iterator<Item = &'static String> {
     LIB_TEXT_STORE.keys()
 }
+
+/// Libraries which are shipped with hakim
+#[derive(Debug, Clone, Copy)]
+pub struct BundledResolver;
+
+impl LibraryResolver for BundledResolver {
+    fn load(&self, name: &str) -> Option<String> {
+        load_text(name).map(|x| x.to_string())
+    }
+
+    fn names(&self) -> Vec<String> {
+        all_names().cloned().collect()
+    }
+}
@@ -1,5 +1,6 @@
 mod ast;
 mod binop;
+mod coercion;
 mod pretty_print;
 mod semantic_highlight;
 mod span_counter;
@@ -11,11 +12,13 @@ use std::collections::HashSet;
 use std::ops::Sub;
 
 use crate::library::prelude::z;
+use crate::report::{Message, Report, TermPrinter};
 use crate::term_ref;
 
 pub use self::ast::{ast_to_term, AstTerm};
 pub use self::binop::BinOp;
 use self::binop::ALL_BINOPS;
+pub use self::coercion::insert_coercions;
 #[cfg(test)]
 pub use self::pretty_print::structural_print;
 pub use self::pretty_print::{
@@ -24,7 +27,7 @@ pub use self::pretty_print::{
 };
 pub use self::semantic_highlight::{HighlightTag, HtmlRenderer};
 pub use self::span_counter::pos_of_span;
-pub use self::tokenizer::{is_valid_ident, is_whity_char};
+pub use self::tokenizer::{is_valid_ident, is_valid_ident_char, is_whity_char};
 pub use self::wild::{fix_wild_scope, InferGenerator};
 
 use self::{
@@ -45,10 +48,61 @@ pub enum Error {
     TokenizerError(String),
     BadSigma,
     InvalidUnitTuple,
+    /// `/` on operands which are not in `ℚ`
+    DivisionOfIntegers,
 }
 
 type Result<T> = std::result::Result<T, Error>;
 
+use Error::*;
+
+impl Report for Error {
+    fn code(&self) -> &'static str {
+        match self {
+            UnexpectedEOF => "E0101",
+            ExpectedIdentButGot(_) => "E0102",
+            ExpectedSignButGot(..) => "E0103",
+            ExpectedExprButGot(_) => "E0104",
+            UndefinedName(_) => "E0105",
+            BadChar(_) => "E0106",
+            InvalidUniverseIndex(_) => "E0107",
+            RemainTokens(_) => "E0108",
+            TokenizerError(_) => "E0109",
+            BadSigma => "E0110",
+            InvalidUnitTuple => "E0111",
+            DivisionOfIntegers => "E0112",
+        }
+    }
+
+    fn message(&self, _: TermPrinter) -> Message {
+        let text = |t: &Token| t.trimmed().1;
+        match self {
+            UnexpectedEOF => Message::new("unexpected_eof", vec![]),
+            ExpectedIdentButGot(t) => Message::new("expected_ident", vec![text(t)]),
+            ExpectedSignButGot(s, t) => Message::new("expected_sign", vec![s.clone(), text(t)]),
+            ExpectedExprButGot(t) => Message::new("expected_expr", vec![text(t)]),
+            UndefinedName(x) => Message::new("undefined_name", vec![x.clone()]),
+            BadChar(c) => Message::new("bad_char", vec![c.to_string()]),
+            InvalidUniverseIndex(x) => Message::new("invalid_universe_index", vec![x.clone()]),
+            RemainTokens(t) => Message::new("remain_tokens", vec![text(&t[0])]),
+            TokenizerError(x) => Message::new("tokenizer_error", vec![x.clone()]),
+            BadSigma => Message::new("bad_sigma", vec![]),
+            InvalidUnitTuple => Message::new("invalid_unit_tuple", vec![]),
+            DivisionOfIntegers => Message::new("division_of_integers", vec![]),
+        }
+    }
+
+    fn span(&self) -> Option<(usize, usize)> {
+        match self {
+            ExpectedIdentButGot(t) | ExpectedSignButGot(_, t) | ExpectedExprButGot(t) => {
+                Some(t.trimmed().0)
+            }
+            RemainTokens(t) => Some((t[0].trimmed().0 .0, t.last()?.span.1)),
+            _ => None,
+        }
+    }
+}
+
 pub struct ParserConfig {
     pub names_with_hidden_args: im::HashMap<String, usize>,
 }
@@ -52,6 +52,7 @@ macro_rules! binop {
 binop! {
     And, 80, Right, "∧";
     App, 1, Left, " ";
+    Div, 40, Left, "/";
     Divide, 70, No, "|";
     Eq, 70, No, "=";
     Ge, 70, No, "≥";
@@ -117,6 +118,7 @@ impl BinOp {
         match self {
             And => app_ref!(and(), l, r),
             App => app_ref!(l, r),
+            Div => app_ref!(qdiv(), l, r),
             Divide => app_ref!(divide(), l, r),
             Eq => {
                 let i = infer_cnt.generate();
@@ -146,7 +148,11 @@ impl BinOp {
                 let w = term_ref!(_ i);
                 app_ref!(inset(), w, l, r)
             }
-            Le => app_ref!(or(), app_ref!(lt(), l, r), app_ref!(eq(), z(), l, r)),
+            Le => {
+                let i = infer_cnt.generate();
+                let w = term_ref!(_ i);
+                app_ref!(or(), app_ref!(lt(), l, r), app_ref!(eq(), w, l, r))
+            }
             Lt => app_ref!(lt(), l, r),
             Minus => app_ref!(minus(), l, r),
             ModOf => app_ref!(mod_of(), l, r),
@@ -206,12 +212,13 @@ impl BinOp {
                     Term::Axiom { ty: _, unique_name } => match unique_name.as_str() {
                         "divide" => found!(op, Divide, op2),
                         "iff" => found!(op, Iff, op2),
-                        "plus" => found!(op, Plus, op2),
+                        "plus" | "qplus" => found!(op, Plus, op2),
                         "pow" => found!(op, Pow, op2),
-                        "minus" => found!(op, Minus, op2),
+                        "minus" | "qminus" => found!(op, Minus, op2),
                         "mod_of" => found!(op, ModOf, op2),
-                        "mult" => found!(op, Mult, op2),
-                        "lt" => found!(op, Lt, op2),
+                        "mult" | "qmult" => found!(op, Mult, op2),
+                        "qdiv" => found!(op, Div, op2),
+                        "lt" | "qlt" => found!(op, Lt, op2),
                         "or" => {
                             if let Some((a1, BinOp::Lt, b1)) = BinOp::detect(op) {
                                 if let Some((a2, BinOp::Eq, b2)) = BinOp::detect(op2) {
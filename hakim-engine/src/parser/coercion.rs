@@ -0,0 +1,316 @@
+//! Coercions between the number types. The notations are parsed to the operators
+//! on `ℤ`, and this pass moves them to the type of their operands, and lifts the
+//! operands of a smaller type, like `2 * x` which becomes `qmult (q_of_z 2) x` when
+//! `x: ℚ`. Natural numbers have no operators, so `n + 1` is `plus (z_of_n n) 1`.
+//! Integers have no division, so `/` needs an operand which is already in `ℚ`.
+
+use super::Error;
+use crate::{
+    app_ref,
+    brain::{Abstraction, Term, TermRef},
+    library::prelude::*,
+    term_ref,
+};
+
+/// The number types, from the smallest one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum NumberKind {
+    N,
+    Z,
+    Q,
+}
+
+use NumberKind::*;
+
+impl NumberKind {
+    fn of_type(ty: &Term) -> Option<Self> {
+        match ty {
+            Term::Axiom { unique_name, .. } => match unique_name.as_str() {
+                "ℕ" => Some(N),
+                "ℤ" => Some(Z),
+                "ℚ" => Some(Q),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Converts a term of this kind to the next bigger kind
+    fn lift(self, t: TermRef) -> Option<(TermRef, Self)> {
+        match self {
+            N => Some((app_ref!(z_of_n(), t), Z)),
+            Z => Some((app_ref!(q_of_z(), t), Q)),
+            Q => None,
+        }
+    }
+
+    /// The arithmetic operator `name` on this kind, which `name` is the operator on
+    /// any of the kinds.
+    fn operator(self, name: &str) -> Option<TermRef> {
+        Some(match (name, self) {
+            ("plus" | "qplus", Z) => plus(),
+            ("plus" | "qplus", Q) => qplus(),
+            ("minus" | "qminus", Z) => minus(),
+            ("minus" | "qminus", Q) => qminus(),
+            ("mult" | "qmult", Z) => mult(),
+            ("mult" | "qmult", Q) => qmult(),
+            ("lt" | "qlt", Z) => lt(),
+            ("lt" | "qlt", Q) => qlt(),
+            ("qdiv", Q) => qdiv(),
+            _ => return None,
+        })
+    }
+
+    /// The smallest kind which the operator `name` works on
+    fn operator_min(name: &str) -> Option<Self> {
+        match name {
+            "plus" | "qplus" | "minus" | "qminus" | "mult" | "qmult" | "lt" | "qlt" => Some(Z),
+            "qdiv" => Some(Q),
+            _ => None,
+        }
+    }
+}
+
+/// The marker which `↑ x` is parsed to, and is replaced by the coercion of `x` to the
+/// next bigger number type.
+pub(super) fn coercion_marker() -> TermRef {
+    term_ref!(axiom "↑", u())
+}
+
+/// If the term is a coercion between number types, returns the coerced term.
+pub(super) fn detect_coercion(term: &Term) -> Option<TermRef> {
+    if let Term::App { func, op } = term {
+        if let Term::Axiom { unique_name, .. } = func.as_ref() {
+            if unique_name == "z_of_n" || unique_name == "q_of_z" {
+                return Some(op.clone());
+            }
+        }
+    }
+    None
+}
+
+/// Lifts the term until it reaches the kind `to`, if it is smaller
+fn lift_to(mut t: TermRef, from: Option<NumberKind>, to: NumberKind) -> TermRef {
+    let Some(mut from) = from else {
+        return t;
+    };
+    while from < to {
+        match from.lift(t.clone()) {
+            Some((lifted, kind)) => {
+                t = lifted;
+                from = kind;
+            }
+            None => break,
+        }
+    }
+    t
+}
+
+struct Elaborator {
+    /// Types of the bound variables, the innermost last
+    var_types: Vec<TermRef>,
+    /// the first division which has no operand in `ℚ`
+    error: Option<Error>,
+}
+
+impl Elaborator {
+    fn abstraction(&mut self, abs: &Abstraction) -> Abstraction {
+        let var_ty = self.elaborate(&abs.var_ty).0;
+        self.var_types.push(var_ty.clone());
+        let body = self.elaborate(&abs.body).0;
+        self.var_types.pop();
+        Abstraction {
+            var_ty,
+            body,
+            hint_name: abs.hint_name.clone(),
+        }
+    }
+
+    /// Type of the head of an application, if it is known without type checking
+    fn head_type(&self, head: &Term) -> Option<TermRef> {
+        match head {
+            Term::Axiom { ty, .. } => Some(ty.clone()),
+            Term::Var { index } => {
+                let i = self.var_types.len().checked_sub(index + 1)?;
+                Some(self.var_types[i].clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn app(&mut self, head: &TermRef, args: &[TermRef]) -> (TermRef, Option<NumberKind>) {
+        let (head, _) = self.elaborate(head);
+        let mut args: Vec<_> = args.iter().map(|x| self.elaborate(x)).collect();
+        let rebuild = |head: TermRef, args: Vec<(TermRef, Option<NumberKind>)>| {
+            args.into_iter().fold(head, |f, (x, _)| app_ref!(f, x))
+        };
+        let name = match head.as_ref() {
+            Term::Axiom { unique_name, .. } => unique_name.as_str(),
+            _ => "",
+        };
+        if name == "↑" && args.len() == 1 {
+            let (t, kind) = args.pop().unwrap();
+            return match kind.and_then(|k| k.lift(t.clone())) {
+                Some((t, kind)) => (t, Some(kind)),
+                None => (t, kind),
+            };
+        }
+        if let Some(min) = NumberKind::operator_min(name).filter(|_| args.len() == 2) {
+            if name == "qdiv" && args.iter().all(|x| x.1.is_some_and(|k| k < Q)) {
+                self.error.get_or_insert(Error::DivisionOfIntegers);
+            }
+            let kind = args.iter().filter_map(|x| x.1).fold(min, |a, b| a.max(b));
+            if let Some(op) = kind.operator(name) {
+                let result = (name != "lt" && name != "qlt").then_some(kind);
+                let args = args
+                    .into_iter()
+                    .map(|(x, k)| (lift_to(x, k, kind), Some(kind)))
+                    .collect();
+                return (rebuild(op, args), result);
+            }
+        }
+        if name == "eq" && args.len() == 3 {
+            // an explicit type is kept, and the operands are lifted to it
+            let ty_kind = NumberKind::of_type(&args[0].0);
+            let kinds = args[1..].iter().filter_map(|x| x.1);
+            if let Some(kind) = ty_kind.or_else(|| kinds.max()) {
+                for x in &mut args[1..] {
+                    x.0 = lift_to(x.0.clone(), x.1, kind);
+                }
+            }
+            return (rebuild(head, args), None);
+        }
+        let Some(mut ty) = self.head_type(&head) else {
+            return (rebuild(head, args), None);
+        };
+        for (x, kind) in &mut args {
+            let Term::Forall(abs) = ty.as_ref() else {
+                return (rebuild(head, args), None);
+            };
+            if let Some(expected) = NumberKind::of_type(&abs.var_ty) {
+                *x = lift_to(x.clone(), *kind, expected);
+            }
+            ty = abs.body.clone();
+        }
+        (rebuild(head, args), NumberKind::of_type(&ty))
+    }
+
+    fn elaborate(&mut self, t: &TermRef) -> (TermRef, Option<NumberKind>) {
+        match t.as_ref() {
+            Term::Axiom { ty, .. } => (t.clone(), NumberKind::of_type(ty)),
+            Term::Var { .. } => {
+                let ty = self.head_type(t);
+                (t.clone(), ty.and_then(|x| NumberKind::of_type(&x)))
+            }
+            Term::Number { .. } => (t.clone(), Some(Z)),
+            Term::Universe { .. } | Term::Wild { .. } => (t.clone(), None),
+            Term::Forall(abs) => (TermRef::new(Term::Forall(self.abstraction(abs))), None),
+            Term::Fun(abs) => (TermRef::new(Term::Fun(self.abstraction(abs))), None),
+            Term::App { .. } => {
+                let mut head = t;
+                let mut args = vec![];
+                while let Term::App { func, op } = head.as_ref() {
+                    args.push(op.clone());
+                    head = func;
+                }
+                args.reverse();
+                self.app(head, &args)
+            }
+        }
+    }
+}
+
+/// Moves the arithmetic operators of the parsed term to the type of their operands,
+/// and inserts the coercions which the operands need.
+pub fn insert_coercions(term: TermRef) -> Result<TermRef, Error> {
+    let mut elaborator = Elaborator {
+        var_types: vec![],
+        error: None,
+    };
+    let term = elaborator.elaborate(&term).0;
+    match elaborator.error {
+        Some(e) => Err(e),
+        None => Ok(term),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{engine::Engine, parser::Error};
+
+    fn check(text: &str, structure: &str) {
+        let eng = Engine::default();
+        let term = eng.parse_text(text).unwrap();
+        assert_eq!(crate::parser::structural_print(&term), structure);
+    }
+
+    #[test]
+    fn integers_are_untouched() {
+        check("2 * 3 < 7", "((lt ((mult 2) 3)) 7)");
+    }
+
+    #[test]
+    fn lift_to_rationals() {
+        check(
+            "∀ x: ℚ, 2 * x < 1",
+            "(∀ x: ℚ, ((qlt ((qmult (q_of_z 2)) @0)) (q_of_z 1)))",
+        );
+        check(
+            "∀ x: ℚ, 1 / x = x",
+            "(∀ x: ℚ, (((eq ℚ) ((qdiv (q_of_z 1)) @0)) @0))",
+        );
+        check(
+            "∀ a: ℤ, ∀ x: ℚ, ↑ a ≤ x",
+            "(∀ a: ℤ, (∀ x: ℚ, ((or ((qlt (q_of_z @1)) @0)) (((eq ℚ) (q_of_z @1)) @0))))",
+        );
+    }
+
+    #[test]
+    fn naturals() {
+        check(
+            "∀ n: ℕ, 0 ≤ n",
+            "(∀ n: ℕ, ((or ((lt 0) (z_of_n @0))) (((eq ℤ) 0) (z_of_n @0))))",
+        );
+        check("∀ n m: ℕ, n = m", "(∀ n: ℕ, (∀ m: ℕ, (((eq ℕ) @1) @0)))");
+        check(
+            "∀ n: ℕ, ∀ x: ℚ, n < x",
+            "(∀ n: ℕ, (∀ x: ℚ, ((qlt (q_of_z (z_of_n @1))) @0)))",
+        );
+    }
+
+    #[test]
+    fn division() {
+        let eng = Engine::default();
+        for text in ["1 / 2", "∀ a: ℤ, a / 2 = a", "∀ n: ℕ, n / 2 = 1"] {
+            assert!(matches!(
+                eng.parse_text(text),
+                Err(crate::engine::Error::ParserError(Error::DivisionOfIntegers))
+            ));
+        }
+        check("↑ 1 / 2", "((qdiv (q_of_z 1)) (q_of_z 2))");
+    }
+
+    #[test]
+    fn explicit_type() {
+        // the type which is written is kept, even if it doesn't match the operands
+        check("∀ x: ℚ, eq ℚ x 2", "(∀ x: ℚ, (((eq ℚ) @0) (q_of_z 2)))");
+        let eng = Engine::default();
+        assert!(eng.parse_text("∀ x: ℚ, eq ℤ x 2").is_err());
+        check("∀ n: ℕ, eq ℤ n 2", "(∀ n: ℕ, (((eq ℤ) (z_of_n @0)) 2))");
+    }
+
+    #[test]
+    fn round_trip() {
+        let eng = Engine::default();
+        for text in [
+            "∀ x: ℚ, 2 * x < x / 3",
+            "∀ a: ℤ, ∀ x: ℚ, ↑ a ≤ x",
+            "∀ x: ℚ, - x + x = 0",
+            "∀ a b: ℤ, ↑ (a + b) = ↑ a + ↑ b",
+            "∀ n: ℕ, ↑ (↑ n) = ↑ (↑ n + 1) - 1",
+        ] {
+            let term = eng.parse_text(text).unwrap();
+            assert_eq!(eng.pretty_print(&term), text);
+        }
+    }
+}
@@ -1,8 +1,8 @@
-use std::{cmp::min, collections::HashSet, fmt::Display, rc::Rc};
+use std::{cmp::min, collections::HashSet, fmt::Display};
 
 use crate::{
     app_ref,
-    brain::{detect_char, detect_len, increase_foreign_vars},
+    brain::{detect_char, detect_len, increase_foreign_vars, level::Level},
     library::prelude,
     parser::{
         ast::{AstAbs, AstSet, AstSigma},
@@ -230,7 +230,12 @@ pub fn structural_print(term: &Term) -> String {
     fn f(w: &mut impl std::fmt::Write, term: &Term) -> Result<(), std::fmt::Error> {
         match term {
             Term::Axiom { unique_name, .. } => write!(w, "{unique_name}"),
-            Term::Universe { index } => write!(w, "Universe{index}"),
+            Term::Universe {
+                level: Level::Const(index),
+            } => write!(w, "Universe{index}"),
+            Term::Universe {
+                level: Level::Var { var, offset },
+            } => write!(w, "Universe?{var}+{offset}"),
             Term::Forall(abs) => g(w, '∀', abs),
             Term::Fun(abs) => g(w, 'λ', abs),
             Term::Var { index } => write!(w, "@{index}"),
@@ -250,12 +255,30 @@ pub fn structural_print(term: &Term) -> String {
     s
 }
 
+/// If the term is a name applied to exactly its hidden arguments, returns the name.
+fn head_with_hidden_args<'a>(term: &'a Term, c: &PrettyPrintConfig) -> Option<&'a Term> {
+    let mut head = term;
+    let mut args = 0;
+    while let Term::App { func, .. } = head {
+        head = func;
+        args += 1;
+    }
+    match head {
+        Term::Axiom { unique_name, .. }
+            if c.names_with_hidden_args.get(unique_name) == Some(&args) =>
+        {
+            Some(head)
+        }
+        _ => None,
+    }
+}
+
 pub fn term_to_ast(
     term: &Term,
     names: &mut (Vec<(String, usize, TermRef)>, impl Fn(&str) -> bool),
     c: &PrettyPrintConfig,
 ) -> AstTerm {
-    use super::{binop::BinOp, uniop::UniOp};
+    use super::{binop::BinOp, coercion::detect_coercion, uniop::UniOp};
     use AstTerm::*;
     fn detect_special(
         term: &Term,
@@ -283,7 +306,7 @@ pub fn term_to_ast(
                 exp.map(|x| term_to_ast(&x, names, c)).collect(),
             )));
         }
-        if let Some(x) = detect_tuple_items(Rc::new(term.clone())) {
+        if let Some(x) = detect_tuple_items(TermRef::new(term.clone())) {
             return Some(Tuple(
                 x.into_iter().map(|x| term_to_ast(&x, names, c)).collect(),
             ));
@@ -297,6 +320,11 @@ pub fn term_to_ast(
             }
             return Some(List(l.iter().map(|x| term_to_ast(x, names, c)).collect()));
         }
+        if let Some(t) = detect_coercion(term) {
+            if let Term::Number { value } = t.as_ref() {
+                return Some(Number(value.clone()));
+            }
+        }
         if let Some((op, t)) = UniOp::detect(term) {
             return Some(UniOp(op, Box::new(term_to_ast(&t, names, c))));
         }
@@ -359,7 +387,7 @@ pub fn term_to_ast(
         return value;
     }
     let uncurried = app_ref!(
-        increase_foreign_vars(Rc::new(term.clone()), 0),
+        increase_foreign_vars(TermRef::new(term.clone()), 0),
         term_ref!(v 0)
     );
     if detect_special(&uncurried, names, c).is_some() {
@@ -371,7 +399,15 @@ pub fn term_to_ast(
         Term::Axiom { unique_name, ty } => {
             Ident(unique_name.clone(), Some(HighlightTag::from_type(ty)))
         }
-        Term::Universe { index } => Universe(*index),
+        Term::Universe {
+            level: Level::Const(index),
+        } => Universe(*index),
+        Term::Universe {
+            level: Level::Var { var, offset: 0 },
+        } => Wild(Some(format!("u{var}"))),
+        Term::Universe {
+            level: Level::Var { var, offset },
+        } => Wild(Some(format!("u{var}+{offset}"))),
         Term::Forall(abs) => compress_abs(AbsSign::Forall, for_abs(abs, names, c)),
         Term::Fun(abs) => compress_abs(AbsSign::Fun, for_abs(abs, names, c)),
         Term::Var { index } => {
@@ -385,10 +421,8 @@ pub fn term_to_ast(
         }
         Term::Number { value } => Number(value.clone()),
         Term::App { func, op } => {
-            if let Term::Axiom { unique_name, .. } = func.as_ref() {
-                if let Some(1) = c.names_with_hidden_args.get(unique_name) {
-                    return term_to_ast(func, names, c);
-                }
+            if let Some(head) = head_with_hidden_args(term, c) {
+                return term_to_ast(head, names, c);
             }
             BinOp(
                 Box::new(term_to_ast(func, names, c)),
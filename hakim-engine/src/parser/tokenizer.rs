@@ -43,6 +43,16 @@ impl std::fmt::Debug for Token {
     }
 }
 
+impl Token {
+    /// The text of the token and its span, without the white spaces before it
+    pub fn trimmed(&self) -> ((usize, usize), String) {
+        let (l, r) = self.span;
+        let text = self.original_text.chars().skip(l).take(r - l);
+        let spaces = text.clone().take_while(|c| is_whity_char(*c)).count();
+        ((l + spaces, r), text.skip(spaces).collect())
+    }
+}
+
 use std::{
     fmt::{Display, Write},
     rc::Rc,
@@ -1,5 +1,6 @@
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum UniOp {
+    Coerce,
     Neg,
     Not,
 }
@@ -14,11 +15,16 @@ use crate::{
     library, term_ref,
 };
 
-use super::{binop::BinOp, InferGenerator, PrecLevel};
+use super::{
+    binop::BinOp,
+    coercion::{coercion_marker, detect_coercion},
+    InferGenerator, PrecLevel,
+};
 
 impl Display for UniOp {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
         f.write_str(match self {
+            Coerce => "↑",
             Neg => "-",
             Not => "~",
         })
@@ -29,6 +35,7 @@ impl UniOp {
     // Source: https://coq.inria.fr/library/Coq.Init.Notations.html
     pub fn prec(&self) -> PrecLevel {
         PrecLevel(match self {
+            Coerce => 0,
             Neg => 35,
             Not => 75,
         })
@@ -38,11 +45,13 @@ impl UniOp {
         match self {
             Not => term_ref!(forall t, library::prelude::false_ty()),
             Neg => app_ref!(library::prelude::minus(), term_ref!(n 0), t),
+            Coerce => app_ref!(coercion_marker(), t),
         }
     }
 
     pub fn from_str(op: &str) -> Option<Self> {
         Some(match op {
+            "↑" => Coerce,
             "-" => Neg,
             "~" => Not,
             _ => return None,
@@ -50,6 +59,9 @@ impl UniOp {
     }
 
     pub(crate) fn detect(term: &crate::brain::Term) -> Option<(Self, TermRef)> {
+        if let Some(t) = detect_coercion(term) {
+            return Some((Coerce, t));
+        }
         if let Some((a, op, b)) = BinOp::detect(term) {
             match op {
                 BinOp::Imply => {
@@ -60,6 +72,7 @@ impl UniOp {
                     }
                 }
                 BinOp::Minus => {
+                    let a = detect_coercion(&a).unwrap_or(a);
                     if let Term::Number { value } = a.as_ref() {
                         if value == &0.into() {
                             return Some((Neg, b));
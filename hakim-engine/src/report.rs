@@ -0,0 +1,216 @@
+//! Errors in the form which users see. Every layer reports its errors through
+//! this module, so it depends only on the terms.
+
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::brain::Term;
+
+/// Prints the terms which appear in an error message
+pub type TermPrinter<'a> = &'a dyn Fn(&Term) -> String;
+
+/// An error which can be shown to the user. Errors which wrap another error report
+/// the inner one.
+pub trait Report {
+    /// Stable identifier of the error, which doesn't change with the wording
+    fn code(&self) -> &'static str;
+    fn message(&self, print: TermPrinter) -> Message;
+    /// Characters of the parsed text which the error is about
+    fn span(&self) -> Option<(usize, usize)> {
+        None
+    }
+    /// Where the error happened, innermost first
+    fn context(&self, _print: TermPrinter) -> Vec<Message> {
+        vec![]
+    }
+}
+
+/// A message as a translation key and its arguments. The English text of each key is
+/// in `template`, where `{i}` stands for the i-th argument. Each message has its own
+/// key, so a key without a template is a bug, which the tests catch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Message {
+    pub key: &'static str,
+    pub args: Vec<String>,
+}
+
+impl Message {
+    pub fn new(key: &'static str, args: Vec<String>) -> Self {
+        Message { key, args }
+    }
+
+    pub fn english(&self) -> String {
+        let mut r = template(self.key).unwrap_or(self.key).to_string();
+        for (i, arg) in self.args.iter().enumerate() {
+            r = r.replace(&format!("{{{i}}}"), arg);
+        }
+        r
+    }
+}
+
+fn template(key: &str) -> Option<&'static str> {
+    Some(match key {
+        // parser
+        "unexpected_eof" => "unexpected end of the text",
+        "expected_ident" => "expected a name, but got `{0}`",
+        "expected_sign" => "expected `{0}`, but got `{1}`",
+        "expected_expr" => "expected an expression, but got `{0}`",
+        "undefined_name" => "undefined name `{0}`",
+        "bad_char" => "invalid character literal `{0}`",
+        "invalid_universe_index" => "invalid universe index `{0}`",
+        "remain_tokens" => "unexpected `{0}` after the end of the expression",
+        "tokenizer_error" => "invalid token: {0}",
+        "bad_sigma" => "the range of a sigma should be like `[a, b)`",
+        "invalid_unit_tuple" => "empty parentheses are not a valid expression",
+        "division_of_integers" => {
+            "integers have no division, use `↑` to move an operand of `/` to ℚ"
+        }
+        // brain
+        "foreign_variable" => "the term contains the unbound variable {0}",
+        "type_mismatch" => "`{0}` doesn't match `{1}`",
+        "is_not_func" => "`{0}` is applied to an argument, but its type `{1}` is not a function",
+        "contains_wild" => "the term contains a wildcard",
+        "is_not_universe" => "expected a type",
+        "loop_of_inference" => "the wildcard ?{0} should contain itself in `{1}`",
+        "wild_need_local_var" => "the wildcard ?{0} needs a variable which is bound inside it",
+        "universe_inconsistency" => "the universe levels {0} and {1} are inconsistent",
+        "in_matching" => "while matching `{0}` with `{1}`",
+        "in_typechecking" => "while type checking `{0}`",
+        // engine
+        "duplicate_name" => "the name `{0}` is already defined",
+        "invalid_ident_name" => "`{0}` is not a valid name",
+        "unknown_library" => "unknown library `{0}`",
+        "unknown_definition" => "`{0}` is not a definition",
+        "unknown_name" => "unknown name `{0}`",
+        "library_syntax" => "syntax error in library `{0}` at {1}",
+        "invalid_type_for_axiom" => "the type of the axiom `{0}` is not a type",
+        "goal_with_wildcard" => "the goal `{0}` contains a wildcard",
+        "incomplete_proof" => "the proof is incomplete",
+        "unknown_axiom_in_proof" => "the proof uses the unknown axiom `{0}`",
+        "library_proof" => "the proof of `{1}` in library `{0}` is not valid",
+        "in_tactic" => "in tactic `{0}`",
+        "bad_arity" => "the type of an inductive should be its parameters followed by `U`",
+        "bad_conclusion" => {
+            "the constructor `{0}` doesn't return the inductive applied to its parameters"
+        }
+        "not_strictly_positive" => {
+            "the inductive occurs in the constructor `{0}` in a position which is not \
+            strictly positive"
+        }
+        "invalid_tactic_macro" => "the script of the tactic `{0}` is not valid",
+        "not_universe_generic" => {
+            "the statement of `{0}` has no universe, so it can not be universe polymorphic"
+        }
+        "recursive_tactic_macro" => {
+            "the tactic `{0}` is used by itself or by a tactic declared before it"
+        }
+        // tactics
+        "unknown_tactic" => "unknown tactic `{0}`",
+        "disabled_tactic" => "the tactic `{0}` is disabled",
+        "unknown_hyp" => "unknown hypothesis `{0}`",
+        "rewrite_expects_eq" => "rewrite expects an equality, but got `{0}`",
+        "intros_expects_forall" => "intros expects forall",
+        "ring_expects_eq" => "ring only works on an equality",
+        "ring_expects_integers" => "ring only works on an equality of integers",
+        "bad_arg_count" => "wrong number of arguments for `{0}`",
+        "bad_arg" => "invalid argument `{1}` for `{0}`",
+        "can_not_solve" => "`{0}` can not solve the goal",
+        "can_not_undo" => "there is nothing to undo",
+        "can_not_redo" => "there is nothing to redo",
+        "empty_tactic" => "the tactic is empty",
+        "invalid_goal_number" => "there is no goal {0}, since there are {1} goals",
+        "hyp_is_from_lib" => "the hypothesis `{0}` is from the library and can not be changed",
+        "context_depend_on_hyp" => "`{1}` depends on the hypothesis `{0}`",
+        "can_not_find_instance" => "some arguments of `{0}` can not be inferred",
+        "term_is_not_type" => "`{0}` is not a type",
+        "no_progress" => "the tactic doesn't change the goals",
+        "no_focused_goal" => "there is no goal in focus, close the bullet or the brace",
+        "unfinished_focus" => "the focused goal is not solved yet",
+        "no_open_brace" => "there is no `{` to close",
+        "unknown_history_node" => "there is no state {0} in the history",
+        "unfinished_proof" => "the proof is not finished",
+        _ => return None,
+    })
+}
+
+/// An error in the form which front ends show. It can be serialized, and the message
+/// and the context can be translated by their keys.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorReport {
+    pub code: &'static str,
+    /// The English text of the message
+    pub text: String,
+    pub message: Message,
+    pub span: Option<(usize, usize)>,
+    pub context: Vec<Message>,
+}
+
+impl ErrorReport {
+    pub fn new(e: &(impl Report + ?Sized), print: TermPrinter) -> Self {
+        let message = e.message(print);
+        ErrorReport {
+            code: e.code(),
+            text: message.english(),
+            message,
+            span: e.span(),
+            context: e.context(print),
+        }
+    }
+}
+
+impl Display for ErrorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error[{}]: {}", self.code, self.text)?;
+        for x in &self.context {
+            write!(f, "\n    {}", x.english())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::template;
+
+    /// Keys which are written in the sources, as the first argument of the functions
+    /// which build the messages
+    fn keys_in(dir: &Path, keys: &mut Vec<String>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                keys_in(&path, keys);
+                continue;
+            }
+            // this file has the templates, and the builders in this test
+            if path.extension().is_none_or(|x| x != "rs") || path.ends_with("src/report.rs") {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path).unwrap();
+            for builder in ["Message::new(", "msg(", "BadGoal(", "BadHyp("] {
+                for (i, _) in text.match_indices(builder) {
+                    let rest = text[i + builder.len()..].trim_start();
+                    let Some(rest) = rest.strip_prefix('"') else {
+                        continue;
+                    };
+                    keys.push(rest[..rest.find('"').unwrap()].to_string());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn all_keys_have_template() {
+        let mut keys = vec![];
+        keys_in(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut keys,
+        );
+        assert!(keys.len() > 50);
+        for key in keys {
+            assert!(template(&key).is_some(), "the key `{key}` has no template");
+        }
+    }
+}
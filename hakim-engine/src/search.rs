@@ -69,7 +69,7 @@ pub fn search(engine: &Engine, query: &str) -> Result<Vec<String>> {
 
 #[cfg(test)]
 mod tests {
-    use std::panic::catch_unwind;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
 
     use crate::engine::Engine;
 
@@ -85,10 +85,10 @@ mod tests {
         let eng = build_engine();
         let r = eng.search(query).unwrap();
         for x in r {
-            let r = catch_unwind(|| {
+            let r = catch_unwind(AssertUnwindSafe(|| {
                 let ty = eng.calc_type_and_infer(&x).unwrap();
                 format!("{}: {:?}\n", x, ty);
-            });
+            }));
             r.unwrap_or_else(|_| panic!("broken search item showing in {x}"));
         }
     }
@@ -135,7 +135,17 @@ mod tests {
 
     #[test]
     fn dont_stack_overflow() {
-        check_search("?x -> ?x -> ?x -> ?x", "if_f");
+        check_search(
+            "?x -> ?x -> ?x -> ?x",
+            r#"
+            eq_ind
+            if_f
+            nat_induction
+            ℕ_ind
+            ℕ_rect
+            ℕ_rect_succ
+            "#,
+        );
     }
 
     #[test]
@@ -11,6 +11,8 @@ use hakim_engine::{
     engine::Engine,
     interactive::{tactic::Error, Session, Suggestion},
     notation_list,
+    report::ErrorReport,
+    LibraryResolver,
 };
 use wasm_bindgen::prelude::*;
 
@@ -30,6 +32,22 @@ extern "C" {
 #[derive(Default, Serialize, Deserialize)]
 pub struct Instance {
     session: Option<Session>,
+    #[serde(skip)]
+    resolvers: Vec<CallbackResolver>,
+}
+
+/// Resolves libraries by calling a JS function, which takes the name of a library and
+/// returns its text, or `undefined` if it doesn't have it.
+#[derive(Debug, Clone)]
+struct CallbackResolver(js_sys::Function);
+
+impl LibraryResolver for CallbackResolver {
+    fn load(&self, name: &str) -> Option<String> {
+        self.0
+            .call1(&JsValue::NULL, &JsValue::from_str(name))
+            .ok()?
+            .as_string()
+    }
 }
 
 #[wasm_bindgen(start)]
@@ -58,38 +76,36 @@ enum Monitor {
     },
 }
 
+/// Runs the tactic, and asks the user for the instance which the tactic can not find.
+/// Fails only when `ask_question` doesn't give a string.
 #[async_recursion(?Send)]
-async fn run_tactic_inner(session: &mut Session, tactic: &str) -> Option<String> {
+async fn run_tactic_inner(
+    session: &mut Session,
+    tactic: &str,
+) -> Result<Option<ErrorReport>, JsValue> {
     match session.run_tactic(tactic) {
-        Ok(_) => None,
+        Ok(_) => Ok(None),
         Err(Error::CanNotFindInstance(e)) => {
             let mut qt = e.question_text();
             loop {
-                if let Some(ans) = ask_question(&qt).await.as_string() {
-                    if ans.trim() == "" {
-                        return None;
-                    } else {
-                        let e = e.clone();
-                        match e.tactic_by_answer(&ans) {
-                            Ok(t) => match run_tactic_inner(session, &t).await {
-                                None => return None,
-                                Some(e) => {
-                                    qt = format!("$error: {e:?}\n{qt}");
-                                    continue;
-                                }
-                            },
-                            Err(e) => {
-                                qt = format!("$error: {e:?}\n{qt}");
-                                continue;
-                            }
-                        }
-                    }
-                } else {
-                    return Some("bad output of ask_question".to_string());
+                let Some(ans) = ask_question(&qt).await.as_string() else {
+                    return Err("bad output of ask_question".into());
+                };
+                if ans.trim() == "" {
+                    return Ok(None);
                 }
+                let e = e.clone();
+                let report = match e.tactic_by_answer(&ans) {
+                    Ok(t) => match run_tactic_inner(session, &t).await? {
+                        None => return Ok(None),
+                        Some(report) => report,
+                    },
+                    Err(e) => session.initial_engine().report(&e),
+                };
+                qt = format!("$error: {report}\n{qt}");
             }
         }
-        Err(e) => Some(format!("{:?}", e)),
+        Err(e) => Ok(Some(session.initial_engine().report(&e))),
     }
 }
 
@@ -98,7 +114,26 @@ impl Instance {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
         start();
-        Instance { session: None }
+        Instance {
+            session: None,
+            resolvers: vec![],
+        }
+    }
+
+    fn engine(&self, params: &str) -> Engine {
+        let mut eng = Engine::new(params);
+        for resolver in &self.resolvers {
+            eng.add_library_resolver(resolver.clone());
+        }
+        eng
+    }
+
+    /// Adds a JS function as a source of libraries, for the sessions which are started
+    /// after this. The function takes a name like `/myteam/Algebra` and returns the
+    /// text of the library, or `undefined`.
+    #[wasm_bindgen]
+    pub fn add_library_callback(&mut self, callback: js_sys::Function) {
+        self.resolvers.push(CallbackResolver(callback));
     }
 
     #[wasm_bindgen]
@@ -120,15 +155,15 @@ impl Instance {
 
     #[wasm_bindgen]
     pub fn start_session(&mut self, goal: &str, libs: &str, params: &str) -> Option<String> {
-        let mut eng = Engine::new(params);
+        let mut eng = self.engine(params);
         for lib in libs.split(',') {
             if let Err(e) = eng.load_library(lib) {
-                return Some(format!("{:?}", e));
+                return Some(eng.report(&e).to_string());
             }
         }
         self.session = match eng.interactive_session(goal) {
             Ok(s) => Some(s),
-            Err(e) => return Some(format!("{:?}", e)),
+            Err(e) => return Some(eng.report(&e).to_string()),
         };
         None
     }
@@ -165,6 +200,18 @@ impl Instance {
         Some(s.natural())
     }
 
+    #[wasm_bindgen]
+    pub fn to_script(&self, name: &str) -> Result<Option<String>, String> {
+        let session = match &self.session {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+        match session.to_script(name) {
+            Ok(script) => Ok(Some(script)),
+            Err(e) => Err(session.initial_engine().report(&e).to_string()),
+        }
+    }
+
     pub fn try_auto_history(&self) -> JsValue {
         if let Some(x) = &self.session {
             serde_wasm_bindgen::to_value(&x.history_based_auto()).unwrap()
@@ -189,16 +236,24 @@ impl Instance {
         }
     }
 
+    /// Runs the tactic, and resolves to the error as a serialized `ErrorReport`, which
+    /// has a code, a translatable message and the span of parse errors, or to
+    /// `undefined` if the tactic runs without error.
     #[wasm_bindgen]
     pub fn run_tactic(&mut self, tactic: String) -> Promise {
         let this = unsafe { std::mem::transmute::<&mut Instance, &'static mut Instance>(self) };
         future_to_promise(async move {
             let session = match &mut this.session {
                 Some(s) => s,
-                None => return Ok("session not started".into()),
+                None => return Err("session not started".into()),
             };
-            let r = run_tactic_inner(session, &tactic).await;
-            Ok(r.into())
+            match run_tactic_inner(session, &tactic).await? {
+                None => Ok(JsValue::UNDEFINED),
+                Some(report) => {
+                    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+                    Ok(report.serialize(&serializer).unwrap())
+                }
+            }
         })
     }
 
@@ -211,6 +266,36 @@ impl Instance {
         serde_wasm_bindgen::to_value(&session.get_history()).unwrap()
     }
 
+    #[wasm_bindgen]
+    pub fn history_branches(&self) -> JsValue {
+        let branches = self.session.as_ref().map(|s| s.branches());
+        serde_wasm_bindgen::to_value(&branches).unwrap()
+    }
+
+    #[wasm_bindgen]
+    pub fn current_history_node(&self) -> Option<usize> {
+        Some(self.session.as_ref()?.current_node())
+    }
+
+    #[wasm_bindgen]
+    pub fn goto_history_node(&mut self, node: usize) -> Option<String> {
+        let session = self.session.as_mut()?;
+        let e = session.goto_node(node).err()?;
+        Some(session.initial_engine().report(&e).to_string())
+    }
+
+    #[wasm_bindgen]
+    pub fn compare_history_branches(&self, left: usize, right: usize) -> Result<JsValue, String> {
+        let session = match &self.session {
+            Some(s) => s,
+            None => return Ok(JsValue::UNDEFINED),
+        };
+        match session.compare_branches(left, right) {
+            Ok(diff) => Ok(serde_wasm_bindgen::to_value(&diff).unwrap()),
+            Err(e) => Err(session.initial_engine().report(&e).to_string()),
+        }
+    }
+
     async fn run_sugg(&mut self, sugg: Suggestion) -> Option<String> {
         let session = match &mut self.session {
             Some(s) => s,
@@ -229,7 +314,7 @@ impl Instance {
         }
         match session.run_suggestion(sugg, v) {
             Ok(_) => None,
-            Err(e) => Some(format!("{:?}", e)),
+            Err(e) => Some(session.initial_engine().report(&e).to_string()),
         }
     }
 
@@ -349,7 +434,20 @@ impl Instance {
         };
         match eng.check(query) {
             Ok(r) => r,
-            Err(e) => format!("{:?}", e),
+            Err(e) => eng.report(&e).to_string(),
+        }
+    }
+
+    /// Axioms, todos and trusted tactics that the theorem `name` of the library
+    /// `lib` transitively depends on. Proofs of the library are checked first.
+    pub fn assumptions_of(&self, lib: &str, name: &str) -> JsValue {
+        let mut eng = self.engine("");
+        if let Err(e) = eng.load_library_verified(lib) {
+            return JsValue::from_str(&eng.report(&e).to_string());
+        }
+        match eng.assumptions_of(name) {
+            Ok(r) => serde_wasm_bindgen::to_value(&r).unwrap(),
+            Err(e) => JsValue::from_str(&eng.report(&e).to_string()),
         }
     }
 
@@ -384,7 +482,7 @@ impl Instance {
                     .collect::<Vec<_>>();
                 serde_wasm_bindgen::to_value(&x).unwrap()
             }
-            Err(e) => JsValue::from_str(&format!("{:?}", e)),
+            Err(e) => JsValue::from_str(&eng.report(&e).to_string()),
         }
     }
 }
@@ -3,41 +3,16 @@ Import /Eq.
 Import /Induction.
 
 Theorem zero_lt_mult_pos: ∀ a b: ℤ, 0 < a -> 0 < b -> 0 < a * b.
-Proof.
-    intros.
-    add_hyp (1 ≤ b).
-    lia.
-    remove_hyp H0.
-    revert H1.
-    revert b.
-    apply z_induction_simple.
-    intros.
-    lia.
-    lia.
-Qed.
+Proof. intros. nia. Qed.
 
 Theorem zero_lt_mult_neg: ∀ a b: ℤ, a < 0 -> b < 0 -> 0 < a * b.
-Proof.
-    intros.
-    replace #1 (a * b) with ((-a) * (-b)).
-    lia.
-    apply zero_lt_mult_pos.
-    lia.
-    lia.
-Qed.
+Proof. intros. nia. Qed.
 Suggest goal apply zero_lt_mult_pos; 0 < a * b => 0 < a ∧ 0 < b.
 Suggest goal apply zero_lt_mult_neg; 0 < a * b => a < 0 ∧ b < 0.
 Theorem zero_le_mult_pos: ∀ a b: ℤ, 0 ≤ a -> 0 ≤ b -> 0 ≤ a * b.
-Proof.
-    intros.
-    revert H0.
-    revert b.
-    apply z_induction_simple.
-    intros.
-    lia.
-    lia.
-Qed.
-Todo zero_le_mult_neg: ∀ a b: ℤ, a ≤ 0 -> b ≤ 0 -> 0 ≤ a * b.
+Proof. intros. nia. Qed.
+Theorem zero_le_mult_neg: ∀ a b: ℤ, a ≤ 0 -> b ≤ 0 -> 0 ≤ a * b.
+Proof. intros. nia. Qed.
 Suggest goal apply zero_le_mult_pos; 0 ≤ a * b => 0 ≤ a ∧ 0 ≤ b.
 Suggest goal apply zero_le_mult_neg; 0 ≤ a * b => a ≤ 0 ∧ b ≤ 0.
 
@@ -58,9 +33,12 @@ Theorem plus_lt_l: ∀ a b c: ℤ, c + a < c + b -> a < b.
 Proof. intros. lia. Qed.
 Theorem lt_trans: ∀ n m p : ℤ, n < m -> m < p -> n < p.
 Proof. intros. lia. Qed.
-Todo multiply_lt_positive: ∀ a b c: ℤ, 0 < c -> c * a < c * b -> a < b.
-Todo multiply_lt_negative: ∀ a b c: ℤ, c < 0 -> c * a < c * b -> b < a.
-Todo lt_multiply_positive: ∀ a b c: ℤ, 0 < c -> a < b -> c * a < c * b.
+Theorem multiply_lt_positive: ∀ a b c: ℤ, 0 < c -> c * a < c * b -> a < b.
+Proof. intros. nia. Qed.
+Theorem multiply_lt_negative: ∀ a b c: ℤ, c < 0 -> c * a < c * b -> b < a.
+Proof. intros. nia. Qed.
+Theorem lt_multiply_positive: ∀ a b c: ℤ, 0 < c -> a < b -> c * a < c * b.
+Proof. intros. nia. Qed.
 Theorem lt_multiply_negative: ∀ a b c: ℤ, c < 0 -> a < b -> c * b < c * a.
 Proof.
     intros.
@@ -75,8 +53,10 @@ Theorem eq_plus_r: ∀ a b c: ℤ, a + c = b + c -> a = b.
 Proof. intros. lia. Qed.
 Theorem eq_plus_l: ∀ a b c: ℤ, c + a = c + b -> a = b.
 Proof. intros. lia. Qed.
-Todo eq_mult_r: ∀ a b c: ℤ, ~ c = 0 -> a * c = b * c -> a = b.
-Todo eq_mult_l: ∀ a b c: ℤ, ~ c = 0 -> c * a = c * b -> a = b.
+Theorem eq_mult_r: ∀ a b c: ℤ, ~ c = 0 -> a * c = b * c -> a = b.
+Proof. intros. nia. Qed.
+Theorem eq_mult_l: ∀ a b c: ℤ, ~ c = 0 -> c * a = c * b -> a = b.
+Proof. intros. nia. Qed.
 Theorem eq_subtract_positive_lt: ∀ a b c: ℤ, 0 < b -> a + b = c -> a < c.
 Proof. intros. lia. Qed.
 
@@ -88,16 +68,12 @@ Theorem le_plus_l: ∀ a b c: ℤ, a ≤ b -> c + a ≤ c + b.
 Proof. intros. lia. Qed.
 Theorem le_trans: ∀ n m p : ℤ, n ≤ m -> m ≤ p -> n ≤ p.
 Proof. intros. lia. Qed.
-Todo le_divide_positive: ∀ a b c: ℤ, 0 < c -> c * a ≤ c * b -> a ≤ b.
-Todo le_divide_negative: ∀ a b c: ℤ, c < 0 -> c * a ≤ c * b -> b ≤ a.
+Theorem le_divide_positive: ∀ a b c: ℤ, 0 < c -> c * a ≤ c * b -> a ≤ b.
+Proof. intros. nia. Qed.
+Theorem le_divide_negative: ∀ a b c: ℤ, c < 0 -> c * a ≤ c * b -> b ≤ a.
+Proof. intros. nia. Qed.
 Theorem le_multiply_positive: ∀ a b c: ℤ, 0 ≤ c -> a ≤ b -> c * a ≤ c * b.
-Proof.
-    intros a b.
-    apply z_induction_simple.
-    intros.
-    lia.
-    lia.
-Qed.
+Proof. intros. nia. Qed.
 Theorem le_multiply_negative: ∀ a b c: ℤ, c ≤ 0 -> a ≤ b -> c * b ≤ c * a.
 Proof.
     intros.
@@ -115,10 +91,8 @@ Axiom pow_unfold_l: ∀ a n: ℤ, 0 ≤ n -> a ^ (n + 1) = a * a ^ n.
 Theorem pow_unfold_r: ∀ a n: ℤ, 0 ≤ n -> a ^ (n + 1) = a ^ n * a.
 Proof.
     intros.
-    replace (a ^ n * a) with (a * a ^ n).
-    lia.
-    apply pow_unfold_l.
-    assumption.
+    rewrite (pow_unfold_l a n H).
+    ring.
 Qed.
 Suggest goal apply pow_unfold_l;  a ^ (n + 1) = a * a ^ n => 0 ≤ n.
 Suggest goal apply pow_unfold_r;  a ^ (n + 1) = a ^ n * a => 0 ≤ n.
@@ -133,9 +107,7 @@ Proof.
     add_hyp H0_o := (H0 H2).
     remove_hyp H2.
     remove_hyp H0.
-    replace #1 (a ^ (n + 1)) with (a * a ^ n).
-    apply pow_unfold_l.
-    assumption.
+    rewrite (pow_unfold_l a n H).
     apply zero_lt_mult_pos.
     assumption.
     assumption.
@@ -143,7 +115,27 @@ Proof.
     lia.
 Qed.
 
-Todo pow_lt_l: ∀ a b c: ℤ, 0 < a -> a < b -> 0 < c -> a ^ c < b ^ c.
+Theorem pow_lt_l: ∀ a b c: ℤ, 0 < a -> a < b -> 0 < c -> a ^ c < b ^ c.
+Proof.
+    intros a b c H H0 H1.
+    add_hyp (1 ≤ c).
+    lia.
+    remove_hyp H1.
+    revert H2.
+    revert c.
+    apply z_induction_simple.
+    intros.
+    add_hyp (0 ≤ n).
+    lia.
+    rewrite (pow_unfold_l a n H3).
+    rewrite (pow_unfold_l b n H3).
+    add_hyp (0 < a ^ n).
+    apply pow_pos.
+    lia.
+    lia.
+    nia.
+    lia.
+Qed.
 Todo pow_lt_r: ∀ a b c: ℤ, 0 < a -> a < b -> 1 < c -> c ^ a < c ^ b.
 
 
@@ -382,14 +382,13 @@ Qed.
 
 Todo bijection_append: ∀ T: U, ∀ t: list T, ∀ S: set (list T), finite S -> |{ l: list T | ∃ k: list T, l = t ++ k ∧ k ∈ S }| = |S|. 
 
-Todo count_of_lists: ∀ T: U, ∀ S: set T, finite S -> ∀ n, 0 ≤ n -> |{ l: list T | member_set l ⊆ S ∧ |l| = n }| = |S| ^ n.
-Theorem count_of_binary_lists: ∀ T: U, ∀ a b: T, ~ a = b -> ∀ n, 0 ≤ n -> |{ l: list T | member_set l ⊆ {a, b} ∧ |l| = n }| = 2 ^ n.
+Todo count_of_lists: ∀ T: U, ∀ S: set T, finite S -> ∀ n: ℕ, |{ l: list T | member_set l ⊆ S ∧ |l| = n }| = |S| ^ n.
+Theorem count_of_binary_lists: ∀ T: U, ∀ a b: T, ~ a = b -> ∀ n: ℕ, |{ l: list T | member_set l ⊆ {a, b} ∧ |l| = n }| = 2 ^ n.
 Proof.
     intros.
     replace #1 (2) with (|{a,b}|).
     Switch 1.
     apply count_of_lists.
-    assumption.
     Switch 1.
     apply eq_sym.
     replace #1 (2) with (|{a}|+|{b}|).
@@ -509,6 +508,13 @@ Proof.
     lia.
     lia.
     replace #1 ((Σ i in [0, n + 1) cm n i * a ^ (i + 1) * b ^ (n - (i + 1) + 1))) with ((Σ i in [0+1, n + 1 +1) cm n (i-1) * a ^ i * b ^ (n - i + 1))).
+    replace #1 ((Σ i in [0, n + 1) cm n i * a ^ (i + 1) * b ^ (n - (i + 1) + 1))) with ((Σ i in [0, n + 1) cm n (i + 1 - 1) * a ^ (i + 1) * b ^ (n - (i + 1) + 1))).
+    apply sigma_f_equal.
+    intros.
+    replace #1 (i + 1 - 1) with (i).
+    lia.
+    apply eq_refl.
+    lia.
     apply sigma_shift.
     replace #1 ((Σ i in [0 + 1, n + 1 + 1) cm n (i - 1) * a ^ i * b ^ (n - i + 1))) with ((Σ i in [0 + 1, n + 1) cm n (i - 1) * a ^ i * b ^ (n - i + 1)) + cm n n * a ^ (n+1) * b ^ (n - (n+1) + 1)).
     lia.
@@ -631,19 +637,19 @@ Proof.
     lia.
 Qed.
 
-Theorem sigma_cm_n: ∀ n, 0 ≤ n -> (Σ i in [0, n + 1) cm n i) = 2 ^ n.
+Theorem sigma_cm_n: ∀ n: ℕ, (Σ i in [0, n + 1) cm n i) = 2 ^ n.
 Proof.
     intros.
     add_from_lib binomial_coefficients.
     add_hyp binomial_coefficients_ex := (binomial_coefficients (n)).
-    Seq (add_hyp (0 ≤ n)) (remove_hyp binomial_coefficients_ex) (Switch 1) (add_hyp binomial_coefficients_ex_o := (binomial_coefficients_ex H0)) (remove_hyp H0) (remove_hyp binomial_coefficients_ex).
+    Seq (add_hyp (0 ≤ n)) (remove_hyp binomial_coefficients_ex) (Switch 1) (add_hyp binomial_coefficients_ex_o := (binomial_coefficients_ex H)) (remove_hyp H) (remove_hyp binomial_coefficients_ex).
     add_hyp binomial_coefficients_ex_o_ex := (binomial_coefficients_ex_o (1)).
     add_hyp binomial_coefficients_ex_o_ex_ex := (binomial_coefficients_ex_o_ex (1)).
     lia.
-    assumption.
+    lia.
 Qed.
 
-Todo count_of_paths: ∀ r, 0 ≤ r -> ∀ u, 0 ≤ u -> |{ l: list char | cnt 'r' l = r ∧ cnt 'u' l = u ∧ |l| = r + u }| = cm (r+u) u.
+Todo count_of_paths: ∀ r u: ℕ, |{ l: list char | cnt 'r' l = r ∧ cnt 'u' l = u ∧ |l| = r + u }| = cm (r+u) u.
 
 Axiom valid_paren: list char -> Universe.
 Axiom valid_paren_unfold: ∀ l, valid_paren l -> l = "" ∨ ∃ x y, valid_paren x ∧ valid_paren y ∧ l = "(" ++ x ++ ")" ++ y.
@@ -1,7 +1,7 @@
 Import /Graph.
 Import /Induction.
 
-Axiom #1 win_f: ∀ A: U, A -> dGraph A -> U.
+Axiom win_f {A: U}: A -> dGraph A -> U.
 Axiom win_f_def: ∀ A: U, ∀ G: dGraph A, well_founded_rel G -> ∀ a b: A, (a, b) ∈ dedge_set G -> ~ win_f b G -> win_f a G.
 Axiom lose_f_def: ∀ A: U, ∀ G: dGraph A, well_founded_rel G
     -> ∀ a: A, (∀ b: A, (a, b) ∈ dedge_set G -> win_f b G) -> ~ win_f a G.
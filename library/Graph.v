@@ -2,6 +2,6 @@ Import /Set.
 Import /Tuples.
 
 Axiom dGraph: U -> U.
-Axiom #1 dvertex_set: ∀ A: U, dGraph A -> set A.
-Axiom #1 dedge_set: ∀ A: U, dGraph A -> set (A ∧ A).
-Axiom #1 well_founded_rel: ∀ A: U, dGraph A -> U.
+Axiom dvertex_set {A: U}: dGraph A -> set A.
+Axiom dedge_set {A: U}: dGraph A -> set (A ∧ A).
+Axiom well_founded_rel {A: U}: dGraph A -> U.
@@ -42,3 +42,17 @@ Proof.
 Qed.
 
 Todo z_recursion_simple: ∀ k: ℤ, ∀ B: U, ∀ a: B, ∀ b: ℤ -> B -> B, ∃ f: ℤ -> B, f k = a ∧ ∀ n: ℤ, k ≤ n -> f (n+1) = b n (f n).
+
+Theorem nat_induction: ∀ P: ℤ -> U, P 0 -> (∀ n: ℕ, P n -> P (n + 1)) -> ∀ n: ℕ, P n.
+Proof.
+    intros P H H0.
+    apply ℕ_ind.
+    intros x H1.
+    replace #1 (↑ (succ x)) with (↑ x + 1).
+    lia.
+    apply H0.
+    assumption.
+    replace #1 (↑ zero) with (0).
+    lia.
+    assumption.
+Qed.